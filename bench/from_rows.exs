@@ -0,0 +1,20 @@
+# Compares building a frame from rows natively against transposing the
+# rows into columns in Elixir first.
+#
+#     mix run bench/from_rows.exs
+
+alias Explorer.PolarsBackend.Native
+
+rows =
+  for i <- 1..200_000 do
+    %{id: i, name: "user #{i}", score: i / 3, active: rem(i, 2) == 0}
+  end
+
+Benchee.run(%{
+  "df_from_rows" => fn ->
+    {:ok, _} = Native.df_from_rows(rows, nil, 1000, true)
+  end,
+  "DataFrame.new" => fn ->
+    Explorer.DataFrame.new(rows)
+  end
+})
@@ -0,0 +1,34 @@
+# Compares computing several quantiles per group in one pass against
+# one quantile expression per value.
+#
+#     mix run bench/quantiles.exs
+
+alias Explorer.PolarsBackend.Native
+
+rows = 5_000_000
+groups = 10
+
+df =
+  Explorer.DataFrame.new(
+    group: Enum.map(1..rows, &rem(&1, groups)),
+    value: Enum.map(1..rows, fn _ -> :rand.uniform() end)
+  )
+
+group_by = [Native.expr_column("group")]
+quantiles = [0.5, 0.9, 0.99]
+
+{:ok, one_pass} = Native.expr_quantiles(Native.expr_column("value"), quantiles, "nearest")
+
+separate =
+  for quantile <- quantiles do
+    Native.expr_alias(Native.expr_quantile(Native.expr_column("value"), quantile), "q#{quantile}")
+  end
+
+Benchee.run(%{
+  "expr_quantiles" => fn ->
+    {:ok, _} = Native.df_groupby_agg_with(df.data, group_by, [one_pass])
+  end,
+  "expr_quantile x3" => fn ->
+    {:ok, _} = Native.df_groupby_agg_with(df.data, group_by, separate)
+  end
+})
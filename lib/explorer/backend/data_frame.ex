@@ -29,7 +29,7 @@ defmodule Explorer.Backend.DataFrame do
               filename :: String.t(),
               dtypes :: list({column_name(), dtype()}),
               delimiter :: String.t(),
-              null_character :: String.t(),
+              null_character :: String.t() | [String.t()],
               skip_rows :: integer(),
               header? :: boolean(),
               encoding :: String.t(),
@@ -38,10 +38,26 @@ defmodule Explorer.Backend.DataFrame do
               infer_schema_length :: integer() | nil,
               parse_dates :: boolean()
             ) :: result(df)
-  @callback to_csv(df, filename :: String.t(), header? :: boolean(), delimiter :: String.t()) ::
+  @callback to_csv(
+              df,
+              filename :: String.t(),
+              header? :: boolean(),
+              delimiter :: String.t(),
+              null_character :: String.t(),
+              quote_style :: atom(),
+              datetime_format :: String.t() | nil,
+              compression :: nil | atom(),
+              batch_size :: pos_integer(),
+              append? :: boolean()
+            ) ::
               ok_result()
 
-  @callback from_parquet(filename :: String.t()) :: result(df)
+  @callback from_parquet(
+              filename :: String.t(),
+              columns :: list(String.t()) | list(atom()) | nil,
+              max_rows :: nil | integer(),
+              row_index :: nil | {String.t(), non_neg_integer()}
+            ) :: result(df)
   @callback to_parquet(
               df,
               filename :: String.t(),
@@ -49,6 +65,18 @@ defmodule Explorer.Backend.DataFrame do
             ) ::
               ok_result()
 
+  @callback to_parquet_partitioned(
+              df,
+              dir :: String.t(),
+              partition_by :: list(String.t()) | list(atom()),
+              compression :: {nil | atom(), nil | integer()},
+              max_rows_per_file :: nil | pos_integer()
+            ) ::
+              ok_result()
+
+  @callback dump_parquet(df, compression :: {nil | atom(), nil | integer()}) :: result(binary())
+  @callback load_parquet(contents :: binary()) :: result(df)
+
   @callback from_ipc(
               filename :: String.t(),
               columns :: list(String.t()) | list(atom()) | list(integer()) | nil
@@ -56,6 +84,20 @@ defmodule Explorer.Backend.DataFrame do
   @callback to_ipc(df, filename :: String.t(), compression :: {nil | atom(), nil | integer()}) ::
               ok_result()
 
+  @callback dump_ipc(df, compression :: {nil | atom(), nil | integer()}) :: result(binary())
+  @callback load_ipc(contents :: binary()) :: result(df)
+
+  @callback from_ipc_stream(
+              filename :: String.t(),
+              columns :: list(String.t()) | list(atom()) | list(integer()) | nil
+            ) :: result(df)
+  @callback to_ipc_stream(
+              df,
+              filename :: String.t(),
+              compression :: {nil | atom(), nil | integer()}
+            ) ::
+              ok_result()
+
   @callback from_ndjson(
               filename :: String.t(),
               infer_schema_length :: integer(),
@@ -71,7 +113,14 @@ defmodule Explorer.Backend.DataFrame do
   @callback from_tabular(Table.Reader.t()) :: df
   @callback from_series(map() | Keyword.t()) :: df
   @callback to_rows(df, atom_keys? :: boolean()) :: [map()]
-  @callback dump_csv(df, header? :: boolean(), delimiter :: String.t()) :: String.t()
+  @callback dump_csv(
+              df,
+              header? :: boolean(),
+              delimiter :: String.t(),
+              null_character :: String.t(),
+              quote_style :: atom(),
+              datetime_format :: String.t() | nil
+            ) :: String.t()
 
   # Introspection
 
@@ -173,7 +222,7 @@ defmodule Explorer.Backend.DataFrame do
         concat([
           line(),
           color("#{name} ", :map, inspect_opts),
-          color("#{Series.dtype(series)}", :atom, inspect_opts),
+          color(Explorer.Shared.dtype_to_string(Series.dtype(series)), :atom, inspect_opts),
           " ",
           data
         ])
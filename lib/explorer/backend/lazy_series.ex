@@ -25,6 +25,8 @@ defmodule Explorer.Backend.LazySeries do
     lt_eq: 2,
     is_nil: 1,
     is_not_nil: 1,
+    is_duplicated: 1,
+    is_unique: 1,
     binary_and: 2,
     binary_or: 2,
     add: 2,
@@ -37,11 +39,11 @@ defmodule Explorer.Backend.LazySeries do
     fill_missing: 2,
     fill_missing_with_value: 2,
     coalesce: 2,
-    cast: 2,
+    cast: 3,
     # Window functions
-    cumulative_max: 2,
-    cumulative_min: 2,
-    cumulative_sum: 2,
+    cumulative_max: 3,
+    cumulative_min: 3,
+    cumulative_sum: 3,
     window_max: 5,
     window_mean: 5,
     window_min: 5,
@@ -69,7 +71,10 @@ defmodule Explorer.Backend.LazySeries do
     quantile: 2,
     first: 1,
     last: 1,
-    count: 1
+    count: 1,
+    nil_count: 1,
+    all: 1,
+    any: 1
   ]
 
   @comparison_operations [:eq, :neq, :gt, :gt_eq, :lt, :lt_eq]
@@ -87,12 +92,16 @@ defmodule Explorer.Backend.LazySeries do
     :count,
     :first,
     :last,
-    :n_distinct
+    :n_distinct,
+    :nil_count
   ]
 
   @window_fun_operations [:window_max, :window_mean, :window_min, :window_sum]
   @cumulative_operations [:cumulative_max, :cumulative_min, :cumulative_sum]
 
+  @float_dtypes Explorer.Shared.float_types()
+  @numeric_dtypes @float_dtypes ++ Explorer.Shared.integer_types()
+
   @doc false
   def new(op, args, aggregation \\ false, window \\ false) do
     %__MODULE__{op: op, args: args, aggregation: aggregation, window: window}
@@ -108,8 +117,8 @@ defmodule Explorer.Backend.LazySeries do
   def dtype(%Series{} = s), do: s.dtype
 
   @impl true
-  def cast(%Series{} = s, dtype) when is_atom(dtype) do
-    args = [lazy_series!(s), dtype]
+  def cast(%Series{} = s, dtype, strict?) do
+    args = [lazy_series!(s), dtype, strict?]
     data = new(:cast, args, aggregations?(args), window_functions?(args))
 
     Backend.Series.new(data, dtype)
@@ -293,8 +302,8 @@ defmodule Explorer.Backend.LazySeries do
 
   for op <- @cumulative_operations do
     @impl true
-    def unquote(op)(%Series{} = series, reverse) do
-      args = [lazy_series!(series), reverse]
+    def unquote(op)(%Series{} = series, reverse, skip_nulls) do
+      args = [lazy_series!(series), reverse, skip_nulls]
 
       if aggregations?(args), do: raise_agg_inside_window(unquote(op))
 
@@ -309,6 +318,19 @@ defmodule Explorer.Backend.LazySeries do
             "which is a window function"
   end
 
+  @impl true
+  def all?(%Series{} = series), do: boolean_aggregation(:all, series)
+
+  @impl true
+  def any?(%Series{} = series), do: boolean_aggregation(:any, series)
+
+  defp boolean_aggregation(op, series) do
+    args = [lazy_series!(series)]
+    data = new(op, args, true, window_functions?(args))
+
+    Backend.Series.new(data, :boolean)
+  end
+
   @impl true
   def quantile(%Series{} = series, float) when is_float(float) do
     args = [lazy_series!(series), float]
@@ -323,7 +345,7 @@ defmodule Explorer.Backend.LazySeries do
     data = new(:coalesce, args, aggregations?(args), window_functions?(args))
 
     dtype =
-      if left.dtype in [:float, :integer] do
+      if left.dtype in @numeric_dtypes do
         resolve_numeric_dtype([left, right])
       else
         left.dtype
@@ -332,7 +354,7 @@ defmodule Explorer.Backend.LazySeries do
     Backend.Series.new(data, dtype)
   end
 
-  defp dtype_for_agg_operation(op, _) when op in [:count, :n_distinct], do: :integer
+  defp dtype_for_agg_operation(op, _) when op in [:count, :n_distinct, :nil_count], do: :integer
 
   defp dtype_for_agg_operation(op, series) when op in [:first, :last, :sum, :min, :max],
     do: series.dtype
@@ -349,8 +371,8 @@ defmodule Explorer.Backend.LazySeries do
       end
 
     case dtypes do
-      [dtype] when dtype in [:integer, :float] -> dtype
-      [_, _] -> :float
+      [dtype] when dtype in @numeric_dtypes -> dtype
+      [_, _] -> if Enum.any?(dtypes, &(&1 in @float_dtypes)), do: :float, else: :integer
     end
   end
 
@@ -396,6 +418,16 @@ defmodule Explorer.Backend.LazySeries do
     Backend.Series.new(data, :boolean)
   end
 
+  for op <- [:is_duplicated, :is_unique] do
+    @impl true
+    def unquote(op)(%Series{} = series) do
+      args = [lazy_series!(series)]
+      data = new(unquote(op), args, aggregations?(args), window_functions?(args))
+
+      Backend.Series.new(data, :boolean)
+    end
+  end
+
   @impl true
   def all_equal(%Series{} = left, %Series{} = right) do
     args = [lazy_series!(left), lazy_series!(right)]
@@ -410,7 +442,7 @@ defmodule Explorer.Backend.LazySeries do
 
     open = color("[", :list, opts)
     close = color("]", :list, opts)
-    dtype = color("#{Series.dtype(series)}", :atom, opts)
+    dtype = color(Explorer.Shared.dtype_to_string(Series.dtype(series)), :atom, opts)
 
     concat([
       color("LazySeries ", :atom, opts),
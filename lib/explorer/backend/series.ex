@@ -18,7 +18,7 @@ defmodule Explorer.Backend.Series do
   @callback from_list(list(), dtype()) :: s
   @callback to_list(s) :: list()
   @callback to_enum(s) :: Enumerable.t()
-  @callback cast(s, dtype) :: s
+  @callback cast(s, dtype, strict? :: boolean()) :: s
 
   # Introspection
 
@@ -52,12 +52,14 @@ defmodule Explorer.Backend.Series do
   @callback var(s) :: float() | lazy_s()
   @callback std(s) :: float() | lazy_s()
   @callback quantile(s, float()) :: number | Date.t() | NaiveDateTime.t() | lazy_s()
+  @callback all?(s) :: boolean() | lazy_s()
+  @callback any?(s) :: boolean() | lazy_s()
 
   # Cumulative
 
-  @callback cumulative_max(s, reverse? :: boolean()) :: s
-  @callback cumulative_min(s, reverse? :: boolean()) :: s
-  @callback cumulative_sum(s, reverse? :: boolean()) :: s
+  @callback cumulative_max(s, reverse? :: boolean(), skip_nulls? :: boolean()) :: s
+  @callback cumulative_min(s, reverse? :: boolean(), skip_nulls? :: boolean()) :: s
+  @callback cumulative_sum(s, reverse? :: boolean(), skip_nulls? :: boolean()) :: s
 
   # Local minima/maxima
 
@@ -100,6 +102,8 @@ defmodule Explorer.Backend.Series do
   @callback unordered_distinct(s) :: s
   @callback n_distinct(s) :: integer() | lazy_s()
   @callback count(s) :: df | lazy_s()
+  @callback is_duplicated(s) :: s
+  @callback is_unique(s) :: s
 
   # Rolling
 
@@ -121,6 +125,7 @@ defmodule Explorer.Backend.Series do
             ) :: s
   @callback is_nil(s) :: s
   @callback is_not_nil(s) :: s
+  @callback nil_count(s) :: integer() | lazy_s()
 
   # Escape hatch
 
@@ -145,7 +150,7 @@ defmodule Explorer.Backend.Series do
       when is_binary(backend) and (is_integer(n_rows) or is_nil(n_rows)) and is_list(opts) do
     open = color("[", :list, inspect_opts)
     close = color("]", :list, inspect_opts)
-    dtype = color("#{Series.dtype(series)}", :atom, inspect_opts)
+    dtype = color(Explorer.Shared.dtype_to_string(Series.dtype(series)), :atom, inspect_opts)
 
     data =
       container_doc(
@@ -191,6 +191,13 @@ defmodule Explorer.DataFrame do
         }
 
   @default_infer_schema_length 1000
+  @csv_write_defaults [
+    header: true,
+    delimiter: ",",
+    null_character: "",
+    quote_style: :necessary,
+    datetime_format: nil
+  ]
   @default_sample_nrows 5
 
   # Guards and helpers for columns
@@ -367,12 +374,12 @@ defmodule Explorer.DataFrame do
 
   ## Options
 
-    * `delimiter` - A single character used to separate fields within a record. (default: `","`)
+    * `delimiter` - A single byte used to separate fields within a record. (default: `","`)
     * `dtypes` - A list/map of `{"column_name", dtype}` tuples. Any non-specified column has its type
       imputed from the first 1000 rows. (default: `[]`)
     * `header` - Does the file have a header of column names as the first row or not? (default: `true`)
     * `max_rows` - Maximum number of lines to read. (default: `nil`)
-    * `null_character` - The string that should be interpreted as a nil value. (default: `"NA"`)
+    * `null_character` - The string, or list of strings, that should be interpreted as a nil value. (default: `"NA"`)
     * `skip_rows` - The number of lines to skip at the beginning of the file. (default: `0`)
     * `columns` - A list of column names or indexes to keep. If present, only these columns are read into the dataframe. (default: `nil`)
     * `infer_schema_length` Maximum number of rows read for schema inference. Setting this to nil will do a full table scan and will be slow (default: `1000`).
@@ -427,13 +434,27 @@ defmodule Explorer.DataFrame do
 
   @doc """
   Reads a parquet file into a dataframe.
+
+  The filename may also be a directory or a glob, such as `"data/**/*.parquet"`,
+  in which case all matching files are read as a single dataset. Hive-style
+  partition directories (`year=2023/month=1`) are turned into columns, with
+  their dtypes inferred from the values in the paths.
+
+  ## Options
+
+    * `columns` - A list of column names to keep. Only the requested columns
+      are decoded from the file. (default: `nil`)
+    * `max_rows` - Maximum number of rows to read. (default: `nil`)
+    * `row_index` - A `{name, offset}` tuple adding a column with the row
+      number, starting at `offset`. (default: `nil`)
   """
   @doc type: :io
   @spec from_parquet(filename :: String.t(), opts :: Keyword.t()) ::
           {:ok, DataFrame.t()} | {:error, term()}
   def from_parquet(filename, opts \\ []) do
+    opts = Keyword.validate!(opts, columns: nil, max_rows: nil, row_index: nil)
     backend = backend_from_options!(opts)
-    backend.from_parquet(filename)
+    backend.from_parquet(filename, opts[:columns], opts[:max_rows], opts[:row_index])
   end
 
   @doc """
@@ -452,31 +473,80 @@ defmodule Explorer.DataFrame do
         * `:zstd` (with levels -7-22)
         * `:lz4raw`.
 
+    * `partition_by` - A list of columns to partition the dataset by. When given,
+      `filename` is treated as a directory and one file is written per combination
+      of values, under hive-style directories such as `year=2023/month=1`.
+      Values containing `/` or `=` are percent-encoded. The partitioned dataset can
+      be read back by giving the directory to `from_parquet/2`. (default: `nil`)
+
+    * `max_rows_per_file` - The maximum number of rows in each file of a
+      partitioned dataset. (default: `nil`)
+
   """
   @doc type: :io
   @spec to_parquet(df :: DataFrame.t(), filename :: String.t()) ::
           {:ok, String.t()} | {:error, term()}
   def to_parquet(df, filename, opts \\ []) do
-    opts = Keyword.validate!(opts, compression: nil)
+    opts =
+      Keyword.validate!(opts, compression: nil, partition_by: nil, max_rows_per_file: nil)
+
     compression = parquet_compression(opts[:compression])
-    Shared.apply_impl(df, :to_parquet, [filename, compression])
+
+    case opts[:partition_by] do
+      nil ->
+        Shared.apply_impl(df, :to_parquet, [filename, compression])
+
+      partition_by ->
+        columns = to_existing_columns(df, partition_by)
+
+        Shared.apply_impl(df, :to_parquet_partitioned, [
+          filename,
+          columns,
+          compression,
+          opts[:max_rows_per_file]
+        ])
+    end
   end
 
-  defp parquet_compression(nil), do: {nil, nil}
+  @doc """
+  Writes a dataframe to a binary representation of a Parquet file.
+
+  The binary is identical to the contents written by `to_parquet/3`
+  with the same options.
+
+  ## Options
 
-  defp parquet_compression(algorithm) when algorithm in ~w(snappy gzip brotli zstd lz4raw)a do
-    {algorithm, nil}
+    * `compression` - The compression algorithm to use. See `to_parquet/3`
+      for the supported options.
+
+  """
+  @doc type: :io
+  @spec dump_parquet(df :: DataFrame.t(), opts :: Keyword.t()) ::
+          {:ok, binary()} | {:error, term()}
+  def dump_parquet(df, opts \\ []) do
+    opts = Keyword.validate!(opts, compression: nil)
+    compression = parquet_compression(opts[:compression])
+    Shared.apply_impl(df, :dump_parquet, [compression])
   end
 
-  for {algorithm, min, max} <- [{:gzip, 1, 9}, {:brotli, 1, 11}, {:zstd, -7, 22}] do
-    defp parquet_compression({unquote(algorithm), level}) do
-      if level in unquote(min)..unquote(max) or is_nil(level) do
-        {unquote(algorithm), level}
-      else
-        raise ArgumentError,
-              "#{unquote(algorithm)} compression level must be between #{unquote(min)} and #{unquote(max)} inclusive or nil, got #{level}"
-      end
-    end
+  @doc """
+  Reads a binary representation of a Parquet file into a dataframe.
+  """
+  @doc type: :io
+  @spec load_parquet(contents :: binary(), opts :: Keyword.t()) ::
+          {:ok, DataFrame.t()} | {:error, term()}
+  def load_parquet(contents, opts \\ []) do
+    backend = backend_from_options!(opts)
+    backend.load_parquet(contents)
+  end
+
+  # Codecs and levels are validated by the backend, which returns an error tuple.
+  defp parquet_compression(nil), do: {nil, nil}
+  defp parquet_compression(algorithm) when is_atom(algorithm), do: {algorithm, nil}
+
+  defp parquet_compression({algorithm, level})
+       when is_atom(algorithm) and (is_integer(level) or is_nil(level)) do
+    {algorithm, level}
   end
 
   defp parquet_compression(other) do
@@ -537,27 +607,165 @@ defmodule Explorer.DataFrame do
     backend = backend_from_options!(opts)
     compression = opts[:compression]
 
-    unless is_nil(compression) or compression in ~w(zstd lz4) do
+    unless is_nil(compression) or compression in ~w(zstd lz4)a do
       raise ArgumentError, "unsupported :compression #{inspect(compression)} for IPC"
     end
 
     backend.to_ipc(df, filename, {compression, nil})
   end
 
+  @doc """
+  Writes a dataframe to a binary representation of an IPC file.
+
+  The binary is identical to the contents written by `to_ipc/3`
+  with the same options.
+
+  ## Options
+
+    * `compression` - Sets the algorithm used to compress the IPC file.
+      It accepts `:zstd` or `:lz4` compression. (default: `nil`)
+  """
+  @doc type: :io
+  @spec dump_ipc(df :: DataFrame.t(), opts :: Keyword.t()) ::
+          {:ok, binary()} | {:error, term()}
+  def dump_ipc(df, opts \\ []) do
+    opts = Keyword.validate!(opts, compression: nil)
+    compression = opts[:compression]
+
+    unless is_nil(compression) or compression in ~w(zstd lz4)a do
+      raise ArgumentError, "unsupported :compression #{inspect(compression)} for IPC"
+    end
+
+    Shared.apply_impl(df, :dump_ipc, [{compression, nil}])
+  end
+
+  @doc """
+  Reads a binary representation of an IPC file into a dataframe.
+  """
+  @doc type: :io
+  @spec load_ipc(contents :: binary(), opts :: Keyword.t()) ::
+          {:ok, DataFrame.t()} | {:error, term()}
+  def load_ipc(contents, opts \\ []) do
+    backend = backend_from_options!(opts)
+    backend.load_ipc(contents)
+  end
+
+  @doc """
+  Reads an IPC stream file into a dataframe.
+
+  The IPC streaming format carries the schema followed by record batches
+  and can be consumed without seeking, which makes it suitable for pipes.
+
+  ## Options
+
+    * `columns` - List with the name or index of columns to be selected. Defaults to all columns.
+  """
+  @doc type: :io
+  @spec from_ipc_stream(filename :: String.t()) :: {:ok, DataFrame.t()} | {:error, term()}
+  def from_ipc_stream(filename, opts \\ []) do
+    opts = Keyword.validate!(opts, columns: nil)
+    backend = backend_from_options!(opts)
+
+    backend.from_ipc_stream(filename, opts[:columns])
+  end
+
+  @doc """
+  Similar to `from_ipc_stream/2` but raises if there is a problem reading the IPC stream file.
+  """
+  @doc type: :io
+  @spec from_ipc_stream!(filename :: String.t(), opts :: Keyword.t()) :: DataFrame.t()
+  def from_ipc_stream!(filename, opts \\ []) do
+    case from_ipc_stream(filename, opts) do
+      {:ok, df} -> df
+      {:error, error} -> raise "#{error}"
+    end
+  end
+
+  @doc """
+  Writes a dataframe to an IPC stream file.
+
+  ## Options
+
+    * `compression` - Sets the algorithm used to compress the IPC stream.
+      It accepts `:zstd` or `:lz4` compression. (default: `nil`)
+  """
+  @doc type: :io
+  @spec to_ipc_stream(df :: DataFrame.t(), filename :: String.t()) ::
+          {:ok, String.t()} | {:error, term()}
+  def to_ipc_stream(df, filename, opts \\ []) do
+    opts = Keyword.validate!(opts, compression: nil)
+    compression = opts[:compression]
+
+    unless is_nil(compression) or compression in ~w(zstd lz4)a do
+      raise ArgumentError, "unsupported :compression #{inspect(compression)} for IPC"
+    end
+
+    Shared.apply_impl(df, :to_ipc_stream, [filename, {compression, nil}])
+  end
+
   @doc """
   Writes a dataframe to a delimited file.
 
   ## Options
 
     * `header` - Should the column names be written as the first line of the file? (default: `true`)
-    * `delimiter` - A single character used to separate fields within a record. (default: `","`)
+    * `delimiter` - A single byte used to separate fields within a record. (default: `","`)
+    * `null_character` - The string used to represent nil values. (default: `""`)
+    * `quote_style` - When fields are quoted. One of `:necessary`, `:always`, `:never`
+      or `:non_numeric`. With `:necessary`, fields containing the delimiter, quotes or
+      newlines are quoted. (default: `:necessary`)
+    * `datetime_format` - A `strftime`-like format used for datetime columns. (default: `nil`)
+    * `compression` - Compresses the written file. It accepts `:gzip` or `:zstd`.
+      Compressed files can be read back with `from_csv/2`. (default: `nil`)
+    * `batch_size` - The number of rows written at once. Bounds the extra memory
+      used while writing. (default: `100_000`)
+    * `append` - Appends to the file instead of overwriting it. The header is only
+      written when the file is empty, otherwise the header already in the file must
+      match the columns of the dataframe. (default: `false`)
   """
   @doc type: :io
   @spec to_csv(df :: DataFrame.t(), filename :: String.t(), opts :: Keyword.t()) ::
           {:ok, String.t()} | {:error, term()}
   def to_csv(df, filename, opts \\ []) do
-    opts = Keyword.validate!(opts, header: true, delimiter: ",")
-    Shared.apply_impl(df, :to_csv, [filename, opts[:header], opts[:delimiter]])
+    opts =
+      Keyword.validate!(
+        opts,
+        [compression: nil, batch_size: 100_000, append: false] ++ @csv_write_defaults
+      )
+
+    compression = opts[:compression]
+    batch_size = opts[:batch_size]
+
+    unless is_nil(compression) or compression in ~w(gzip zstd)a do
+      raise ArgumentError, "unsupported :compression #{inspect(compression)} for CSV"
+    end
+
+    unless is_integer(batch_size) and batch_size > 0 do
+      raise ArgumentError,
+            "expected :batch_size to be a positive integer, got: #{inspect(batch_size)}"
+    end
+
+    Shared.apply_impl(
+      df,
+      :to_csv,
+      [filename | csv_write_args(opts)] ++ [compression, batch_size, opts[:append]]
+    )
+  end
+
+  defp csv_write_args(opts) do
+    quote_style = opts[:quote_style]
+
+    unless quote_style in [:necessary, :always, :never, :non_numeric] do
+      raise ArgumentError, "unsupported :quote_style #{inspect(quote_style)} for CSV"
+    end
+
+    [
+      opts[:header],
+      opts[:delimiter],
+      opts[:null_character],
+      quote_style,
+      opts[:datetime_format]
+    ]
   end
 
   @doc """
@@ -575,6 +783,9 @@ defmodule Explorer.DataFrame do
   @doc """
   Read a file of JSON objects or lists separated by new lines
 
+  Nested JSON objects are loaded as struct columns. If a line
+  cannot be parsed, the error includes its line number.
+
   ## Options
 
     * `batch_size` - Sets the batch size for reading rows.
@@ -618,7 +829,12 @@ defmodule Explorer.DataFrame do
   ## Options
 
     * `header` - Should the column names be written as the first line of the file? (default: `true`)
-    * `delimiter` - A single character used to separate fields within a record. (default: `","`)
+    * `delimiter` - A single byte used to separate fields within a record. (default: `","`)
+    * `null_character` - The string used to represent nil values. (default: `""`)
+    * `quote_style` - When fields are quoted. One of `:necessary`, `:always`, `:never`
+      or `:non_numeric`. With `:necessary`, fields containing the delimiter, quotes or
+      newlines are quoted. (default: `:necessary`)
+    * `datetime_format` - A `strftime`-like format used for datetime columns. (default: `nil`)
 
   ## Examples
 
@@ -629,8 +845,8 @@ defmodule Explorer.DataFrame do
   @doc type: :io
   @spec dump_csv(df :: DataFrame.t(), opts :: Keyword.t()) :: String.t()
   def dump_csv(df, opts \\ []) do
-    opts = Keyword.validate!(opts, header: true, delimiter: ",")
-    Shared.apply_impl(df, :dump_csv, [opts[:header], opts[:delimiter]])
+    opts = Keyword.validate!(opts, @csv_write_defaults)
+    Shared.apply_impl(df, :dump_csv, csv_write_args(opts))
   end
 
   ## Conversion
@@ -2561,9 +2777,12 @@ defmodule Explorer.DataFrame do
     [values_from, names_from] = to_existing_columns(df, [values_from, names_from])
     dtypes = df.dtypes
 
-    unless dtypes[values_from] in [:integer, :float, :date, :datetime] do
+    values_from_dtypes = [:date, :datetime] ++ Shared.integer_types() ++ Shared.float_types()
+
+    unless dtypes[values_from] in values_from_dtypes do
       raise ArgumentError,
-            "the values_from column must be numeric, but found #{dtypes[values_from]}"
+            "the values_from column must be numeric, but found " <>
+              Shared.dtype_to_string(dtypes[values_from])
     end
 
     id_columns = to_existing_columns(df, opts[:id_columns]) -- [names_from, values_from]
@@ -3228,7 +3447,7 @@ defmodule Explorer.DataFrame do
         _ -> slice(df, 0, @default_sample_nrows)
       end
 
-    types = Enum.map(df.names, &"\n<#{Atom.to_string(df.dtypes[&1])}>")
+    types = Enum.map(df.names, &"\n<#{Shared.dtype_to_string(df.dtypes[&1])}>")
 
     values =
       headers
@@ -2,4 +2,26 @@ defmodule Explorer.PolarsBackend do
   @moduledoc """
   The Explorer backend for Polars.
   """
+
+  alias Explorer.PolarsBackend.Native
+
+  @doc """
+  Returns the number of threads in the Polars thread pool.
+
+  The pool is started on first use, sized by the `POLARS_MAX_THREADS`
+  environment variable or by the number of cores.
+  """
+  @spec max_threads() :: pos_integer()
+  def max_threads, do: Native.max_threads()
+
+  @doc """
+  Starts the Polars thread pool with `n` threads.
+
+  The size of the pool cannot change once it is started, so this must
+  be called before any other Polars operation, for example when your
+  application starts. Returns an error if the pool is already running
+  with a different number of threads.
+  """
+  @spec set_max_threads(pos_integer()) :: {:ok, pos_integer()} | {:error, String.t()}
+  def set_max_threads(n) when is_integer(n) and n > 0, do: Native.set_max_threads(n)
 end
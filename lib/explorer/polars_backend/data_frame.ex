@@ -37,10 +37,11 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
     dtypes =
       Enum.map(dtypes, fn {column_name, dtype} ->
-        {column_name, Shared.internal_from_dtype(dtype)}
+        {column_name, Shared.cast_dtype(dtype)}
       end)
 
     {columns, with_projection} = column_list_check(columns)
+    delimiter = delimiter_byte!(delimiter)
 
     df =
       Native.df_read_csv(
@@ -55,7 +56,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
         columns,
         dtypes,
         encoding,
-        null_character,
+        List.wrap(null_character),
         parse_dates
       )
 
@@ -65,6 +66,13 @@ defmodule Explorer.PolarsBackend.DataFrame do
     end
   end
 
+  # The native readers and writers take the delimiter as a single byte.
+  defp delimiter_byte!(<<delimiter>>), do: delimiter
+
+  defp delimiter_byte!(delimiter) do
+    raise ArgumentError, "expected :delimiter to be a single byte, got: #{inspect(delimiter)}"
+  end
+
   defp column_list_check(list) do
     cond do
       is_nil(list) ->
@@ -87,10 +95,32 @@ defmodule Explorer.PolarsBackend.DataFrame do
   end
 
   @impl true
-  def to_csv(%DataFrame{data: df}, filename, header?, delimiter) do
-    <<delimiter::utf8>> = delimiter
-
-    case Native.df_to_csv_file(df, filename, header?, delimiter) do
+  def to_csv(
+        %DataFrame{data: df},
+        filename,
+        header?,
+        delimiter,
+        null_character,
+        quote_style,
+        datetime_format,
+        compression,
+        batch_size,
+        append?
+      ) do
+    delimiter = delimiter_byte!(delimiter)
+
+    case Native.df_to_csv_file(
+           df,
+           filename,
+           header?,
+           delimiter,
+           null_character,
+           Atom.to_string(quote_style),
+           datetime_format,
+           compression && Atom.to_string(compression),
+           batch_size,
+           append?
+         ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -111,14 +141,23 @@ defmodule Explorer.PolarsBackend.DataFrame do
   end
 
   @impl true
-  def dump_csv(%DataFrame{} = df, header?, delimiter) do
-    <<delimiter::utf8>> = delimiter
-    Shared.apply_dataframe(df, :df_to_csv, [header?, delimiter])
+  def dump_csv(%DataFrame{} = df, header?, delimiter, null_character, quote_style, datetime_format) do
+    delimiter = delimiter_byte!(delimiter)
+
+    Shared.apply_dataframe(df, :df_to_csv, [
+      header?,
+      delimiter,
+      null_character,
+      Atom.to_string(quote_style),
+      datetime_format
+    ])
   end
 
   @impl true
-  def from_parquet(filename) do
-    case Native.df_read_parquet(filename) do
+  def from_parquet(filename, columns, max_rows, row_index) do
+    columns = columns && Enum.map(columns, &to_string/1)
+
+    case Native.df_read_parquet(filename, columns, max_rows, row_index, true) do
       {:ok, df} -> {:ok, Shared.create_dataframe(df)}
       {:error, error} -> {:error, error}
     end
@@ -126,12 +165,51 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def to_parquet(%DataFrame{data: df}, filename, {compression, compression_level}) do
-    case Native.df_write_parquet(df, filename, Atom.to_string(compression), compression_level) do
+    compression = compression && Atom.to_string(compression)
+
+    case Native.df_write_parquet(df, filename, compression, compression_level) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
   end
 
+  @impl true
+  def to_parquet_partitioned(
+        %DataFrame{data: df},
+        dir,
+        partition_by,
+        {compression, compression_level},
+        max_rows_per_file
+      ) do
+    compression = compression && Atom.to_string(compression)
+
+    case Native.df_to_parquet_partitioned(
+           df,
+           dir,
+           Enum.map(partition_by, &to_string/1),
+           compression,
+           compression_level,
+           max_rows_per_file
+         ) do
+      {:ok, _} -> :ok
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @impl true
+  def dump_parquet(%DataFrame{data: df}, {compression, compression_level}) do
+    compression = compression && Atom.to_string(compression)
+    Native.df_dump_parquet(df, compression, compression_level)
+  end
+
+  @impl true
+  def load_parquet(contents) when is_binary(contents) do
+    case Native.df_load_parquet(contents) do
+      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
+      {:error, error} -> {:error, error}
+    end
+  end
+
   @impl true
   def from_ipc(filename, columns) do
     {columns, projection} = column_list_check(columns)
@@ -144,7 +222,43 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def to_ipc(%DataFrame{data: df}, filename, {compression, _level}) do
-    case Native.df_write_ipc(df, filename, Atom.to_string(compression)) do
+    compression = compression && Atom.to_string(compression)
+
+    case Native.df_write_ipc(df, filename, compression) do
+      {:ok, _} -> :ok
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @impl true
+  def dump_ipc(%DataFrame{data: df}, {compression, _level}) do
+    compression = compression && Atom.to_string(compression)
+    Native.df_dump_ipc(df, compression)
+  end
+
+  @impl true
+  def load_ipc(contents) when is_binary(contents) do
+    case Native.df_load_ipc(contents) do
+      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @impl true
+  def from_ipc_stream(filename, columns) do
+    {columns, projection} = column_list_check(columns)
+
+    case Native.df_read_ipc_stream(filename, columns, projection) do
+      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @impl true
+  def to_ipc_stream(%DataFrame{data: df}, filename, {compression, _level}) do
+    compression = compression && Atom.to_string(compression)
+
+    case Native.df_write_ipc_stream(df, filename, compression) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -219,17 +333,8 @@ defmodule Explorer.PolarsBackend.DataFrame do
   end
 
   @impl true
-  def to_rows(%DataFrame{data: polars_df, names: names} = df, atom_keys?) do
-    keys = if atom_keys?, do: Enum.map(names, &String.to_atom/1), else: df.names
-
-    names
-    |> Enum.map(fn name ->
-      {:ok, series} = Native.df_column(polars_df, name)
-      {:ok, list} = Native.s_to_list(series)
-      list
-    end)
-    |> Enum.zip_with(fn row -> keys |> Enum.zip(row) |> Map.new() end)
-  end
+  def to_rows(%DataFrame{} = df, atom_keys?),
+    do: Shared.apply_dataframe(df, :df_to_rows, [atom_keys? || false])
 
   # Introspection
 
@@ -335,7 +440,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
       end)
       |> Enum.unzip()
 
-    Shared.apply_dataframe(df, df, :df_sort, [columns, directions, groups])
+    Shared.apply_dataframe(df, df, :df_sort, [columns, directions, false, false, groups])
   end
 
   @impl true
@@ -467,7 +572,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
       |> Enum.map(fn {left, right} -> {right, left} end)
       |> Enum.unzip()
 
-    args = [left.data, left_on, right_on, "left", "_left"]
+    args = [left.data, left_on, right_on, "left", "_left", true]
     Shared.apply_dataframe(right, out_df, :df_join, args)
   end
 
@@ -475,7 +580,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
     how = Atom.to_string(how)
     {left_on, right_on} = Enum.unzip(on)
 
-    args = [right.data, left_on, right_on, how, "_right"]
+    args = [right.data, left_on, right_on, how, "_right", true]
     Shared.apply_dataframe(left, out_df, :df_join, args)
   end
 
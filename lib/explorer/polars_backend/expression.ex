@@ -6,15 +6,16 @@ defmodule Explorer.PolarsBackend.Expression do
   alias Explorer.DataFrame
   alias Explorer.Backend.LazySeries
   alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
 
   defstruct resource: nil, reference: nil
 
   @type t :: %__MODULE__{resource: binary(), reference: reference()}
 
   @window_operations [
-    cumulative_max: 2,
-    cumulative_min: 2,
-    cumulative_sum: 2,
+    cumulative_max: 3,
+    cumulative_min: 3,
+    cumulative_sum: 3,
     window_max: 5,
     window_mean: 5,
     window_min: 5,
@@ -27,25 +28,57 @@ defmodule Explorer.PolarsBackend.Expression do
                                        sort: 2,
                                        slice: 3,
                                        head: 2,
-                                       tail: 2,
-                                       peaks: 2,
-                                       fill_missing: 2
+                                       tail: 2
                                      ] ++
                                        @window_operations
-  @special_operations [cast: 2, column: 1] ++ @lazy_series_and_literal_args_funs
+  @special_operations [cast: 3, column: 1, divide: 2, fill_missing: 2, peaks: 2] ++
+                        @lazy_series_and_literal_args_funs
 
   # Some operations are special because they don't receive all args as lazy series.
   # We define them first.
 
-  def to_expr(%LazySeries{op: :cast, args: [lazy_series, dtype]}) do
+  def to_expr(%LazySeries{op: :cast, args: [lazy_series, dtype, strict?]}) do
     expr = to_expr(lazy_series)
-    Native.expr_cast(expr, Atom.to_string(dtype))
+
+    case Native.expr_cast(expr, Shared.cast_dtype(dtype), strict?) do
+      {:ok, expr} -> expr
+      {:error, error} -> raise ArgumentError, error
+    end
   end
 
   def to_expr(%LazySeries{op: :column, args: [name]}) do
     Native.expr_column(name)
   end
 
+  def to_expr(%LazySeries{op: :divide, args: [left, right]}) do
+    case Native.expr_divide(to_expr(left), to_expr(right), "infinity") do
+      {:ok, expr} -> expr
+      {:error, error} -> raise ArgumentError, error
+    end
+  end
+
+  def to_expr(%LazySeries{op: :fill_missing, args: [lazy_series, strategy]}) do
+    case Native.expr_fill_missing(to_expr(lazy_series), strategy) do
+      {:ok, expr} -> expr
+      {:error, error} -> raise ArgumentError, error
+    end
+  end
+
+  def to_expr(%LazySeries{op: :peaks, args: [lazy_series, min_or_max]}) do
+    expr = to_expr(lazy_series)
+
+    result =
+      case min_or_max do
+        :max -> Native.expr_peak_max(expr, "global")
+        :min -> Native.expr_peak_min(expr, "global")
+      end
+
+    case result do
+      {:ok, expr} -> expr
+      {:error, error} -> raise ArgumentError, error
+    end
+  end
+
   for {op, _arity} <- @lazy_series_and_literal_args_funs do
     expr_op = :"expr_#{op}"
 
@@ -20,12 +20,12 @@ defmodule Explorer.PolarsBackend.Native do
         _stop_after_n_rows,
         _skip_rows,
         _projection,
-        _sep,
+        _delimiter,
         _rechunk,
         _columns,
         _dtypes,
         _encoding,
-        _null_char,
+        _null_values,
         _parse_dates
       ),
       do: err()
@@ -33,7 +33,10 @@ defmodule Explorer.PolarsBackend.Native do
   def df_to_csv(
         _df,
         _has_headers,
-        _delimiter
+        _delimiter,
+        _null_value,
+        _quote_style,
+        _datetime_format
       ),
       do: err()
 
@@ -41,7 +44,13 @@ defmodule Explorer.PolarsBackend.Native do
         _df,
         _filename,
         _has_headers,
-        _delimiter
+        _delimiter,
+        _null_value,
+        _quote_style,
+        _datetime_format,
+        _compression,
+        _batch_size,
+        _append
       ),
       do: err()
 
@@ -58,71 +67,205 @@ defmodule Explorer.PolarsBackend.Native do
       ),
       do: err()
 
+  def df_from_xlsx(_filename, _sheet, _skip_rows, _header), do: err()
+  def df_to_xlsx(_df, _filename, _sheet_name), do: err()
   def df_column(_df, _name), do: err()
+  def df_correlation(_df, _columns, _method), do: err()
+  def df_describe_plan(_df, _exprs, _operation, _optimized), do: err()
   def df_drop(_df, _name), do: err()
   def df_drop_duplicates(_df, _maintain_order, _subset, _selection), do: err()
   def df_drop_nulls(_df, _subset), do: err()
+  def df_dump_ipc(_df, _compression), do: err()
+  def df_dump_parquet(_df, _compression, _compression_level), do: err()
+  def df_export_arrow_c(_df), do: err()
   def df_dtypes(_df), do: err()
+  def df_estimated_size(_df), do: err()
+  def df_size_bytes(df), do: df_estimated_size(df)
+  def df_n_chunks(_df), do: err()
+  def df_rechunk(_df), do: err()
   def df_filter_with(_df, _operation, _groups), do: err()
+  def df_from_rows(_rows, _schema, _infer_schema_length, _strict), do: err()
   def df_get_columns(_df), do: err()
+  def df_hash_rows(_df, _columns, _seed), do: err()
   def df_group_indices(_df, _column_names), do: err()
   def df_groupby_agg_with(_df, _groups_exprs, _aggs_pairs), do: err()
   def df_groups(_df, _column_names), do: err()
   def df_head(_df, _length, _groups), do: err()
   def df_height(_df), do: err()
   def df_hstack_many(_df, _others), do: err()
-  def df_join(_df, _other, _left_on, _right_on, _how, _suffix), do: err()
+  def df_import_arrow_c(_schema_ptr, _array_ptrs), do: err()
+  def df_join(_df, _other, _left_on, _right_on, _how, _suffix, _parallel), do: err()
+  def df_load_ipc(_binary), do: err()
+  def df_load_parquet(_binary), do: err()
   def df_mask(_df, _mask), do: err()
+  def df_take(_df, _indices), do: err()
   def df_melt(_df, _id_vars, _value_vars, _names_to, _values_to), do: err()
   def df_names(_df), do: err()
   def df_new(_columns), do: err()
+  def df_partition_by(_df, _by, _maintain_order, _include_key), do: err()
   def df_pivot_wider(_df, _id_columns, _pivot_column, _values_column), do: err()
   def df_read_ipc(_filename, _columns, _projection), do: err()
-  def df_read_parquet(_filename), do: err()
+  def df_read_ipc_stream(_filename, _columns, _projection), do: err()
+  def df_read_parquet(_filename, _columns, _n_rows, _row_index, _parallel), do: err()
   def df_select(_df, _selection), do: err()
   def df_select_at_idx(_df, _idx), do: err()
   def df_rename_columns(_df, _old_new_pairs), do: err()
+  def df_rename_many(_df, _pairs), do: err()
+  def df_rename_with_affix(_df, _prefix, _suffix), do: err()
   def df_shape(_df), do: err()
   def df_slice(_df, _offset, _length), do: err()
   def df_slice_by_indices(_df, _indices), do: err()
-  def df_sort(_df, _by, _reverse, _groups), do: err()
+  def df_sort(_df, _by, _reverse, _nulls_last, _maintain_order, _groups), do: err()
   def df_arrange_with(_df, _expressions, _directions, _groups), do: err()
   def df_tail(_df, _length, _groups), do: err()
   def df_to_dummies(_df, _columns), do: err()
+  def df_to_rows(_df, _atom_keys), do: err()
+  def df_to_rows_stream(_df, _chunk_size), do: err()
+  def df_to_rows_stream_next(_stream, _atom_keys), do: err()
   def df_to_lazy(_df), do: err()
+
+  def df_to_parquet_partitioned(
+        _df,
+        _dir,
+        _partition_by,
+        _compression,
+        _compression_level,
+        _max_rows_per_file
+      ),
+      do: err()
+
+  def df_unnest(_df, _columns), do: err()
   def df_vstack_many(_df, _others), do: err()
   def df_width(_df), do: err()
   def df_with_columns(_df, _columns), do: err()
   def df_with_column_exprs(_df, _exprs), do: err()
+  def df_with_row_count(_df, _name, _offset), do: err()
   def df_write_ipc(_df, _filename, _compression), do: err()
+  def df_write_ipc_stream(_df, _filename, _compression), do: err()
   def df_write_parquet(_df, _filename, _compression, _compression_level), do: err()
 
   # Expressions (for lazy queries)
   # We first generate functions for known operations.
-  for {op, arity} <- Explorer.Backend.LazySeries.operations() do
+  for {op, arity} <- Explorer.Backend.LazySeries.operations() -- [divide: 2] do
     args = Macro.generate_arguments(arity, __MODULE__)
     expr_op = :"expr_#{op}"
     def unquote(expr_op)(unquote_splicing(args)), do: err()
   end
 
   # Then we generate for some specific expressions
+  def expr_binary(_binary), do: err()
   def expr_boolean(_bool), do: err()
+  def expr_nil, do: err()
   def expr_date(_date), do: err()
   def expr_datetime(_datetime), do: err()
   def expr_float(_number), do: err()
   def expr_integer(_number), do: err()
+  def expr_is_in(_ex_expr, _values), do: err()
   def expr_string(_string), do: err()
   def expr_describe_filter_plan(_df, _expr), do: err()
+  def expr_describe_filter_plan_optimized(_df, _expr), do: err()
+  def expr_to_string(_ex_expr), do: err()
   def expr_alias(_ex_expr, _alias_name), do: err()
+  def expr_clip_to_dtype_range(_ex_expr, _dtype), do: err()
+  def expr_to_physical(_ex_expr), do: err()
+  def expr_shrink_dtype(_ex_expr), do: err()
+  def expr_replace_time_zone(_ex_expr, _time_zone), do: err()
+  def expr_convert_time_zone(_ex_expr, _time_zone), do: err()
+  def expr_round_datetime(_ex_expr, _every), do: err()
+  def expr_is_between_datetime(_ex_expr, _lower, _upper, _inclusive), do: err()
+  def expr_timestamp(_ex_expr, _time_unit), do: err()
+  def expr_total_days(_ex_expr), do: err()
+  def expr_total_milliseconds(_ex_expr), do: err()
+  def expr_total_seconds(_ex_expr), do: err()
+  def expr_winsorize(_ex_expr, _lower, _upper), do: err()
+  def expr_over(_ex_expr, _partition_by), do: err()
+  def expr_cumulative_sum_over(_ex_expr, _partition_by, _reverse), do: err()
+  def expr_rolling_sum_by(_ex_expr, _by, _window, _closed), do: err()
+  def expr_interpolate_by(_ex_expr, _by), do: err()
+  def expr_append(_left, _right), do: err()
+  def expr_if_else(_predicate, _on_true, _on_false), do: err()
+  def expr_divide(_left, _right, _mode), do: err()
+  def expr_replace_values(_ex_expr, _old, _new), do: err()
+  def expr_mad(_ex_expr), do: err()
+  def expr_top_k(_ex_expr, _k), do: err()
+  def expr_bottom_k(_ex_expr, _k), do: err()
+  def expr_first_non_null(_ex_expr), do: err()
+  def expr_last_non_null(_ex_expr), do: err()
+  def expr_search_sorted(_sorted, _element, _side), do: err()
+  def expr_set_sorted(_ex_expr, _descending), do: err()
+  def expr_rechunk(_ex_expr), do: err()
+  def expr_rle(_ex_expr), do: err()
+  def expr_sample_n(_ex_expr, _n, _with_replacement, _shuffle, _seed), do: err()
+  def expr_shuffle(_ex_expr, _seed), do: err()
+  def expr_window_skew(_ex_expr, _window_size, _min_periods, _bias), do: err()
+  def expr_dot(_left, _right), do: err()
+  def expr_abs_diff(_left, _right), do: err()
+  def expr_round_to_multiple(_ex_expr, _step), do: err()
+  def expr_forward_fill(_ex_expr, _limit), do: err()
+  def expr_backward_fill(_ex_expr, _limit), do: err()
+  def expr_map_dict(_ex_expr, _keys, _values, _default), do: err()
+  def expr_filter(_ex_expr, _predicate), do: err()
+  def expr_gather_every(_ex_expr, _n, _offset), do: err()
+  def expr_take(_ex_expr, _indices), do: err()
+  def expr_sort_by(_ex_expr, _by, _descending), do: err()
+  def expr_quantiles(_ex_expr, _quantiles, _strategy), do: err()
+  def expr_value_counts(_ex_expr, _sort, _parallel), do: err()
+  def expr_min_max_scale(_ex_expr), do: err()
+  def expr_checked_add(_left, _right), do: err()
+  def expr_checked_multiply(_left, _right), do: err()
+  def expr_bitwise_and(_left, _right), do: err()
+  def expr_bitwise_or(_left, _right), do: err()
+  def expr_bitwise_xor(_left, _right), do: err()
+  def expr_shift_left(_ex_expr, _amount), do: err()
+  def expr_shift_right(_ex_expr, _amount), do: err()
+  def expr_max_horizontal(_ex_exprs, _ignore_nulls), do: err()
+  def expr_min_horizontal(_ex_exprs, _ignore_nulls), do: err()
+  def expr_sum_horizontal(_ex_exprs, _ignore_nulls), do: err()
+  def expr_arg_max(_ex_expr), do: err()
+  def expr_arg_min(_ex_expr), do: err()
+  def expr_arg_unique(_ex_expr), do: err()
+  def expr_list_lengths(_ex_expr), do: err()
+  def expr_list_get(_ex_expr, _index), do: err()
+  def expr_list_join(_ex_expr, _separator), do: err()
+  def expr_list_contains(_ex_expr, _item), do: err()
+  def expr_str_count_matches(_ex_expr, _pattern, _literal), do: err()
+  def expr_extract_all(_ex_expr, _pattern), do: err()
+  def expr_str_ends_with(_ex_expr, _suffix), do: err()
+  def expr_str_head(_ex_expr, _n), do: err()
+  def expr_str_json_path_match(_ex_expr, _path), do: err()
+  def expr_str_reverse(_ex_expr), do: err()
+  def expr_str_starts_with(_ex_expr, _prefix), do: err()
+  def expr_str_tail(_ex_expr, _n), do: err()
+  def expr_hash(_ex_expr, _seed), do: err()
+  def expr_to_binary(_ex_expr), do: err()
+  def expr_from_binary(_binary), do: err()
+  def expr_peak_max(_ex_expr, _mode), do: err()
+  def expr_peak_min(_ex_expr, _mode), do: err()
+  def expr_repeat_by(_ex_expr, _by), do: err()
+  def expr_row_index(_offset), do: err()
 
   # LazyFrame
   def lf_collect(_df), do: err()
+  def lf_collect_streaming(_df), do: err()
   def lf_describe_plan(_df, _optimized), do: err()
   def lf_drop(_df, _columns), do: err()
   def lf_dtypes(_df), do: err()
   def lf_fetch(_df, _n_rows), do: err()
+  def lf_filter(_df, _expr), do: err()
   def lf_head(_df, _n_rows), do: err()
   def lf_names(_df), do: err()
+  def lf_scan_csv(
+        _filename,
+        _has_header,
+        _delimiter,
+        _skip_rows,
+        _null_values,
+        _infer_schema_length,
+        _max_rows
+      ),
+      do: err()
+
+  def lf_scan_parquet(_filename), do: err()
   def lf_select(_df, _columns), do: err()
   def lf_tail(_df, _n_rows), do: err()
 
@@ -130,13 +273,14 @@ defmodule Explorer.PolarsBackend.Native do
   def s_add(_s, _other), do: err()
   def s_and(_s, _s2), do: err()
   def s_append(_s, _other), do: err()
+  def s_concat(_series), do: err()
   def s_argsort(_s, _reverse), do: err()
   def s_as_str(_s), do: err()
-  def s_cast(_s, _dtype), do: err()
+  def s_cast(_s, _dtype, _strict), do: err()
   def s_coalesce(_s, _other), do: err()
-  def s_cum_max(_s, _reverse), do: err()
-  def s_cum_min(_s, _reverse), do: err()
-  def s_cum_sum(_s, _reverse), do: err()
+  def s_cum_max(_s, _reverse, _skip_nulls), do: err()
+  def s_cum_min(_s, _reverse, _skip_nulls), do: err()
+  def s_cum_sum(_s, _reverse, _skip_nulls), do: err()
   def s_distinct(_s), do: err()
   def s_div(_s, _other), do: err()
   def s_dtype(_s), do: err()
@@ -145,14 +289,20 @@ defmodule Explorer.PolarsBackend.Native do
   def s_fill_none_with_int(_s, _strategy), do: err()
   def s_fill_none_with_float(_s, _strategy), do: err()
   def s_fill_none_with_bin(_s, _strategy), do: err()
+  def s_from_binary(_binary, _dtype), do: err()
   def s_filter(_s, _filter), do: err()
+  def s_mask(_s, _mask), do: err()
+  def s_take(_s, _indices), do: err()
   def s_get(_s, _idx), do: err()
   def s_gt(_s, _rhs), do: err()
   def s_gt_eq(_s, _rhs), do: err()
   def s_head(_s, _length), do: err()
+  def s_is_duplicated(_s), do: err()
   def s_is_not_null(_s), do: err()
   def s_is_null(_s), do: err()
+  def s_is_unique(_s), do: err()
   def s_len(_s), do: err()
+  def s_size_bytes(_s), do: err()
   def s_lt(_s, _rhs), do: err()
   def s_lt_eq(_s, _rhs), do: err()
   def s_max(_s), do: err()
@@ -161,13 +311,17 @@ defmodule Explorer.PolarsBackend.Native do
   def s_min(_s), do: err()
   def s_mul(_s, _other), do: err()
   def s_n_chunks(_s), do: err()
+  def s_sorted_flag(_s), do: err()
   def s_name(_s), do: err()
   def s_neq(_s, _rhs), do: err()
+  def s_new_binary(_name, _val), do: err()
   def s_new_bool(_name, _val), do: err()
   def s_new_date32(_name, _val), do: err()
   def s_new_date64(_name, _val), do: err()
   def s_new_f64(_name, _val), do: err()
   def s_new_i64(_name, _val), do: err()
+  def s_new_list(_name, _val), do: err()
+  def s_new_u64(_name, _val), do: err()
   def s_new_str(_name, _val), do: err()
   def s_or(_s, _s2), do: err()
   def s_peak_max(_s), do: err()
@@ -191,13 +345,21 @@ defmodule Explorer.PolarsBackend.Native do
   def s_std(_s), do: err()
   def s_sub(_s, _other), do: err()
   def s_sum(_s), do: err()
+  def s_all(_s), do: err()
+  def s_any(_s), do: err()
   def s_tail(_s, _length), do: err()
   def s_take_every(_s, _n), do: err()
   def s_to_list(_s), do: err()
+  def s_to_binary(_s), do: err()
   def s_unordered_distinct(_s), do: err()
-  def s_value_counts(_s), do: err()
   def s_var(_s), do: err()
   def s_n_unique(_s), do: err()
+  def s_value_counts(_s, _sort, _parallel), do: err()
+  def s_to_dummies(_s, _drop_first, _include_null), do: err()
+  def s_null_count(_s), do: err()
+
+  def max_threads, do: err()
+  def set_max_threads(_n), do: err()
 
   defp err, do: :erlang.nif_error(:nif_not_loaded)
 end
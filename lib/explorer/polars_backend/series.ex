@@ -3,7 +3,6 @@ defmodule Explorer.PolarsBackend.Series do
 
   import Kernel, except: [length: 1]
 
-  alias Explorer.DataFrame
   alias Explorer.PolarsBackend.Native
   alias Explorer.PolarsBackend.Shared
   alias Explorer.Series
@@ -21,17 +20,49 @@ defmodule Explorer.PolarsBackend.Series do
   def from_list(data, type, name \\ "") when is_list(data) do
     series =
       case type do
-        :integer -> Native.s_new_i64(name, data)
+        :integer -> integers_from_list(data, name)
         :float -> Native.s_new_f64(name, data)
         :boolean -> Native.s_new_bool(name, data)
         :string -> Native.s_new_str(name, data)
-        :date -> Native.s_new_date32(name, data)
-        :datetime -> Native.s_new_date64(name, data)
+        :binary -> Native.s_new_binary(name, data)
+        :date -> unwrap!(Native.s_new_date32(name, data))
+        :datetime -> unwrap!(Native.s_new_date64(name, data))
+        {:f, 32} -> narrow!(Native.s_new_f64(name, data), type)
+        {_signedness, _bits} -> narrow!(integers_from_list(data, name), type)
       end
 
-    Explorer.Backend.Series.new(series, type)
+    # Integers above the i64 range are built as u64 series.
+    Shared.create_series(series)
   end
 
+  @min_i64 -9_223_372_036_854_775_808
+  @max_i64 9_223_372_036_854_775_807
+  @max_u64 18_446_744_073_709_551_615
+
+  # Integers that don't fit in an i64 can only be represented as u64.
+  defp integers_from_list(data, name) do
+    {min, max} = data |> Enum.filter(&is_integer/1) |> Enum.min_max(fn -> {0, 0} end)
+
+    cond do
+      min >= @min_i64 and max <= @max_i64 ->
+        Native.s_new_i64(name, data)
+
+      min >= 0 and max <= @max_u64 ->
+        Native.s_new_u64(name, data)
+
+      true ->
+        raise ArgumentError,
+              "no integer dtype fits values between #{min} and #{max}, " <>
+                "i64 holds #{@min_i64}..#{@max_i64} and u64 holds 0..#{@max_u64}"
+    end
+  end
+
+  defp narrow!(series, type),
+    do: unwrap!(Native.s_cast(series, Shared.internal_from_dtype(type), true))
+
+  defp unwrap!({:ok, series}), do: series
+  defp unwrap!({:error, error}), do: raise("#{error}")
+
   @impl true
   def to_list(series), do: Shared.apply_series(series, :s_to_list)
 
@@ -39,7 +70,12 @@ defmodule Explorer.PolarsBackend.Series do
   def to_enum(series), do: Explorer.PolarsBackend.Series.Iterator.new(series)
 
   @impl true
-  def cast(series, dtype), do: Shared.apply_series(series, :s_cast, [Atom.to_string(dtype)])
+  def cast(series, dtype, strict?) do
+    case Native.s_cast(series.data, Shared.cast_dtype(dtype), strict?) do
+      {:ok, series} -> Shared.create_series(series)
+      {:error, error} -> raise ArgumentError, error
+    end
+  end
 
   # Introspection
 
@@ -53,9 +89,14 @@ defmodule Explorer.PolarsBackend.Series do
   def memtype(series) do
     case Shared.apply_series(series, :s_dtype) do
       "u8" -> {:u, 8}
+      "u16" -> {:u, 16}
       "u32" -> {:u, 32}
+      "u64" -> {:u, 64}
+      "i8" -> {:s, 8}
+      "i16" -> {:s, 16}
       "i32" -> {:s, 32}
       "i64" -> {:s, 64}
+      "f32" -> {:f, 32}
       "f64" -> {:f, 64}
       "bool" -> {:u, 8}
       "str" -> :utf8
@@ -122,6 +163,12 @@ defmodule Explorer.PolarsBackend.Series do
   @impl true
   def sum(series), do: Shared.apply_series(series, :s_sum)
 
+  @impl true
+  def all?(series), do: Shared.apply_series(series, :s_all)
+
+  @impl true
+  def any?(series), do: Shared.apply_series(series, :s_any)
+
   @impl true
   def min(series), do: Shared.apply_series(series, :s_min)
 
@@ -147,16 +194,16 @@ defmodule Explorer.PolarsBackend.Series do
   # Cumulative
 
   @impl true
-  def cumulative_max(series, reverse?),
-    do: Shared.apply_series(series, :s_cum_max, [reverse?])
+  def cumulative_max(series, reverse?, skip_nulls?),
+    do: Shared.apply_series(series, :s_cum_max, [reverse?, skip_nulls?])
 
   @impl true
-  def cumulative_min(series, reverse?),
-    do: Shared.apply_series(series, :s_cum_min, [reverse?])
+  def cumulative_min(series, reverse?, skip_nulls?),
+    do: Shared.apply_series(series, :s_cum_min, [reverse?, skip_nulls?])
 
   @impl true
-  def cumulative_sum(series, reverse?),
-    do: Shared.apply_series(series, :s_cum_sum, [reverse?])
+  def cumulative_sum(series, reverse?, skip_nulls?),
+    do: Shared.apply_series(series, :s_cum_sum, [reverse?, skip_nulls?])
 
   # Local minima/maxima
 
@@ -216,8 +263,11 @@ defmodule Explorer.PolarsBackend.Series do
 
   def pow(left, exponent) when is_integer(exponent) and exponent >= 0 do
     cond do
-      Series.dtype(left) == :integer -> Shared.apply_series(left, :s_int_pow, [exponent])
-      Series.dtype(left) == :float -> Shared.apply_series(left, :s_pow, [exponent / 1])
+      Series.dtype(left) in Explorer.Shared.integer_types() ->
+        Shared.apply_series(left, :s_int_pow, [exponent])
+
+      Series.dtype(left) in Explorer.Shared.float_types() ->
+        Shared.apply_series(left, :s_pow, [exponent / 1])
     end
   end
 
@@ -300,14 +350,17 @@ defmodule Explorer.PolarsBackend.Series do
   def n_distinct(series), do: Shared.apply_series(series, :s_n_unique)
 
   @impl true
-  def count(%Series{data: polars_series}) do
-    df =
-      case Native.s_value_counts(polars_series) do
-        {:ok, polars_df} -> Shared.create_dataframe(polars_df)
-        {:error, error} -> raise "#{error}"
-      end
+  def is_duplicated(series), do: Shared.apply_series(series, :s_is_duplicated)
+
+  @impl true
+  def is_unique(series), do: Shared.apply_series(series, :s_is_unique)
 
-    DataFrame.rename(df, ["values", "counts"])
+  @impl true
+  def count(%Series{data: polars_series}) do
+    case Native.s_value_counts(polars_series, true, true) do
+      {:ok, polars_df} -> Shared.create_dataframe(polars_df)
+      {:error, error} -> raise "#{error}"
+    end
   end
 
   # Window
@@ -363,6 +416,9 @@ defmodule Explorer.PolarsBackend.Series do
   @impl true
   def is_not_nil(series), do: Shared.apply_series(series, :s_is_not_null)
 
+  @impl true
+  def nil_count(series), do: Shared.apply_series(series, :s_null_count)
+
   # Escape hatch
   @impl true
   def transform(series, fun), do: series |> Series.to_list() |> Enum.map(fun)
@@ -106,22 +106,43 @@ defmodule Explorer.PolarsBackend.Shared do
     Enum.map(dtypes, &normalise_dtype/1)
   end
 
-  def normalise_dtype("u8"), do: :integer
-  def normalise_dtype("u32"), do: :integer
-  def normalise_dtype("i32"), do: :integer
+  def normalise_dtype("u8"), do: {:u, 8}
+  def normalise_dtype("u16"), do: {:u, 16}
+  def normalise_dtype("u32"), do: {:u, 32}
+  def normalise_dtype("u64"), do: {:u, 64}
+  def normalise_dtype("i8"), do: {:s, 8}
+  def normalise_dtype("i16"), do: {:s, 16}
+  def normalise_dtype("i32"), do: {:s, 32}
   def normalise_dtype("i64"), do: :integer
+  def normalise_dtype("f32"), do: {:f, 32}
   def normalise_dtype("f64"), do: :float
   def normalise_dtype("bool"), do: :boolean
   def normalise_dtype("str"), do: :string
+  # Categoricals are decoded back to their string values.
+  def normalise_dtype("cat"), do: :string
+  def normalise_dtype("binary"), do: :binary
   def normalise_dtype("date"), do: :date
   def normalise_dtype("datetime[ms]"), do: :datetime
   def normalise_dtype("datetime[μs]"), do: :datetime
+  def normalise_dtype("datetime[ms, " <> _time_zone), do: :datetime
+  def normalise_dtype("datetime[μs, " <> _time_zone), do: :datetime
   def normalise_dtype("list[u32]"), do: :integer
 
+  def normalise_dtype("list[" <> inner),
+    do: {:list, inner |> String.slice(0..-2) |> normalise_dtype()}
+
   def internal_from_dtype(:integer), do: "i64"
   def internal_from_dtype(:float), do: "f64"
   def internal_from_dtype(:boolean), do: "bool"
   def internal_from_dtype(:string), do: "str"
+  def internal_from_dtype(:binary), do: "binary"
   def internal_from_dtype(:date), do: "date"
   def internal_from_dtype(:datetime), do: "datetime[μs]"
+  def internal_from_dtype({:s, bits}) when bits in [8, 16, 32], do: "i#{bits}"
+  def internal_from_dtype({:u, bits}) when bits in [8, 16, 32, 64], do: "u#{bits}"
+  def internal_from_dtype({:f, 32}), do: "f32"
+
+  # Casts name the 64-bit dtypes like Explorer does and narrow dtypes like Polars.
+  def cast_dtype(dtype) when is_atom(dtype), do: Atom.to_string(dtype)
+  def cast_dtype(dtype), do: internal_from_dtype(dtype)
 end
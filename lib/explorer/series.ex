@@ -8,8 +8,12 @@ defmodule Explorer.Series do
     * `:integer` - 64-bit signed integer
     * `:boolean` - Boolean
     * `:string` - UTF-8 encoded binary
+    * `:binary` - Raw binary that is not validated as UTF-8
     * `:date` - Date type that unwraps to `Elixir.Date`
     * `:datetime` - DateTime type that unwraps to `Elixir.NaiveDateTime`
+    * `{:s, 8}`, `{:s, 16}` and `{:s, 32}` - 8, 16 and 32-bit signed integers
+    * `{:u, 8}`, `{:u, 16}`, `{:u, 32}` and `{:u, 64}` - unsigned integers
+    * `{:f, 32}` - 32-bit floating point number
 
   A series must consist of a single data type only. Series are nullable, but may not consist only of
   nils.
@@ -27,7 +31,17 @@ defmodule Explorer.Series do
 
   @valid_dtypes Explorer.Shared.dtypes()
 
-  @type dtype :: :integer | :float | :boolean | :string | :date | :datetime
+  @type dtype ::
+          :integer
+          | :float
+          | :boolean
+          | :string
+          | :binary
+          | :date
+          | :datetime
+          | {:s, 8 | 16 | 32}
+          | {:u, 8 | 16 | 32 | 64}
+          | {:f, 32}
   @type t :: %Series{data: Explorer.Backend.Series.t(), dtype: dtype()}
   @type lazy_t :: %Series{data: Explorer.Backend.LazySeries.t(), dtype: dtype()}
 
@@ -38,9 +52,13 @@ defmodule Explorer.Series do
 
   @compile {:no_warn_undefined, Nx}
 
-  defguardp numeric_dtype?(dtype) when dtype in [:float, :integer]
-  defguardp numeric_or_bool_dtype?(dtype) when dtype in [:float, :integer, :boolean]
-  defguardp numeric_or_date_dtype?(dtype) when dtype in [:float, :integer, :date, :datetime]
+  @integer_dtypes Explorer.Shared.integer_types()
+  @numeric_dtypes Explorer.Shared.float_types() ++ @integer_dtypes
+
+  defguardp integer_dtype?(dtype) when dtype in @integer_dtypes
+  defguardp numeric_dtype?(dtype) when dtype in @numeric_dtypes
+  defguardp numeric_or_bool_dtype?(dtype) when dtype in [:boolean | @numeric_dtypes]
+  defguardp numeric_or_date_dtype?(dtype) when dtype in [:date, :datetime | @numeric_dtypes]
 
   @impl true
   def fetch(series, idx) when is_integer(idx), do: {:ok, fetch!(series, idx)}
@@ -147,6 +165,19 @@ defmodule Explorer.Series do
         [2022-04-13 21:00:42.000000]
       >
 
+  Binaries that are not valid UTF-8, such as hashes, can be kept as raw bytes with the `:binary` dtype.
+
+      iex> Explorer.Series.from_list([<<0xFF, 0xFE>>, nil], dtype: :binary) |> Explorer.Series.to_list()
+      [<<0xFF, 0xFE>>, nil]
+
+  Numbers can be stored with fewer bits, raising if a value does not fit the dtype.
+
+      iex> Explorer.Series.from_list([1, 2], dtype: {:s, 16})
+      #Explorer.Series<
+        s16[2]
+        [1, 2]
+      >
+
   Mixing non-numeric data types will raise an ArgumentError.
 
       iex> Explorer.Series.from_list([1, "a"])
@@ -156,13 +187,21 @@ defmodule Explorer.Series do
   @spec from_list(list :: list(), opts :: Keyword.t()) :: Series.t()
   def from_list(list, opts \\ []) do
     backend = backend_from_options!(opts)
-    type = Shared.check_types!(list)
-    {list, type} = Shared.cast_numerics(list, type)
+
+    {list, type} =
+      case opts[:dtype] do
+        # Raw bytes are not inferred, as they may not be valid UTF-8.
+        :binary -> {list, :binary}
+        _ -> Shared.cast_numerics(list, Shared.check_types!(list))
+      end
+
     series = backend.from_list(list, type)
 
     case {type, check_optional_dtype!(opts[:dtype])} do
       {t, t} -> series
       {_t, nil} -> series
+      # Values that do not fit a narrow dtype raise instead of becoming nil.
+      {t, {_, _} = narrow} when t in [:integer, :float] -> cast(series, narrow, strict: true)
       {_, other} -> cast(series, other)
     end
   end
@@ -276,6 +315,10 @@ defmodule Explorer.Series do
   @doc """
   Cast the series to another type.
 
+  ## Options
+
+    * `:strict` - When `true`, raises if any value cannot be cast. (default: `false`)
+
   ## Examples
 
       iex> s = Explorer.Series.from_list([1, 2, 3])
@@ -315,7 +358,7 @@ defmodule Explorer.Series do
         [2022-04-13 21:00:42.000000]
       >
 
-  `cast/2` will return the series as a no-op if you try to cast to the same dtype.
+  `cast/3` will return the series as a no-op if you try to cast to the same dtype.
 
       iex> s = Explorer.Series.from_list([1, 2, 3])
       iex> Explorer.Series.cast(s, :integer)
@@ -323,11 +366,29 @@ defmodule Explorer.Series do
         integer[3]
         [1, 2, 3]
       >
+
+  Values that cannot be cast become nil, unless the cast is strict.
+
+      iex> s = Explorer.Series.from_list(["1", "two", "3"])
+      iex> Explorer.Series.cast(s, :integer)
+      #Explorer.Series<
+        integer[3]
+        [1, nil, 3]
+      >
   """
   @doc type: :element_wise
-  @spec cast(series :: Series.t(), dtype :: dtype()) :: Series.t()
-  def cast(%Series{dtype: dtype} = series, dtype), do: series
-  def cast(series, dtype), do: Shared.apply_impl(series, :cast, [dtype])
+  @spec cast(series :: Series.t(), dtype :: dtype(), opts :: Keyword.t()) :: Series.t()
+  def cast(series, dtype, opts \\ [])
+
+  def cast(%Series{dtype: dtype} = series, dtype, opts) do
+    Keyword.validate!(opts, strict: false)
+    series
+  end
+
+  def cast(series, dtype, opts) do
+    opts = Keyword.validate!(opts, strict: false)
+    Shared.apply_impl(series, :cast, [dtype, opts[:strict]])
+  end
 
   # Introspection
 
@@ -342,6 +403,9 @@ defmodule Explorer.Series do
     * `:string` - UTF-8 encoded binary
     * `:date` - Date type that unwraps to `Elixir.Date`
     * `:datetime` - DateTime type that unwraps to `Elixir.NaiveDateTime`
+    * `{:s, 8}`, `{:s, 16}` and `{:s, 32}` - 8, 16 and 32-bit signed integers
+    * `{:u, 8}`, `{:u, 16}`, `{:u, 32}` and `{:u, 64}` - unsigned integers
+    * `{:f, 32}` - 32-bit floating point number
 
   ## Examples
 
@@ -714,7 +778,12 @@ defmodule Explorer.Series do
        do: Shared.apply_impl(acc, :concat, [cast(s, :float)])
 
   defp concat_reducer(%Series{dtype: dtype1}, %Series{dtype: dtype2}),
-    do: raise(ArgumentError, "dtypes must match, found #{dtype1} and #{dtype2}")
+    do:
+      raise(
+        ArgumentError,
+        "dtypes must match, found #{Shared.dtype_to_string(dtype1)} and " <>
+          Shared.dtype_to_string(dtype2)
+      )
 
   @doc """
   Finds the first non-missing element at each position.
@@ -799,6 +868,72 @@ defmodule Explorer.Series do
 
   def sum(%Series{dtype: dtype}), do: dtype_error("sum/1", dtype, [:integer, :float, :boolean])
 
+  @doc """
+  Returns true if all values of the series are true.
+
+  Nil values are ignored, so an empty series or a series with
+  only nils returns true.
+
+  ## Supported dtypes
+
+    * `:boolean`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([true, nil, true])
+      iex> Explorer.Series.all?(s)
+      true
+
+      iex> s = Explorer.Series.from_list([true, false])
+      iex> Explorer.Series.all?(s)
+      false
+
+      iex> s = Explorer.Series.from_list([], dtype: :boolean)
+      iex> Explorer.Series.all?(s)
+      true
+
+      iex> s = Explorer.Series.from_list([1, 2])
+      iex> Explorer.Series.all?(s)
+      ** (ArgumentError) Explorer.Series.all?/1 not implemented for dtype :integer. Valid dtypes are [:boolean].
+  """
+  @doc type: :aggregation
+  @spec all?(series :: Series.t()) :: boolean()
+  def all?(%Series{dtype: :boolean} = series), do: Shared.apply_impl(series, :all?)
+  def all?(%Series{dtype: dtype}), do: dtype_error("all?/1", dtype, [:boolean])
+
+  @doc """
+  Returns true if at least one value of the series is true.
+
+  Nil values are ignored, so an empty series or a series with
+  only nils returns false.
+
+  ## Supported dtypes
+
+    * `:boolean`
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([false, nil, true])
+      iex> Explorer.Series.any?(s)
+      true
+
+      iex> s = Explorer.Series.from_list([false, nil])
+      iex> Explorer.Series.any?(s)
+      false
+
+      iex> s = Explorer.Series.from_list([], dtype: :boolean)
+      iex> Explorer.Series.any?(s)
+      false
+
+      iex> s = Explorer.Series.from_list([1, 2])
+      iex> Explorer.Series.any?(s)
+      ** (ArgumentError) Explorer.Series.any?/1 not implemented for dtype :integer. Valid dtypes are [:boolean].
+  """
+  @doc type: :aggregation
+  @spec any?(series :: Series.t()) :: boolean()
+  def any?(%Series{dtype: :boolean} = series), do: Shared.apply_impl(series, :any?)
+  def any?(%Series{dtype: dtype}), do: dtype_error("any?/1", dtype, [:boolean])
+
   @doc """
   Gets the minimum value of the series.
 
@@ -1045,6 +1180,15 @@ defmodule Explorer.Series do
 
   Does not fill nil values. See `fill_missing/2`.
 
+  NaN never becomes the running maximum: its position takes the
+  maximum seen so far.
+
+  ## Options
+
+    * `:reverse` - Computes the maximum from the end of the series. (default: `false`)
+    * `:skip_nulls` - When `true`, nil values are skipped and keep their position.
+      When `false`, a nil value makes all subsequent values nil. (default: `true`)
+
   ## Supported dtypes
 
     * `:integer`
@@ -1067,6 +1211,13 @@ defmodule Explorer.Series do
         integer[4]
         [1, 2, nil, 4]
       >
+
+      iex> s = [1, 2, nil, 4] |> Explorer.Series.from_list()
+      iex> Explorer.Series.cumulative_max(s, skip_nulls: false)
+      #Explorer.Series<
+        integer[4]
+        [1, 2, nil, nil]
+      >
   """
   @doc type: :window
   @spec cumulative_max(series :: Series.t(), opts :: Keyword.t()) :: Series.t()
@@ -1074,8 +1225,8 @@ defmodule Explorer.Series do
 
   def cumulative_max(%Series{dtype: dtype} = series, opts)
       when numeric_or_date_dtype?(dtype) do
-    opts = Keyword.validate!(opts, reverse: false)
-    Shared.apply_impl(series, :cumulative_max, [opts[:reverse]])
+    opts = Keyword.validate!(opts, reverse: false, skip_nulls: true)
+    Shared.apply_impl(series, :cumulative_max, [opts[:reverse], opts[:skip_nulls]])
   end
 
   def cumulative_max(%Series{dtype: dtype}, _),
@@ -1088,6 +1239,15 @@ defmodule Explorer.Series do
 
   Does not fill nil values. See `fill_missing/2`.
 
+  NaN never becomes the running minimum: its position takes the
+  minimum seen so far.
+
+  ## Options
+
+    * `:reverse` - Computes the minimum from the end of the series. (default: `false`)
+    * `:skip_nulls` - When `true`, nil values are skipped and keep their position.
+      When `false`, a nil value makes all subsequent values nil. (default: `true`)
+
   ## Supported dtypes
 
     * `:integer`
@@ -1110,6 +1270,13 @@ defmodule Explorer.Series do
         integer[4]
         [1, 1, nil, 1]
       >
+
+      iex> s = [1, 2, nil, 4] |> Explorer.Series.from_list()
+      iex> Explorer.Series.cumulative_min(s, skip_nulls: false)
+      #Explorer.Series<
+        integer[4]
+        [1, 1, nil, nil]
+      >
   """
   @doc type: :window
   @spec cumulative_min(series :: Series.t(), opts :: Keyword.t()) :: Series.t()
@@ -1117,8 +1284,8 @@ defmodule Explorer.Series do
 
   def cumulative_min(%Series{dtype: dtype} = series, opts)
       when numeric_or_date_dtype?(dtype) do
-    opts = Keyword.validate!(opts, reverse: false)
-    Shared.apply_impl(series, :cumulative_min, [opts[:reverse]])
+    opts = Keyword.validate!(opts, reverse: false, skip_nulls: true)
+    Shared.apply_impl(series, :cumulative_min, [opts[:reverse], opts[:skip_nulls]])
   end
 
   def cumulative_min(%Series{dtype: dtype}, _),
@@ -1131,6 +1298,12 @@ defmodule Explorer.Series do
 
   Does not fill nil values. See `fill_missing/2`.
 
+  ## Options
+
+    * `:reverse` - Computes the sum from the end of the series. (default: `false`)
+    * `:skip_nulls` - When `true`, nil values are skipped and keep their position.
+      When `false`, a nil value makes all subsequent sums nil. (default: `true`)
+
   ## Supported dtypes
 
     * `:integer`
@@ -1152,6 +1325,13 @@ defmodule Explorer.Series do
         integer[4]
         [1, 3, nil, 7]
       >
+
+      iex> s = [1, 2, nil, 4] |> Explorer.Series.from_list()
+      iex> Explorer.Series.cumulative_sum(s, skip_nulls: false)
+      #Explorer.Series<
+        integer[4]
+        [1, 3, nil, nil]
+      >
   """
   @doc type: :window
   @spec cumulative_sum(series :: Series.t(), opts :: Keyword.t()) :: Series.t()
@@ -1159,8 +1339,8 @@ defmodule Explorer.Series do
 
   def cumulative_sum(%Series{dtype: dtype} = series, opts)
       when numeric_dtype?(dtype) do
-    opts = Keyword.validate!(opts, reverse: false)
-    Shared.apply_impl(series, :cumulative_sum, [opts[:reverse]])
+    opts = Keyword.validate!(opts, reverse: false, skip_nulls: true)
+    Shared.apply_impl(series, :cumulative_sum, [opts[:reverse], opts[:skip_nulls]])
   end
 
   def cumulative_sum(%Series{dtype: dtype}, _),
@@ -1358,8 +1538,11 @@ defmodule Explorer.Series do
   def divide(%Series{} = left, right) do
     left =
       if K.and(
-           left.dtype == :integer,
-           K.or(is_integer(right), match?(%Series{dtype: :integer}, right))
+           integer_dtype?(left.dtype),
+           K.or(
+             is_integer(right),
+             match?(%Series{dtype: dtype} when integer_dtype?(dtype), right)
+           )
          ) do
         cast(left, :float)
       else
@@ -1373,7 +1556,7 @@ defmodule Explorer.Series do
     left =
       if K.and(
            is_integer(left),
-           right.dtype == :integer
+           integer_dtype?(right.dtype)
          ) do
         left / 1.0
       else
@@ -1420,14 +1603,17 @@ defmodule Explorer.Series do
   """
   @doc type: :element_wise
   @spec quotient(left :: Series.t(), right :: Series.t() | integer()) :: Series.t()
-  def quotient(%Series{dtype: :integer} = left, %Series{dtype: :integer} = right),
-    do: Shared.apply_binary_op_impl(:quotient, left, right)
+  def quotient(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
+      when K.and(integer_dtype?(left_dtype), integer_dtype?(right_dtype)),
+      do: Shared.apply_binary_op_impl(:quotient, left, right)
 
-  def quotient(%Series{dtype: :integer} = left, right) when is_integer(right),
-    do: Shared.apply_binary_op_impl(:quotient, left, right)
+  def quotient(%Series{dtype: dtype} = left, right)
+      when K.and(integer_dtype?(dtype), is_integer(right)),
+      do: Shared.apply_binary_op_impl(:quotient, left, right)
 
-  def quotient(left, %Series{dtype: :integer} = right) when is_integer(left),
-    do: Shared.apply_binary_op_impl(:quotient, left, right)
+  def quotient(left, %Series{dtype: dtype} = right)
+      when K.and(integer_dtype?(dtype), is_integer(left)),
+      do: Shared.apply_binary_op_impl(:quotient, left, right)
 
   @doc """
   Computes the remainder of an element-wise integer division.
@@ -1466,14 +1652,17 @@ defmodule Explorer.Series do
   """
   @doc type: :element_wise
   @spec remainder(left :: Series.t(), right :: Series.t() | integer()) :: Series.t()
-  def remainder(%Series{dtype: :integer} = left, %Series{dtype: :integer} = right),
-    do: Shared.apply_binary_op_impl(:remainder, left, right)
+  def remainder(%Series{dtype: left_dtype} = left, %Series{dtype: right_dtype} = right)
+      when K.and(integer_dtype?(left_dtype), integer_dtype?(right_dtype)),
+      do: Shared.apply_binary_op_impl(:remainder, left, right)
 
-  def remainder(%Series{dtype: :integer} = left, right) when is_integer(right),
-    do: Shared.apply_binary_op_impl(:remainder, left, right)
+  def remainder(%Series{dtype: dtype} = left, right)
+      when K.and(integer_dtype?(dtype), is_integer(right)),
+      do: Shared.apply_binary_op_impl(:remainder, left, right)
 
-  def remainder(left, %Series{dtype: :integer} = right) when is_integer(left),
-    do: Shared.apply_binary_op_impl(:remainder, left, right)
+  def remainder(left, %Series{dtype: dtype} = right)
+      when K.and(integer_dtype?(dtype), is_integer(left)),
+      do: Shared.apply_binary_op_impl(:remainder, left, right)
 
   defp basic_numeric_operation(
          operation,
@@ -2014,6 +2203,41 @@ defmodule Explorer.Series do
   @doc type: :aggregation
   def n_distinct(series), do: Shared.apply_impl(series, :n_distinct)
 
+  @doc """
+  Returns a mask of values that appear more than once.
+
+  Every occurrence of a repeated value is marked, including the first one.
+  Nils are considered equal to each other.
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([1, 2, 1, nil, nil])
+      iex> Explorer.Series.is_duplicated(s)
+      #Explorer.Series<
+        boolean[5]
+        [true, false, true, true, true]
+      >
+  """
+  @doc type: :element_wise
+  @spec is_duplicated(Series.t()) :: Series.t()
+  def is_duplicated(series), do: Shared.apply_impl(series, :is_duplicated)
+
+  @doc """
+  Returns a mask of values that appear exactly once.
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([1, 2, 1, nil])
+      iex> Explorer.Series.is_unique(s)
+      #Explorer.Series<
+        boolean[4]
+        [false, true, false, true]
+      >
+  """
+  @doc type: :element_wise
+  @spec is_unique(Series.t()) :: Series.t()
+  def is_unique(series), do: Shared.apply_impl(series, :is_unique)
+
   @doc """
   Creates a new dataframe with unique values and the count of each.
 
@@ -2303,6 +2527,19 @@ defmodule Explorer.Series do
   @spec is_not_nil(Series.t()) :: Series.t()
   def is_not_nil(series), do: Shared.apply_impl(series, :is_not_nil)
 
+  @doc """
+  Returns the number of nil values in the series.
+
+  ## Examples
+
+      iex> s = Explorer.Series.from_list([1, nil, 3, nil])
+      iex> Explorer.Series.nil_count(s)
+      2
+  """
+  @doc type: :aggregation
+  @spec nil_count(series :: Series.t()) :: non_neg_integer()
+  def nil_count(series), do: Shared.apply_impl(series, :nil_count)
+
   # Escape hatch
 
   @doc """
@@ -2357,15 +2594,18 @@ defmodule Explorer.Series do
     do:
       raise(
         ArgumentError,
-        "cannot invoke Explorer.Series.#{function} with mismatched dtypes: #{left_dtype} and " <>
-          "#{right_dtype}."
+        "cannot invoke Explorer.Series.#{function} with mismatched dtypes: " <>
+          "#{dtype_name(left_dtype)} and #{dtype_name(right_dtype)}."
       )
 
+  # Some callers pass the inspected value instead of a dtype.
+  defp dtype_name(inspected) when is_binary(inspected), do: inspected
+  defp dtype_name(dtype), do: Shared.dtype_to_string(dtype)
+
   defp check_dtypes_for_coalesce!(%Series{} = s1, %Series{} = s2) do
     case {s1.dtype, s2.dtype} do
       {dtype, dtype} -> :ok
-      {:integer, :float} -> :ok
-      {:float, :integer} -> :ok
+      {left, right} when K.and(numeric_dtype?(left), numeric_dtype?(right)) -> :ok
       {left, right} -> dtype_mismatch_error("coalesce/2", left, right)
     end
   end
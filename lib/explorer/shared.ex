@@ -2,10 +2,30 @@ defmodule Explorer.Shared do
   # A collection of **private** helpers shared in Explorer.
   @moduledoc false
 
+  @integer_types [:integer, {:s, 8}, {:s, 16}, {:s, 32}, {:u, 8}, {:u, 16}, {:u, 32}, {:u, 64}]
+  @float_types [:float, {:f, 32}]
+
   @doc """
   All supported dtypes.
   """
-  def dtypes, do: [:float, :integer, :boolean, :string, :date, :datetime]
+  def dtypes,
+    do: @float_types ++ @integer_types ++ [:boolean, :string, :binary, :date, :datetime]
+
+  @doc """
+  All integer dtypes. `:integer` is a signed 64-bit integer.
+  """
+  def integer_types, do: @integer_types
+
+  @doc """
+  All float dtypes. `:float` is a 64-bit float.
+  """
+  def float_types, do: @float_types
+
+  @doc """
+  Returns the name of a dtype, such as "integer" or "f32".
+  """
+  def dtype_to_string({kind, bits}), do: "#{kind}#{bits}"
+  def dtype_to_string(dtype) when is_atom(dtype), do: Atom.to_string(dtype)
 
   @doc """
   Gets the backend from a `Keyword.t()` or `nil`.
@@ -1,15 +1,41 @@
+use calamine::{open_workbook_auto, DataType as CellType, Reader};
+use chrono::{NaiveDate, NaiveDateTime};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression as GzCompression;
+use polars::export::arrow::array::{Array, StructArray};
+use polars::export::arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField};
+use polars::export::arrow::ffi;
 use polars::prelude::*;
 use polars_ops::pivot::{pivot_stable, PivotAgg};
-
-use rustler::{Binary, Env, NewBinary};
+use rust_xlsxwriter::{Format, Workbook};
+use zstd::stream::read::Decoder as ZstdDecoder;
+use zstd::stream::write::Encoder as ZstdEncoder;
+
+use rustler::types::atom;
+use rustler::types::map::MapIterator;
+use rustler::{Atom, Binary, Env, NewBinary, ResourceArc, Term};
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use std::result::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::atoms::{infinity, nan, neg_infinity};
+use crate::datatypes::{ExDate, ExDateTime};
+use crate::encoding::rows_from_dataframe;
+use crate::hashing::hash_rows;
+use crate::series::{cast_str_to_dtype, to_ex_series_collection, to_series_collection};
 
-use crate::series::{to_ex_series_collection, to_series_collection};
+use crate::{
+    ExArrowCRef, ExDataFrame, ExExpr, ExLazyFrame, ExRowStreamRef, ExSeries, ExplorerError,
+};
 
-use crate::{ExDataFrame, ExExpr, ExLazyFrame, ExSeries, ExplorerError};
+// NIFs that read or write files run on dirty IO schedulers and NIFs that
+// process the data run on dirty CPU schedulers. Only metadata accessors,
+// column selection by name and plan descriptions run on normal schedulers.
 
 #[rustler::nif(schedule = "DirtyIo")]
 #[allow(clippy::too_many_arguments)]
@@ -20,12 +46,12 @@ pub fn df_read_csv(
     stop_after_n_rows: Option<usize>,
     skip_rows: usize,
     projection: Option<Vec<usize>>,
-    sep: &str,
+    delimiter: u8,
     do_rechunk: bool,
     column_names: Option<Vec<String>>,
     dtypes: Option<Vec<(&str, &str)>>,
     encoding: &str,
-    null_char: String,
+    null_values: Vec<String>,
     parse_dates: bool,
 ) -> Result<ExDataFrame, ExplorerError> {
     let encoding = match encoding {
@@ -37,52 +63,415 @@ pub fn df_read_csv(
         Some(dtypes) => {
             let mut schema = Schema::new();
             for (name, dtype) in dtypes {
-                schema.with_column(name.to_string(), dtype_from_str(dtype)?)
+                schema.with_column(name.to_string(), cast_str_to_dtype(dtype)?)
             }
             Some(schema)
         }
         None => None,
     };
 
-    let df = CsvReader::from_path(filename)?
+    check_local_path(filename)?;
+    let compression = csv_compression(filename)?;
+    let decompressed = match compression {
+        Some(compression) => Some(decompress_csv(filename, compression)?),
+        None => None,
+    };
+    let path = match &decompressed {
+        Some(temp) => temp.0.as_path(),
+        None => Path::new(filename),
+    };
+
+    let result = CsvReader::from_path(path)?
         .infer_schema(infer_schema_length)
         .has_header(has_header)
         .with_parse_dates(parse_dates)
         .with_n_rows(stop_after_n_rows)
-        .with_delimiter(sep.as_bytes()[0])
+        .with_delimiter(delimiter)
         .with_skip_rows(skip_rows)
         .with_projection(projection)
         .with_rechunk(do_rechunk)
         .with_encoding(encoding)
         .with_columns(column_names)
         .with_dtypes(schema.as_ref())
-        .with_null_values(Some(NullValues::AllColumns(vec![null_char])))
-        .finish()?;
+        .with_null_values(Some(NullValues::AllColumns(null_values)))
+        .finish();
+
+    match (result, compression) {
+        (Ok(df), _) => Ok(ExDataFrame::new(df)),
+        (Err(error), Some(compression)) => Err(ExplorerError::Other(format!(
+            "Could not read {} compressed CSV file {}: {}",
+            compression, filename, error
+        ))),
+        (Err(error), None) => Err(error.into()),
+    }
+}
 
-    Ok(ExDataFrame::new(df))
+// Compression is detected from the magic bytes, so the extension of the
+// file doesn't matter.
+fn csv_compression(filename: &str) -> Result<Option<&'static str>, ExplorerError> {
+    let mut magic = Vec::with_capacity(4);
+    File::open(filename)?.take(4).read_to_end(&mut magic)?;
+
+    let compression = match magic.as_slice() {
+        [0x1f, 0x8b, ..] => Some("gzip"),
+        [0x28, 0xb5, 0x2f, 0xfd] => Some("zstd"),
+        _ => None,
+    };
+
+    Ok(compression)
 }
 
-fn dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
-    match dtype {
-        "str" => Ok(DataType::Utf8),
-        "f64" => Ok(DataType::Float64),
-        "i64" => Ok(DataType::Int64),
-        "bool" => Ok(DataType::Boolean),
-        "date" => Ok(DataType::Date),
-        "datetime[μs]" => Ok(DataType::Datetime(TimeUnit::Microseconds, None)),
-        "datetime[ms]" => Ok(DataType::Datetime(TimeUnit::Milliseconds, None)),
-        _ => Err(ExplorerError::Internal("Unrecognised datatype".into())),
+// A file that is removed when dropped.
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+static TEMP_CSV_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// The CSV reader needs a file it can map into memory, so compressed input
+// is streamed into a temporary file instead of being inflated in memory.
+// Errors from corrupt streams name the file and how far into it the
+// decoder had read.
+fn decompress_csv(filename: &str, compression: &str) -> Result<TempPath, ExplorerError> {
+    let file = File::open(filename)?;
+    let temp = TempPath(std::env::temp_dir().join(format!(
+        "explorer-{}-{}.csv",
+        std::process::id(),
+        TEMP_CSV_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )));
+    let mut writer = BufWriter::new(File::create(&temp.0)?);
+
+    let copied = match compression {
+        "gzip" => std::io::copy(&mut MultiGzDecoder::new(&file), &mut writer),
+        _ => std::io::copy(&mut ZstdDecoder::new(&file)?, &mut writer),
+    };
+
+    if let Err(error) = copied {
+        let mut file = &file;
+        return Err(ExplorerError::Other(format!(
+            "Could not read {} compressed CSV file {}: {} near byte {}",
+            compression,
+            filename,
+            error,
+            file.stream_position()?
+        )));
+    }
+
+    writer.flush()?;
+    Ok(temp)
+}
+
+// Remote sources are not supported by the polars version we bind to,
+// so we fail early with a clear message instead of an IO error.
+pub(crate) fn check_local_path(filename: &str) -> Result<(), ExplorerError> {
+    let remote = ["http://", "https://", "s3://"]
+        .iter()
+        .any(|scheme| filename.starts_with(scheme));
+
+    if remote {
+        Err(ExplorerError::Other(format!(
+            "Reading from remote URLs is not supported, got: {}. \
+             Download the file first or load its contents from memory",
+            filename
+        )))
+    } else {
+        Ok(())
     }
 }
 
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_read_parquet(filename: &str) -> Result<ExDataFrame, ExplorerError> {
+pub fn df_read_parquet(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    n_rows: Option<usize>,
+    row_index: Option<(String, u32)>,
+    parallel: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    check_local_path(filename)?;
+
+    if is_parquet_dataset(filename) {
+        let mut df = read_parquet_dataset(filename, parallel)?;
+
+        if let Some(columns) = &columns {
+            check_parquet_columns(columns, df.get_column_names())?;
+            df = df.select(columns)?;
+        }
+
+        if n_rows.is_some() {
+            df = df.head(n_rows);
+        }
+
+        if let Some((name, offset)) = row_index {
+            df = df.with_row_count(&name, Some(offset))?;
+        }
+
+        return Ok(ExDataFrame::new(df));
+    }
+
+    if let Some(columns) = &columns {
+        let schema = ParquetReader::new(File::open(filename)?).schema()?;
+        check_parquet_columns(
+            columns,
+            schema.iter_names().map(|name| name.as_str()).collect(),
+        )?;
+    }
+
     let file = File::open(filename)?;
     let buf_reader = BufReader::new(file);
-    let df = ParquetReader::new(buf_reader).finish()?;
+    let df = ParquetReader::new(buf_reader)
+        .with_columns(columns)
+        .with_n_rows(n_rows)
+        .with_row_count(row_index.map(|(name, offset)| RowCount { name, offset }))
+        .read_parallel(parallel_strategy(parallel))
+        .finish()?;
     Ok(ExDataFrame::new(df))
 }
 
+fn parallel_strategy(parallel: bool) -> ParallelStrategy {
+    if parallel {
+        ParallelStrategy::Auto
+    } else {
+        ParallelStrategy::None
+    }
+}
+
+fn check_parquet_columns(columns: &[String], available: Vec<&str>) -> Result<(), ExplorerError> {
+    let missing: Vec<&String> = columns
+        .iter()
+        .filter(|column| !available.contains(&column.as_str()))
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ExplorerError::Other(format!(
+            "Columns {:?} not found in parquet file, available columns are: {:?}",
+            missing, available
+        )))
+    }
+}
+
+// Partitioned datasets use hive-style directories, such as `year=2023/month=1/part-00000.parquet`.
+// Partition values are percent-encoded so they can't introduce new path segments.
+
+const HIVE_NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_parquet_partitioned(
+    data: ExDataFrame,
+    dir: &str,
+    partition_by: Vec<String>,
+    compression: Option<&str>,
+    compression_level: Option<i32>,
+    max_rows_per_file: Option<usize>,
+) -> Result<(), ExplorerError> {
+    let df = &data.resource.0;
+    let compression = parquet_compression(compression, compression_level)?;
+
+    if max_rows_per_file == Some(0) {
+        return Err(ExplorerError::Other(
+            "max_rows_per_file must be greater than zero".into(),
+        ));
+    }
+
+    for partition in df.partition_by_stable(partition_by.clone())? {
+        let mut path = PathBuf::from(dir);
+
+        for column in &partition_by {
+            let value = partition_value(partition.column(column)?.get(0));
+            path.push(format!(
+                "{}={}",
+                percent_encode(column),
+                percent_encode(&value)
+            ));
+        }
+
+        std::fs::create_dir_all(&path)?;
+
+        let partition = partition.drop_many(&partition_by);
+        let height = partition.height();
+        let rows_per_file = max_rows_per_file.unwrap_or(height).max(1);
+
+        for (index, offset) in (0..height).step_by(rows_per_file).enumerate() {
+            let mut chunk = partition.slice(offset as i64, rows_per_file);
+            let file = File::create(path.join(format!("part-{:05}.parquet", index)))?;
+
+            ParquetWriter::new(BufWriter::new(file))
+                .with_compression(compression)
+                .finish(&mut chunk)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn partition_value(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => HIVE_NULL_PARTITION.to_string(),
+        AnyValue::Utf8(value) => value.to_string(),
+        value => format!("{}", value),
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '%' | '/' | '\\' | '=' => encoded.push_str(&format!("%{:02X}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = match (bytes[index], bytes.get(index + 1..index + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn is_parquet_dataset(filename: &str) -> bool {
+    filename.contains('*') || Path::new(filename).is_dir()
+}
+
+fn read_parquet_dataset(filename: &str, parallel: bool) -> Result<DataFrame, ExplorerError> {
+    let (root, pattern) = if Path::new(filename).is_dir() {
+        let root = PathBuf::from(filename);
+        let pattern = root.join("**").join("*.parquet");
+        (root, pattern.to_string_lossy().into_owned())
+    } else {
+        let root: PathBuf = Path::new(filename)
+            .components()
+            .take_while(|component| !component.as_os_str().to_string_lossy().contains('*'))
+            .collect();
+        (root, filename.to_string())
+    };
+
+    let mut paths = glob::glob(&pattern)
+        .map_err(|error| ExplorerError::Other(error.to_string()))?
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .map_err(|error| ExplorerError::Other(error.to_string()))?;
+    paths.sort();
+
+    let partitions: Vec<Vec<(String, Option<String>)>> = paths
+        .iter()
+        .map(|path| hive_partitions(&root, path))
+        .collect();
+
+    let keys: Vec<String> = partitions
+        .first()
+        .map(|values| values.iter().map(|(key, _)| key.clone()).collect())
+        .unwrap_or_default();
+
+    for (path, values) in paths.iter().zip(&partitions) {
+        let path_keys: Vec<&String> = values.iter().map(|(key, _)| key).collect();
+
+        if !path_keys.iter().copied().eq(keys.iter()) {
+            return Err(ExplorerError::Other(format!(
+                "Partition keys {:?} of {} do not match partition keys {:?} of {}",
+                path_keys,
+                path.display(),
+                keys,
+                paths[0].display()
+            )));
+        }
+    }
+
+    let dtypes: Vec<DataType> = (0..keys.len())
+        .map(|index| {
+            infer_partition_dtype(
+                partitions
+                    .iter()
+                    .filter_map(|values| values.get(index).and_then(|(_, value)| value.as_deref()))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let mut dataset: Option<DataFrame> = None;
+
+    for (path, values) in paths.iter().zip(&partitions) {
+        let mut df = ParquetReader::new(BufReader::new(File::open(path)?))
+            .read_parallel(parallel_strategy(parallel))
+            .finish()?;
+        let height = df.height();
+
+        for ((key, dtype), (_, value)) in keys.iter().zip(&dtypes).zip(values) {
+            let column = Series::new(key, vec![value.as_deref(); height]).cast(dtype)?;
+            df.with_column(column)?;
+        }
+
+        match dataset.as_mut() {
+            Some(dataset) => {
+                dataset.vstack_mut(&df)?;
+            }
+            None => dataset = Some(df),
+        }
+    }
+
+    dataset.ok_or_else(|| ExplorerError::Other(format!("No parquet files found in {}", filename)))
+}
+
+fn hive_partitions(root: &Path, path: &Path) -> Vec<(String, Option<String>)> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.components())
+        .filter_map(|component| {
+            let segment = component.as_os_str().to_string_lossy();
+            let (key, value) = segment.split_once('=')?;
+            let value = match value {
+                HIVE_NULL_PARTITION => None,
+                value => Some(percent_decode(value)),
+            };
+
+            Some((percent_decode(key), value))
+        })
+        .collect()
+}
+
+fn infer_partition_dtype(values: Vec<&str>) -> DataType {
+    if values.is_empty() {
+        DataType::Utf8
+    } else if values.iter().all(|value| value.parse::<i64>().is_ok()) {
+        DataType::Int64
+    } else if values.iter().all(|value| value.parse::<f64>().is_ok()) {
+        DataType::Float64
+    } else {
+        DataType::Utf8
+    }
+}
+
 #[rustler::nif(schedule = "DirtyIo")]
 pub fn df_write_parquet(
     data: ExDataFrame,
@@ -91,38 +480,113 @@ pub fn df_write_parquet(
     compression_level: Option<i32>,
 ) -> Result<(), ExplorerError> {
     let df = &data.resource.0;
+    let compression = parquet_compression(compression, compression_level)?;
     let file = File::create(filename)?;
     let mut buf_writer = BufWriter::new(file);
+    ParquetWriter::new(&mut buf_writer)
+        .with_compression(compression)
+        .finish(&mut df.clone())?;
+    Ok(())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_parquet(
+    env: Env,
+    data: ExDataFrame,
+    compression: Option<&str>,
+    compression_level: Option<i32>,
+) -> Result<Binary, ExplorerError> {
+    let df = &data.resource.0;
+    let compression = parquet_compression(compression, compression_level)?;
+    let mut buf = vec![];
+
+    ParquetWriter::new(&mut buf)
+        .with_compression(compression)
+        .finish(&mut df.clone())?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_parquet(binary: Binary) -> Result<ExDataFrame, ExplorerError> {
+    let cursor = Cursor::new(binary.as_slice());
+    let df = ParquetReader::new(cursor).finish()?;
+    Ok(ExDataFrame::new(df))
+}
+
+fn parquet_compression(
+    compression: Option<&str>,
+    compression_level: Option<i32>,
+) -> Result<ParquetCompression, ExplorerError> {
     let compression = match (compression, compression_level) {
-        (Some("snappy"), _) => ParquetCompression::Snappy,
+        (None | Some("uncompressed"), None) => ParquetCompression::Uncompressed,
+        (Some("snappy"), None) => ParquetCompression::Snappy,
+        (Some("lz4raw"), None) => ParquetCompression::Lz4Raw,
         (Some("gzip"), level) => {
             let level = match level {
-                Some(level) => Some(GzipLevel::try_new(u8::try_from(level)?)?),
+                Some(level) => {
+                    check_compression_level("gzip", level, 1, 9)?;
+                    Some(GzipLevel::try_new(u8::try_from(level)?)?)
+                }
                 None => None,
             };
             ParquetCompression::Gzip(level)
         }
         (Some("brotli"), level) => {
             let level = match level {
-                Some(level) => Some(BrotliLevel::try_new(u32::try_from(level)?)?),
+                Some(level) => {
+                    check_compression_level("brotli", level, 1, 11)?;
+                    Some(BrotliLevel::try_new(u32::try_from(level)?)?)
+                }
                 None => None,
             };
             ParquetCompression::Brotli(level)
         }
         (Some("zstd"), level) => {
             let level = match level {
-                Some(level) => Some(ZstdLevel::try_new(level)?),
+                Some(level) => {
+                    check_compression_level("zstd", level, -7, 22)?;
+                    Some(ZstdLevel::try_new(level)?)
+                }
                 None => None,
             };
             ParquetCompression::Zstd(level)
         }
-        (Some("lz4raw"), _) => ParquetCompression::Lz4Raw,
-        _ => ParquetCompression::Uncompressed,
+        (None | Some("uncompressed" | "snappy" | "lz4raw"), Some(_level)) => {
+            return Err(ExplorerError::Other(format!(
+                "{} compression does not accept a compression level",
+                compression.unwrap_or("uncompressed")
+            )))
+        }
+        (Some(other), _) => {
+            return Err(ExplorerError::Other(format!(
+                "Parquet compression {} not supported, expected one of: \
+                 uncompressed, snappy, gzip, brotli, zstd, lz4raw",
+                other
+            )))
+        }
     };
-    ParquetWriter::new(&mut buf_writer)
-        .with_compression(compression)
-        .finish(&mut df.clone())?;
-    Ok(())
+
+    Ok(compression)
+}
+
+fn check_compression_level(
+    compression: &str,
+    level: i32,
+    min: i32,
+    max: i32,
+) -> Result<(), ExplorerError> {
+    if (min..=max).contains(&level) {
+        Ok(())
+    } else {
+        Err(ExplorerError::Other(format!(
+            "{} compression level must be between {} and {} inclusive, got {}",
+            compression, min, max, level
+        )))
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -131,14 +595,22 @@ pub fn df_to_csv(
     data: ExDataFrame,
     has_headers: bool,
     delimiter: u8,
+    null_value: String,
+    quote_style: &str,
+    datetime_format: Option<String>,
 ) -> Result<Binary, ExplorerError> {
     let df = &data.resource.0;
     let mut buf = vec![];
 
-    CsvWriter::new(&mut buf)
-        .has_header(has_headers)
-        .with_delimiter(delimiter)
-        .finish(&mut df.clone())?;
+    csv_writer(
+        &mut buf,
+        has_headers,
+        delimiter,
+        null_value,
+        quote_style,
+        datetime_format,
+    )?
+    .finish(&mut df.clone())?;
 
     let mut values_binary = NewBinary::new(env, buf.len());
     values_binary.copy_from_slice(&buf);
@@ -146,21 +618,181 @@ pub fn df_to_csv(
     Ok(values_binary.into())
 }
 
+// Rows are written in batches of `batch_size`, so the extra memory is
+// bounded by one batch. Uncompressed output is flushed after each batch,
+// so an interrupted write leaves a file with complete rows. Compressed
+// output is only flushed once the encoder is finished, since flushing an
+// encoder ends a compression block and makes the output larger, so an
+// interrupted compressed write leaves a truncated stream.
+//
+// When appending, the header is only written if the file is empty.
+// Otherwise the header already in the file must match the frame's columns.
 #[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
 pub fn df_to_csv_file(
     data: ExDataFrame,
     filename: &str,
     has_headers: bool,
     delimiter: u8,
+    null_value: String,
+    quote_style: &str,
+    datetime_format: Option<String>,
+    compression: Option<&str>,
+    batch_size: usize,
+    append: bool,
 ) -> Result<(), ExplorerError> {
+    if batch_size == 0 {
+        return Err(ExplorerError::Other(
+            "CSV batch size must be greater than zero".to_string(),
+        ));
+    }
+
+    if let Some(other) = compression.filter(|c| !matches!(*c, "gzip" | "zstd")) {
+        return Err(ExplorerError::Other(format!(
+            "CSV compression {} not supported, expected one of: gzip, zstd",
+            other
+        )));
+    }
+
     let df = &data.resource.0;
-    let file = File::create(filename)?;
-    let mut buf_writer = BufWriter::new(file);
-    CsvWriter::new(&mut buf_writer)
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(filename)?;
+    let is_empty = file.metadata()?.len() == 0;
+
+    if append && has_headers && !is_empty {
+        check_csv_header(df, filename, delimiter, quote_style, compression)?;
+    }
+
+    let has_headers = has_headers && is_empty;
+    let buf_writer = BufWriter::new(file);
+    let write_batches = |writer: &mut dyn Write, flush: bool| -> Result<(), ExplorerError> {
+        // An empty frame still gets its header.
+        let offsets = (0..df.height().max(1)).step_by(batch_size);
+
+        for (index, offset) in offsets.enumerate() {
+            let mut batch = df.slice(offset as i64, batch_size);
+
+            csv_writer(
+                &mut *writer,
+                has_headers && index == 0,
+                delimiter,
+                null_value.clone(),
+                quote_style,
+                datetime_format.clone(),
+            )?
+            .finish(&mut batch)?;
+
+            if flush {
+                writer.flush()?;
+            }
+        }
+
+        Ok(())
+    };
+
+    // Encoders are finished explicitly so errors writing their trailers
+    // are returned instead of being ignored on drop.
+    let mut buf_writer = match compression {
+        Some("gzip") => {
+            let mut encoder = GzEncoder::new(buf_writer, GzCompression::default());
+            write_batches(&mut encoder, false)?;
+            encoder.finish()?
+        }
+        Some("zstd") => {
+            let mut encoder = ZstdEncoder::new(buf_writer, 0)?;
+            write_batches(&mut encoder, false)?;
+            encoder.finish()?
+        }
+        _ => {
+            let mut buf_writer = buf_writer;
+            write_batches(&mut buf_writer, true)?;
+            buf_writer
+        }
+    };
+
+    buf_writer.flush()?;
+    Ok(())
+}
+
+// Compares the first line of an existing CSV file with the header the
+// frame would be written with.
+fn check_csv_header(
+    df: &DataFrame,
+    filename: &str,
+    delimiter: u8,
+    quote_style: &str,
+    compression: Option<&str>,
+) -> Result<(), ExplorerError> {
+    let file = File::open(filename)?;
+    let mut reader: Box<dyn BufRead> = match compression {
+        Some("gzip") => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+        Some("zstd") => Box::new(BufReader::new(ZstdDecoder::new(file)?)),
+        _ => Box::new(BufReader::new(file)),
+    };
+
+    let mut existing = String::new();
+    reader.read_line(&mut existing)?;
+
+    let mut expected = Vec::new();
+    csv_writer(
+        &mut expected,
+        true,
+        delimiter,
+        String::new(),
+        quote_style,
+        None,
+    )?
+    .finish(&mut df.slice(0, 0))?;
+
+    let is_newline = |c: char| c == '\n' || c == '\r';
+    let existing = existing.trim_end_matches(is_newline);
+    let expected = String::from_utf8_lossy(&expected);
+    let expected = expected.trim_end_matches(is_newline);
+
+    if existing == expected {
+        Ok(())
+    } else {
+        Err(ExplorerError::Other(format!(
+            "cannot append to {}, its header {} does not match the header {} of the dataframe",
+            filename, existing, expected
+        )))
+    }
+}
+
+fn csv_writer<W: Write>(
+    writer: W,
+    has_headers: bool,
+    delimiter: u8,
+    null_value: String,
+    quote_style: &str,
+    datetime_format: Option<String>,
+) -> Result<CsvWriter<W>, ExplorerError> {
+    let quote_style = match quote_style {
+        "necessary" => QuoteStyle::Necessary,
+        "always" => QuoteStyle::Always,
+        "never" => QuoteStyle::Never,
+        "non_numeric" => QuoteStyle::NonNumeric,
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Quote style {} not supported, expected one of: \
+                 necessary, always, never, non_numeric",
+                other
+            )))
+        }
+    };
+
+    let writer = CsvWriter::new(writer)
         .has_header(has_headers)
         .with_delimiter(delimiter)
-        .finish(&mut df.clone())?;
-    Ok(())
+        .with_null_value(null_value)
+        .with_quote_style(quote_style)
+        .with_datetime_format(datetime_format);
+
+    Ok(writer)
 }
 
 #[rustler::nif(schedule = "DirtyIo")]
@@ -185,12 +817,7 @@ pub fn df_write_ipc(
     compression: Option<&str>,
 ) -> Result<(), ExplorerError> {
     let df = &data.resource.0;
-    // Select the compression algorithm.
-    let compression = match compression {
-        Some("lz4") => Some(IpcCompression::LZ4),
-        Some("zstd") => Some(IpcCompression::ZSTD),
-        _ => None,
-    };
+    let compression = ipc_compression(compression)?;
 
     let file = File::create(filename)?;
     let mut buf_writer = BufWriter::new(file);
@@ -200,21 +827,119 @@ pub fn df_write_ipc(
     Ok(())
 }
 
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_read_ipc_stream(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let file = File::open(filename)?;
+    let buf_reader = BufReader::new(file);
+    let df = IpcStreamReader::new(buf_reader)
+        .with_columns(columns)
+        .with_projection(projection)
+        .finish()?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_write_ipc_stream(
+    data: ExDataFrame,
+    filename: &str,
+    compression: Option<&str>,
+) -> Result<(), ExplorerError> {
+    let df = &data.resource.0;
+    let compression = ipc_compression(compression)?;
+
+    let file = File::create(filename)?;
+    let mut buf_writer = BufWriter::new(file);
+    IpcStreamWriter::new(&mut buf_writer)
+        .with_compression(compression)
+        .finish(&mut df.clone())?;
+    Ok(())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc(
+    env: Env,
+    data: ExDataFrame,
+    compression: Option<&str>,
+) -> Result<Binary, ExplorerError> {
+    let df = &data.resource.0;
+    let compression = ipc_compression(compression)?;
+    let mut buf = vec![];
+
+    IpcWriter::new(&mut buf)
+        .with_compression(compression)
+        .finish(&mut df.clone())?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc(binary: Binary) -> Result<ExDataFrame, ExplorerError> {
+    let cursor = Cursor::new(binary.as_slice());
+    let df = IpcReader::new(cursor).finish()?;
+    Ok(ExDataFrame::new(df))
+}
+
+fn ipc_compression(compression: Option<&str>) -> Result<Option<IpcCompression>, ExplorerError> {
+    match compression {
+        Some("lz4") => Ok(Some(IpcCompression::LZ4)),
+        Some("zstd") => Ok(Some(IpcCompression::ZSTD)),
+        None => Ok(None),
+        Some(other) => Err(ExplorerError::Other(format!(
+            "IPC compression {} not supported, expected one of: lz4, zstd",
+            other
+        ))),
+    }
+}
+
 #[rustler::nif(schedule = "DirtyIo")]
 pub fn df_read_ndjson(
     filename: &str,
     infer_schema_length: Option<usize>,
-    batch_size: usize,
+    batch_size: Option<usize>,
 ) -> Result<ExDataFrame, ExplorerError> {
     let file = File::open(filename)?;
     let buf_reader = BufReader::new(file);
-    let df = JsonReader::new(buf_reader)
+    let result = JsonReader::new(buf_reader)
         .with_json_format(JsonFormat::JsonLines)
-        .with_batch_size(batch_size)
+        .with_batch_size(batch_size.unwrap_or(1000))
         .infer_schema_len(infer_schema_length)
-        .finish()?;
+        .finish();
+
+    match result {
+        Ok(df) => Ok(ExDataFrame::new(df)),
+        Err(error) => match ndjson_malformed_line(filename) {
+            Some(malformed) => Err(ExplorerError::Other(format!("{}; {}", error, malformed))),
+            None => Err(error.into()),
+        },
+    }
+}
 
-    Ok(ExDataFrame::new(df))
+// Polars does not report where parsing failed, so we look for the first
+// line that is not valid JSON to point users to it. This is best-effort:
+// the original error is always kept and failures here are ignored.
+fn ndjson_malformed_line(filename: &str) -> Option<String> {
+    let buf_reader = BufReader::new(File::open(filename).ok()?);
+
+    for (index, line) in buf_reader.lines().enumerate() {
+        let line = line.ok()?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Err(error) = serde_json::from_str::<serde_json::Value>(&line) {
+            return Some(format!("malformed JSON on line {}: {}", index + 1, error));
+        }
+    }
+
+    None
 }
 
 #[rustler::nif(schedule = "DirtyIo")]
@@ -226,6 +951,233 @@ pub fn df_write_ndjson(data: ExDataFrame, filename: &str) -> Result<(), Explorer
     Ok(())
 }
 
+// Excel stores dates and datetimes as the number of days since 1899-12-30,
+// which is 25569 days before the Unix epoch. The 1900 leap year bug only
+// affects serials before March 1900 and is not corrected.
+const XLSX_EPOCH_OFFSET: f64 = 25_569.0;
+const XLSX_MAX_ROWS: usize = 1_048_576;
+const XLSX_MAX_COLUMNS: usize = 16_384;
+const MICROSECONDS_PER_DAY: f64 = 86_400_000_000.0;
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_xlsx(
+    filename: &str,
+    sheet: Option<&str>,
+    skip_rows: usize,
+    header: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    check_local_path(filename)?;
+
+    let mut workbook = open_workbook_auto(filename)?;
+    let sheet_names = workbook.sheet_names().to_owned();
+
+    let sheet = match sheet {
+        Some(sheet) if sheet_names.iter().any(|name| name == sheet) => sheet.to_string(),
+        Some(sheet) => {
+            return Err(ExplorerError::Other(format!(
+                "sheet {} does not exist, available sheets are: {}",
+                sheet,
+                sheet_names.join(", ")
+            )))
+        }
+        None => sheet_names
+            .first()
+            .cloned()
+            .ok_or_else(|| ExplorerError::Other("the workbook has no sheets".to_string()))?,
+    };
+
+    let range = workbook
+        .worksheet_range(&sheet)
+        .ok_or_else(|| ExplorerError::Other(format!("cannot read sheet {}", sheet)))??;
+
+    let mut rows = range.rows().skip(skip_rows);
+
+    let names: Vec<String> = match rows.next() {
+        Some(row) if header => row
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| match cell {
+                CellType::Empty => format!("column_{}", index + 1),
+                cell => cell.to_string(),
+            })
+            .collect(),
+        _ if header => Vec::new(),
+        _ => (1..=range.width())
+            .map(|index| format!("column_{}", index))
+            .collect(),
+    };
+
+    // Without a header the first row is data, so we start over.
+    let rows: Vec<&[CellType]> = if header {
+        rows.collect()
+    } else {
+        range.rows().skip(skip_rows).collect()
+    };
+
+    let columns = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let cells: Vec<&CellType> = rows.iter().map(|row| &row[index]).collect();
+            series_from_xlsx_cells(name, &cells)
+        })
+        .collect::<Result<Vec<Series>, ExplorerError>>()?;
+
+    Ok(ExDataFrame::new(DataFrame::new(columns)?))
+}
+
+// Columns get the narrowest dtype that holds all of their cells. Empty
+// cells are nulls and columns mixing kinds of values become strings.
+fn series_from_xlsx_cells(name: &str, cells: &[&CellType]) -> Result<Series, ExplorerError> {
+    let all = |f: fn(&CellType) -> bool| {
+        cells
+            .iter()
+            .all(|cell| matches!(cell, CellType::Empty) || f(cell))
+    };
+
+    let series = if all(|_| false) {
+        Series::full_null(name, cells.len(), &DataType::Utf8)
+    } else if all(|cell| matches!(cell, CellType::Bool(_))) {
+        let values: Vec<Option<bool>> = cells.iter().map(|cell| cell.get_bool()).collect();
+        Series::new(name, values)
+    } else if all(|cell| matches!(cell, CellType::Int(_))) {
+        let values: Vec<Option<i64>> = cells.iter().map(|cell| cell.get_int()).collect();
+        Series::new(name, values)
+    } else if all(|cell| matches!(cell, CellType::Int(_) | CellType::Float(_))) {
+        let values: Vec<Option<f64>> = cells
+            .iter()
+            .map(|cell| match cell {
+                CellType::Int(value) => Some(*value as f64),
+                CellType::Float(value) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        Series::new(name, values)
+    } else if all(|cell| matches!(cell, CellType::DateTime(_))) {
+        let serials: Vec<Option<f64>> = cells
+            .iter()
+            .map(|cell| match cell {
+                CellType::DateTime(serial) => Some(*serial - XLSX_EPOCH_OFFSET),
+                _ => None,
+            })
+            .collect();
+
+        if serials.iter().flatten().all(|serial| serial.fract() == 0.0) {
+            let days: Vec<Option<i32>> = serials
+                .iter()
+                .map(|serial| serial.map(|days| days as i32))
+                .collect();
+            Series::new(name, days).cast(&DataType::Date)?
+        } else {
+            let microseconds: Vec<Option<i64>> = serials
+                .iter()
+                .map(|serial| serial.map(|days| (days * MICROSECONDS_PER_DAY).round() as i64))
+                .collect();
+            Series::new(name, microseconds)
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+        }
+    } else {
+        let values: Vec<Option<String>> = cells
+            .iter()
+            .map(|cell| match cell {
+                CellType::Empty => None,
+                cell => Some(cell.to_string()),
+            })
+            .collect();
+        Series::new(name, values)
+    };
+
+    Ok(series)
+}
+
+// The first row holds the column names. Nulls are written as empty cells,
+// and so are NaN and infinities since Excel cannot store them. Excel keeps
+// every number as a double, so integer columns are read back as floats and
+// integers beyond 2^53 lose precision.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_xlsx(
+    data: ExDataFrame,
+    filename: &str,
+    sheet_name: &str,
+) -> Result<(), ExplorerError> {
+    let df = &data.resource.0;
+
+    if df.height() >= XLSX_MAX_ROWS || df.width() > XLSX_MAX_COLUMNS {
+        return Err(ExplorerError::Other(format!(
+            "xlsx sheets are limited to {} rows and {} columns, got {} rows and {} columns",
+            XLSX_MAX_ROWS - 1,
+            XLSX_MAX_COLUMNS,
+            df.height(),
+            df.width()
+        )));
+    }
+
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet_name)?;
+
+    for (col, s) in df.get_columns().iter().enumerate() {
+        let col = u16::try_from(col)?;
+        worksheet.write_string(0, col, s.name())?;
+
+        match s.dtype() {
+            DataType::Utf8 | DataType::Categorical(_) => {
+                let s = s.cast(&DataType::Utf8)?;
+                for (row, value) in xlsx_rows(s.utf8()?.into_iter()) {
+                    worksheet.write_string(row, col, value)?;
+                }
+            }
+            DataType::Boolean => {
+                for (row, value) in xlsx_rows(s.bool()?.into_iter()) {
+                    worksheet.write_boolean(row, col, value)?;
+                }
+            }
+            DataType::Date => {
+                let s = s.to_physical_repr();
+                for (row, days) in xlsx_rows(s.i32()?.into_iter()) {
+                    let serial = days as f64 + XLSX_EPOCH_OFFSET;
+                    worksheet.write_number_with_format(row, col, serial, &date_format)?;
+                }
+            }
+            DataType::Datetime(_, _) => {
+                let s = s.cast(&DataType::Datetime(TimeUnit::Microseconds, None))?;
+                let s = s.to_physical_repr();
+                for (row, microseconds) in xlsx_rows(s.i64()?.into_iter()) {
+                    let serial = microseconds as f64 / MICROSECONDS_PER_DAY + XLSX_EPOCH_OFFSET;
+                    worksheet.write_number_with_format(row, col, serial, &datetime_format)?;
+                }
+            }
+            dtype if dtype.is_numeric() => {
+                let s = s.cast(&DataType::Float64)?;
+                for (row, value) in xlsx_rows(s.f64()?.into_iter()) {
+                    if value.is_finite() {
+                        worksheet.write_number(row, col, value)?;
+                    }
+                }
+            }
+            dtype => {
+                return Err(ExplorerError::Other(format!(
+                    "cannot write columns of dtype {} to xlsx",
+                    dtype
+                )))
+            }
+        }
+    }
+
+    workbook.save(filename)?;
+    Ok(())
+}
+
+// Pairs each present value with its sheet row, which is offset by the header.
+fn xlsx_rows<T>(values: impl Iterator<Item = Option<T>>) -> impl Iterator<Item = (u32, T)> {
+    values
+        .enumerate()
+        .filter_map(|(index, value)| value.map(|value| (index as u32 + 1, value)))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_join(
     data: ExDataFrame,
@@ -234,6 +1186,7 @@ pub fn df_join(
     right_on: Vec<&str>,
     how: &str,
     suffix: Option<String>,
+    parallel: bool,
 ) -> Result<ExDataFrame, ExplorerError> {
     let how = match how {
         "left" => JoinType::Left,
@@ -248,10 +1201,28 @@ pub fn df_join(
         }
     };
 
-    let df = &data.resource.0;
-    let df1 = &other.resource.0;
-    let new_df = df.join(df1, left_on, right_on, how, suffix)?;
-    Ok(ExDataFrame::new(new_df))
+    // The lazy join runs the same join as DataFrame::join, but it is
+    // the one that lets us choose whether both sides run in parallel.
+    let left_on: Vec<Expr> = left_on.iter().map(|name| col(name)).collect();
+    let right_on: Vec<Expr> = right_on.iter().map(|name| col(name)).collect();
+    let mut join = data
+        .resource
+        .0
+        .clone()
+        .lazy()
+        .join_builder()
+        .with(other.resource.0.clone().lazy())
+        .left_on(left_on)
+        .right_on(right_on)
+        .how(how)
+        .allow_parallel(parallel)
+        .force_parallel(false);
+
+    if let Some(suffix) = suffix {
+        join = join.suffix(suffix);
+    }
+
+    Ok(ExDataFrame::new(join.finish().collect()?))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -278,9 +1249,194 @@ pub fn df_shape(data: ExDataFrame) -> Result<(usize, usize), ExplorerError> {
     Ok(data.resource.0.shape())
 }
 
-#[rustler::nif]
-pub fn df_height(data: ExDataFrame) -> Result<usize, ExplorerError> {
-    Ok(data.resource.0.height())
+#[rustler::nif]
+pub fn df_height(data: ExDataFrame) -> Result<usize, ExplorerError> {
+    Ok(data.resource.0.height())
+}
+
+#[rustler::nif]
+pub fn df_estimated_size(data: ExDataFrame) -> Result<usize, ExplorerError> {
+    Ok(data.resource.0.estimated_size())
+}
+
+// The largest number of chunks across the columns. Every vstack adds
+// chunks until the frame is rechunked.
+#[rustler::nif]
+pub fn df_n_chunks(data: ExDataFrame) -> Result<usize, ExplorerError> {
+    let df = &data.resource.0;
+    Ok(df
+        .get_columns()
+        .iter()
+        .map(|s| s.n_chunks())
+        .max()
+        .unwrap_or(0))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_rechunk(data: ExDataFrame) -> Result<ExDataFrame, ExplorerError> {
+    let mut df = data.resource.0.clone();
+    df.rechunk();
+    Ok(ExDataFrame::new(df))
+}
+
+// Hashes whole rows across the given columns (all of them by default).
+// See the hashing module for the stability guarantees.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_hash_rows(
+    data: ExDataFrame,
+    columns: Option<Vec<&str>>,
+    seed: u64,
+) -> Result<ExSeries, ExplorerError> {
+    let df = &data.resource.0;
+    let columns = match columns {
+        Some(names) => df.columns(names)?.into_iter().cloned().collect(),
+        None => df.get_columns().to_vec(),
+    };
+
+    Ok(ExSeries::new(hash_rows("hash", &columns, seed)?))
+}
+
+// Computes the pairwise correlation matrix of the numeric columns (or of the
+// given columns, which must all be numeric). Every pair becomes a single
+// aggregation of one lazy plan, so the frame is only traversed by polars.
+// Each pair only considers the rows where both values are present, and
+// constant columns correlate as nil instead of NaN.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_correlation(
+    data: ExDataFrame,
+    columns: Option<Vec<&str>>,
+    method: &str,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = &data.resource.0;
+    let spearman = match method {
+        "pearson" => false,
+        "spearman" => true,
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Invalid correlation method: {}, expected one of: pearson, spearman",
+                other
+            )))
+        }
+    };
+
+    let names: Vec<String> = match columns {
+        Some(names) => {
+            for name in &names {
+                let dtype = df.column(name)?.dtype();
+
+                if !dtype.is_numeric() {
+                    return Err(ExplorerError::Other(format!(
+                        "cannot compute correlation of column {} with type {}",
+                        name, dtype
+                    )));
+                }
+            }
+
+            names.iter().map(|name| name.to_string()).collect()
+        }
+        None => df
+            .get_columns()
+            .iter()
+            .filter(|s| s.dtype().is_numeric())
+            .map(|s| s.name().to_string())
+            .collect(),
+    };
+
+    let mut exprs = Vec::with_capacity(names.len() * (names.len() + 1) / 2);
+    for (i, left) in names.iter().enumerate() {
+        for (j, right) in names.iter().enumerate().skip(i) {
+            exprs.push(correlation_expr(left, right, spearman).alias(&format!("{}_{}", i, j)));
+        }
+    }
+
+    let pairs = if exprs.is_empty() {
+        DataFrame::default()
+    } else {
+        df.clone().lazy().select(exprs).collect()?
+    };
+
+    let mut matrix = vec![Series::new("names", &names)];
+    for (j, name) in names.iter().enumerate() {
+        let values = (0..names.len())
+            .map(|i| {
+                let (i, j) = if i <= j { (i, j) } else { (j, i) };
+                Ok(pairs.column(&format!("{}_{}", i, j))?.f64()?.get(0))
+            })
+            .collect::<Result<Vec<Option<f64>>, PolarsError>>()?;
+
+        matrix.push(Series::new(name, values));
+    }
+
+    Ok(ExDataFrame::new(DataFrame::new(matrix)?))
+}
+
+fn correlation_expr(left: &str, right: &str, spearman: bool) -> Expr {
+    let present = col(left).is_not_null().and(col(right).is_not_null());
+    let [x, y] = [left, right].map(|name| {
+        let values = col(name).filter(present.clone());
+        let values = if spearman {
+            values.rank(RankOptions {
+                method: RankMethod::Average,
+                descending: false,
+            })
+        } else {
+            values
+        };
+        let values = values.cast(DataType::Float64);
+
+        values.clone() - values.mean()
+    });
+
+    let covariance = (x.clone() * y.clone()).sum();
+    let spread = ((x.clone() * x).sum() * (y.clone() * y).sum()).pow(lit(0.5));
+
+    when(spread.clone().eq(lit(0.0)))
+        .then(Expr::Literal(LiteralValue::Null))
+        .otherwise(covariance / spread)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_to_rows(env: Env, data: ExDataFrame, atom_keys: bool) -> Result<Term, ExplorerError> {
+    rows_from_dataframe(&data.resource.0, atom_keys, env)
+}
+
+// Rows are encoded lazily, one batch per call to df_to_rows_stream_next,
+// so only a single batch lives on the Elixir heap at a time.
+#[rustler::nif]
+pub fn df_to_rows_stream(
+    data: ExDataFrame,
+    chunk_size: usize,
+) -> Result<ResourceArc<ExRowStreamRef>, ExplorerError> {
+    if chunk_size == 0 {
+        return Err(ExplorerError::Other(
+            "chunk size must be greater than zero".into(),
+        ));
+    }
+
+    let df = data.resource.0.clone();
+    Ok(ResourceArc::new(ExRowStreamRef::new(df, chunk_size)))
+}
+
+// Returns the next batch of rows, or nil once the frame is exhausted.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_to_rows_stream_next(
+    env: Env,
+    stream: ResourceArc<ExRowStreamRef>,
+    atom_keys: bool,
+) -> Result<Option<Term>, ExplorerError> {
+    let mut offset = stream
+        .offset
+        .lock()
+        .map_err(|_| ExplorerError::Internal("row stream lock is poisoned".into()))?;
+
+    if *offset >= stream.df.height() {
+        return Ok(None);
+    }
+
+    let chunk = stream.df.slice(*offset as i64, stream.chunk_size);
+    *offset += chunk.height();
+
+    Ok(Some(rows_from_dataframe(&chunk, atom_keys, env)?))
 }
 
 #[rustler::nif]
@@ -389,16 +1545,45 @@ pub fn df_select(data: ExDataFrame, selection: Vec<&str>) -> Result<ExDataFrame,
     Ok(ExDataFrame::new(new_df))
 }
 
+// As with s_mask, the mask must have exactly one value per row and nils
+// in the mask drop the row.
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_mask(data: ExDataFrame, mask: ExSeries) -> Result<ExDataFrame, ExplorerError> {
     let df = &data.resource.0;
-    let filter_series = &mask.resource.0;
-    if let Ok(ca) = filter_series.bool() {
-        let new_df = df.filter(ca)?;
-        Ok(ExDataFrame::new(new_df))
-    } else {
-        Err(ExplorerError::Other("Expected a boolean mask".into()))
+    let mask = &mask.resource.0;
+
+    if mask.len() != df.height() {
+        return Err(ExplorerError::Other(format!(
+            "mask must have the same length as the dataframe height, got {} and {}",
+            mask.len(),
+            df.height()
+        )));
+    }
+
+    let mask = mask
+        .bool()
+        .map_err(|_| ExplorerError::Other("Expected a boolean mask".into()))?
+        .fill_null_with_values(false)?;
+
+    Ok(ExDataFrame::new(df.filter(&mask)?))
+}
+
+// Gathers rows by position across all columns at once. Indices may repeat
+// and come in any order, but must all be within the frame height.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_take(data: ExDataFrame, indices: Vec<u32>) -> Result<ExDataFrame, ExplorerError> {
+    let df = &data.resource.0;
+
+    if let Some(idx) = indices.iter().find(|idx| **idx as usize >= df.height()) {
+        return Err(ExplorerError::Other(format!(
+            "Index {} is out of bounds for dataframe of height {}",
+            idx,
+            df.height()
+        )));
     }
+
+    let idx = UInt32Chunked::from_vec("idx", indices);
+    Ok(ExDataFrame::new(df.take(&idx)?))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -431,28 +1616,87 @@ pub fn df_slice_by_indices(
     Ok(ExDataFrame::new(new_df))
 }
 
+// Sorts by the given columns, with one direction per column. With
+// maintain_order, rows with equal keys keep their original order. Without
+// groups, the first column is flagged as sorted unless it has nulls.
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_sort(
     data: ExDataFrame,
     by_columns: Vec<String>,
     reverse: Vec<bool>,
+    nulls_last: bool,
+    maintain_order: bool,
     groups: Vec<String>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let df: DataFrame = data.resource.0.clone();
+    if by_columns.len() != reverse.len() {
+        return Err(ExplorerError::Other(format!(
+            "expected one direction per column, got {} columns and {} directions",
+            by_columns.len(),
+            reverse.len()
+        )));
+    }
+
+    let df = &data.resource.0;
 
     let new_df = if groups.is_empty() {
-        let new_df = &df.sort(by_columns, reverse)?;
-        new_df.clone()
+        let mut new_df = sort_frame(df, &by_columns, &reverse, nulls_last, maintain_order)?;
+
+        if let Some(name) = by_columns.first() {
+            if new_df.column(name)?.null_count() == 0 {
+                let flag = if reverse[0] {
+                    IsSorted::Descending
+                } else {
+                    IsSorted::Ascending
+                };
+
+                new_df.apply(name, |s| {
+                    let mut s = s.clone();
+                    s.set_sorted(flag);
+                    s
+                })?;
+            }
+        }
+
+        new_df
     } else {
-        let new_df = &df
-            .groupby_stable(groups)?
-            .apply(|df| df.sort(by_columns.clone(), reverse.clone()))?;
-        new_df.clone()
+        df.groupby_stable(groups)?
+            .apply(|df| sort_frame(&df, &by_columns, &reverse, nulls_last, maintain_order))?
     };
 
     Ok(ExDataFrame::new(new_df))
 }
 
+const SORT_ROW_INDEX: &str = "__explorer_sort_row_index__";
+
+fn sort_frame(
+    df: &DataFrame,
+    by_columns: &[String],
+    reverse: &[bool],
+    nulls_last: bool,
+    maintain_order: bool,
+) -> Result<DataFrame, PolarsError> {
+    let mut exprs: Vec<Expr> = by_columns.iter().map(|name| col(name)).collect();
+    let mut reverse = reverse.to_vec();
+
+    if maintain_order {
+        // Breaking ties by the original position makes the sort stable.
+        exprs.push(col(SORT_ROW_INDEX));
+        reverse.push(false);
+
+        df.clone()
+            .lazy()
+            .with_row_count(SORT_ROW_INDEX, None)
+            .sort_by_exprs(exprs, reverse, nulls_last)
+            .drop_columns([SORT_ROW_INDEX])
+            .collect()
+    } else {
+        df.clone()
+            .lazy()
+            .sort_by_exprs(exprs, reverse, nulls_last)
+            .collect()
+    }
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_arrange_with(
     data: ExDataFrame,
@@ -495,7 +1739,7 @@ pub fn df_head(
     length: Option<usize>,
     groups: Vec<&str>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let df: DataFrame = data.resource.0.clone();
+    let df = &data.resource.0;
 
     let new_df = if groups.is_empty() {
         df.head(length)
@@ -511,7 +1755,7 @@ pub fn df_tail(
     length: Option<usize>,
     groups: Vec<&str>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let df: DataFrame = data.resource.0.clone();
+    let df = &data.resource.0;
 
     let new_df = if groups.is_empty() {
         df.tail(length)
@@ -569,6 +1813,12 @@ pub fn df_to_dummies(
     Ok(ExDataFrame::new(new_df))
 }
 
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_unnest(data: ExDataFrame, columns: Vec<String>) -> Result<ExDataFrame, ExplorerError> {
+    let df = &data.resource.0;
+    Ok(ExDataFrame::new(df.unnest(columns)?))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_with_columns(
     data: ExDataFrame,
@@ -606,6 +1856,316 @@ pub fn df_new(columns: Vec<ExSeries>) -> Result<ExDataFrame, ExplorerError> {
     Ok(ExDataFrame::new(df))
 }
 
+// Builds a frame out of a list of maps or keyword lists, one per row, with
+// string or atom keys. Columns in `schema` come first, followed by the other
+// keys in the order they are first seen. Rows without a key get a nil.
+//
+// Types missing from `schema` are inferred from the first
+// `infer_schema_length` rows (all of them when nil). Integers and floats
+// in the same column become floats. Any other mix of types, or a value
+// that does not fit its column, is an error when `strict` is true.
+// Otherwise mixed columns become strings and misfits become nil.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_from_rows(
+    rows: Vec<Term>,
+    schema: Option<Vec<(String, String)>>,
+    infer_schema_length: Option<usize>,
+    strict: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut names: Vec<String> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut dtypes: HashMap<String, DataType> = HashMap::new();
+
+    for (name, dtype) in schema.unwrap_or_default() {
+        dtypes.insert(name.clone(), cast_str_to_dtype(&dtype)?);
+
+        if !positions.contains_key(&name) {
+            positions.insert(name.clone(), names.len());
+            names.push(name);
+        }
+    }
+
+    let mut columns: Vec<Vec<Option<Term>>> = vec![Vec::with_capacity(rows.len()); names.len()];
+
+    for (i, row) in rows.iter().enumerate() {
+        for column in columns.iter_mut() {
+            column.push(None);
+        }
+
+        for (key, value) in row_entries(*row)? {
+            let name = row_key(key)?;
+            let position = match positions.get(&name) {
+                Some(position) => *position,
+                None => {
+                    positions.insert(name.clone(), names.len());
+                    names.push(name);
+                    columns.push(vec![None; i + 1]);
+                    names.len() - 1
+                }
+            };
+
+            if !is_nil(value) {
+                columns[position][i] = Some(value);
+            }
+        }
+    }
+
+    let inferred_rows = infer_schema_length.unwrap_or(rows.len()).min(rows.len());
+    let series = names
+        .iter()
+        .zip(columns.iter())
+        .map(|(name, values)| {
+            let dtype = match dtypes.get(name) {
+                Some(dtype) => dtype.clone(),
+                None => infer_row_dtype(name, &values[..inferred_rows], strict)?,
+            };
+
+            series_from_row_values(name, values, &dtype, strict)
+        })
+        .collect::<Result<Vec<Series>, ExplorerError>>()?;
+
+    Ok(ExDataFrame::new(DataFrame::new(series)?))
+}
+
+fn row_entries(row: Term) -> Result<Vec<(Term, Term)>, ExplorerError> {
+    match MapIterator::new(row) {
+        Some(entries) => Ok(entries.collect()),
+        None => row
+            .decode::<Vec<(Term, Term)>>()
+            .map_err(|_| ExplorerError::Other("expected rows to be maps or keyword lists".into())),
+    }
+}
+
+fn row_key(key: Term) -> Result<String, ExplorerError> {
+    let name = if key.is_atom() {
+        key.atom_to_string()
+    } else {
+        key.decode::<String>()
+    };
+
+    name.map_err(|_| ExplorerError::Other("expected row keys to be strings or atoms".into()))
+}
+
+fn is_nil(term: Term) -> bool {
+    term.decode::<Atom>()
+        .map_or(false, |value| value == atom::nil())
+}
+
+fn decode_row_float(term: Term) -> Option<f64> {
+    if let Ok(value) = term.decode::<f64>() {
+        return Some(value);
+    }
+
+    if let Ok(value) = term.decode::<i64>() {
+        return Some(value as f64);
+    }
+
+    match term.decode::<Atom>() {
+        Ok(value) if value == nan() => Some(f64::NAN),
+        Ok(value) if value == infinity() => Some(f64::INFINITY),
+        Ok(value) if value == neg_infinity() => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+// Strings are kept as they are and every other value is formatted the
+// way Elixir's to_string/1 would.
+fn stringify_row_value(term: Term) -> Option<String> {
+    if let Ok(value) = term.decode::<String>() {
+        Some(value)
+    } else if let Ok(value) = term.decode::<bool>() {
+        Some(value.to_string())
+    } else if let Ok(value) = term.decode::<i64>() {
+        Some(value.to_string())
+    } else if let Ok(value) = term.decode::<f64>() {
+        Some(format!("{:?}", value))
+    } else if let Ok(value) = term.decode::<ExDate>() {
+        Some(NaiveDate::from(value).to_string())
+    } else if let Ok(value) = term.decode::<ExDateTime>() {
+        Some(NaiveDateTime::from(value).to_string())
+    } else {
+        None
+    }
+}
+
+fn row_value_dtype(name: &str, term: Term) -> Result<DataType, ExplorerError> {
+    if term.decode::<bool>().is_ok() {
+        Ok(DataType::Boolean)
+    } else if term.decode::<i64>().is_ok() {
+        Ok(DataType::Int64)
+    } else if decode_row_float(term).is_some() {
+        Ok(DataType::Float64)
+    } else if term.decode::<String>().is_ok() {
+        Ok(DataType::Utf8)
+    } else if term.is_binary() {
+        Ok(DataType::Binary)
+    } else if term.decode::<ExDate>().is_ok() {
+        Ok(DataType::Date)
+    } else if term.decode::<ExDateTime>().is_ok() {
+        Ok(DataType::Datetime(TimeUnit::Microseconds, None))
+    } else {
+        Err(ExplorerError::Other(format!(
+            "cannot infer the type of column {}, got an unsupported value",
+            name
+        )))
+    }
+}
+
+fn infer_row_dtype(
+    name: &str,
+    values: &[Option<Term>],
+    strict: bool,
+) -> Result<DataType, ExplorerError> {
+    let mut inferred: Option<DataType> = None;
+
+    for value in values.iter().flatten() {
+        let dtype = row_value_dtype(name, *value)?;
+
+        inferred = match inferred {
+            None => Some(dtype),
+            Some(current) if current == dtype => Some(current),
+            Some(DataType::Int64 | DataType::Float64)
+                if matches!(dtype, DataType::Int64 | DataType::Float64) =>
+            {
+                Some(DataType::Float64)
+            }
+            Some(current) if strict => {
+                return Err(ExplorerError::Other(format!(
+                    "column {} has values of conflicting types {} and {}",
+                    name, current, dtype
+                )))
+            }
+            Some(_) => Some(DataType::Utf8),
+        };
+    }
+
+    // Columns with only nils are floats, as in Series.from_list/2.
+    Ok(inferred.unwrap_or(DataType::Float64))
+}
+
+fn decode_row_values<'a, T>(
+    name: &str,
+    values: &[Option<Term<'a>>],
+    dtype: &DataType,
+    strict: bool,
+    decode: impl Fn(Term<'a>) -> Option<T>,
+) -> Result<Vec<Option<T>>, ExplorerError> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(row, value)| match value.map(&decode) {
+            None => Ok(None),
+            Some(Some(value)) => Ok(Some(value)),
+            Some(None) if strict => Err(ExplorerError::Other(format!(
+                "cannot convert the value of column {} at row {} to {}",
+                name, row, dtype
+            ))),
+            Some(None) => Ok(None),
+        })
+        .collect()
+}
+
+// Values are decoded into the closest type Elixir can represent and then
+// cast into the requested one.
+fn series_from_row_values(
+    name: &str,
+    values: &[Option<Term>],
+    dtype: &DataType,
+    strict: bool,
+) -> Result<Series, ExplorerError> {
+    let series = match dtype {
+        DataType::Boolean => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                term.decode::<bool>().ok()
+            })?;
+            Series::new(name, decoded)
+        }
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                term.decode::<i64>().ok()
+            })?;
+            Series::new(name, decoded)
+        }
+        DataType::Float32 | DataType::Float64 => {
+            let decoded = decode_row_values(name, values, dtype, strict, decode_row_float)?;
+            Series::new(name, decoded)
+        }
+        DataType::Utf8 | DataType::Categorical(_) => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                if strict {
+                    term.decode::<String>().ok()
+                } else {
+                    stringify_row_value(term)
+                }
+            })?;
+            Series::new(name, decoded)
+        }
+        DataType::Binary => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                term.decode::<Binary>().ok()
+            })?;
+            let bytes: Vec<Option<&[u8]>> = decoded
+                .iter()
+                .map(|binary| binary.as_ref().map(|binary| binary.as_slice()))
+                .collect();
+            Series::new(name, bytes)
+        }
+        DataType::Date => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                term.decode::<ExDate>().ok().map(i32::from)
+            })?;
+            Series::new(name, decoded)
+        }
+        DataType::Datetime(_, _) => {
+            let decoded = decode_row_values(name, values, dtype, strict, |term| {
+                term.decode::<ExDateTime>().ok().map(i64::from)
+            })?;
+            Series::new(name, decoded).cast(&DataType::Datetime(TimeUnit::Microseconds, None))?
+        }
+        dtype => {
+            return Err(ExplorerError::Other(format!(
+                "cannot build column {} with type {} from rows",
+                name, dtype
+            )))
+        }
+    };
+
+    if series.dtype() == dtype {
+        Ok(series)
+    } else if strict {
+        Ok(series.strict_cast(dtype)?)
+    } else {
+        Ok(series.cast(dtype)?)
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_with_row_count(
+    data: ExDataFrame,
+    name: &str,
+    offset: u32,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = &data.resource.0;
+
+    if df.get_column_names().contains(&name) {
+        return Err(ExplorerError::Other(format!(
+            "Column {} already exists",
+            name
+        )));
+    }
+
+    let mut new_df = df.with_row_count(name, Some(offset))?;
+    new_df.try_apply(name, |s: &Series| s.cast(&DataType::Int64))?;
+    Ok(ExDataFrame::new(new_df))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_rename_columns(
     data: ExDataFrame,
@@ -613,12 +2173,103 @@ pub fn df_rename_columns(
 ) -> Result<ExDataFrame, ExplorerError> {
     let mut df: DataFrame = data.resource.0.clone();
     for (original, new_name) in renames {
-        df.rename(original, new_name).expect("should rename");
+        df.rename(original, new_name)?;
     }
 
     Ok(ExDataFrame::new(df))
 }
 
+// Applies every rename at once, so names can be swapped. All sources
+// renamed more than once, all missing sources and all clashing targets
+// are reported together.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_rename_many(
+    data: ExDataFrame,
+    pairs: Vec<(String, String)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut df: DataFrame = data.resource.0.clone();
+    let mut sources: Vec<&str> = pairs.iter().map(|(from, _to)| from.as_str()).collect();
+    sources.sort_unstable();
+
+    let mut repeated: Vec<&str> = sources
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0])
+        .collect();
+    repeated.dedup();
+
+    if !repeated.is_empty() {
+        return Err(ExplorerError::Other(format!(
+            "cannot rename columns more than once: {}",
+            repeated.join(", ")
+        )));
+    }
+
+    let renames: std::collections::HashMap<&str, &str> = pairs
+        .iter()
+        .map(|(from, to)| (from.as_str(), to.as_str()))
+        .collect();
+
+    let missing: Vec<&str> = sources
+        .into_iter()
+        .filter(|from| df.get_column_names().iter().all(|name| name != from))
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(ExplorerError::Other(format!(
+            "cannot rename missing columns: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| renames.get(name).unwrap_or(name).to_string())
+        .collect();
+
+    check_unique_names(&names)?;
+    df.set_column_names(&names)?;
+    Ok(ExDataFrame::new(df))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_rename_with_affix(
+    data: ExDataFrame,
+    prefix: &str,
+    suffix: &str,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut df: DataFrame = data.resource.0.clone();
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| format!("{}{}{}", prefix, name, suffix))
+        .collect();
+
+    df.set_column_names(&names)?;
+    Ok(ExDataFrame::new(df))
+}
+
+fn check_unique_names(names: &[String]) -> Result<(), ExplorerError> {
+    let mut seen = std::collections::HashSet::new();
+    let mut duplicated: Vec<&str> = vec![];
+
+    for name in names {
+        if !seen.insert(name) && !duplicated.contains(&name.as_str()) {
+            duplicated.push(name.as_str());
+        }
+    }
+
+    if duplicated.is_empty() {
+        Ok(())
+    } else {
+        Err(ExplorerError::Other(format!(
+            "renames would produce duplicated columns: {}",
+            duplicated.join(", ")
+        )))
+    }
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_groups(data: ExDataFrame, groups: Vec<&str>) -> Result<ExDataFrame, ExplorerError> {
     let df = &data.resource.0;
@@ -637,6 +2288,30 @@ pub fn df_group_indices(data: ExDataFrame, groups: Vec<&str>) -> Result<ExSeries
     Ok(series)
 }
 
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_partition_by(
+    data: ExDataFrame,
+    by: Vec<String>,
+    maintain_order: bool,
+    include_key: bool,
+) -> Result<Vec<ExDataFrame>, ExplorerError> {
+    let df = &data.resource.0;
+    let partitions = match maintain_order {
+        true => df.partition_by_stable(by.clone())?,
+        false => df.partition_by(by.clone())?,
+    };
+
+    let partitions = partitions
+        .into_iter()
+        .map(|partition| match include_key {
+            true => ExDataFrame::new(partition),
+            false => ExDataFrame::new(partition.drop_many(&by)),
+        })
+        .collect();
+
+    Ok(partitions)
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_groupby_agg_with(
     data: ExDataFrame,
@@ -647,10 +2322,74 @@ pub fn df_groupby_agg_with(
     let groups = ex_expr_to_exprs(groups);
     let aggs = ex_expr_to_exprs(aggs);
 
+    // Groups can be any expression, such as a derived date, and show up in
+    // the output under their alias. Every output column must be unique.
+    let schema = df.schema();
+    let mut group_names = std::collections::HashSet::new();
+    let mut agg_names = std::collections::HashSet::new();
+
+    for expr in groups.iter() {
+        let name = expr.to_field(&schema, Context::Default)?.name().to_string();
+
+        if !group_names.insert(name.clone()) {
+            return Err(ExplorerError::Other(format!(
+                "column {} is used as a group more than once",
+                name
+            )));
+        }
+    }
+
+    for expr in aggs.iter() {
+        let name = expr.to_field(&schema, Context::Default)?.name().to_string();
+
+        if group_names.contains(&name) {
+            return Err(ExplorerError::Other(format!(
+                "column {} is used both as a group and as an aggregation",
+                name
+            )));
+        }
+
+        if !agg_names.insert(name.clone()) {
+            return Err(ExplorerError::Other(format!(
+                "column {} is produced by more than one aggregation",
+                name
+            )));
+        }
+    }
+
     let new_df = df.lazy().groupby_stable(groups).agg(aggs).collect()?;
     Ok(ExDataFrame::new(new_df))
 }
 
+#[rustler::nif]
+pub fn df_describe_plan(
+    data: ExDataFrame,
+    exprs: Vec<ExExpr>,
+    operation: &str,
+    optimized: bool,
+) -> Result<String, ExplorerError> {
+    let lf = data.resource.0.clone().lazy();
+    let exprs = ex_expr_to_exprs(exprs);
+
+    let lf = match operation {
+        "select" => lf.select(exprs),
+        "with_columns" => lf.with_columns(exprs),
+        "groupby" => lf.groupby_stable(exprs).agg([col("*")]),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Operation {} not supported, expected select, with_columns or groupby",
+                other
+            )))
+        }
+    };
+
+    let plan = match optimized {
+        true => lf.describe_optimized_plan()?,
+        false => lf.describe_plan(),
+    };
+    Ok(plan)
+}
+
 fn ex_expr_to_exprs(ex_exprs: Vec<ExExpr>) -> Vec<Expr> {
     let exprs: Vec<Expr> = ex_exprs
         .iter()
@@ -684,3 +2423,160 @@ pub fn df_to_lazy(data: ExDataFrame) -> Result<ExLazyFrame, ExplorerError> {
     let new_lf = data.resource.0.clone().lazy();
     Ok(ExLazyFrame::new(new_lf))
 }
+
+// Arrow C Data Interface
+//
+// `df_export_arrow_c` returns a resource owning heap allocated `ArrowSchema` and
+// `ArrowArray` structs, one struct array per chunk, along with their addresses.
+// The addresses can be handed to other libraries while the resource is alive.
+// The exported buffers stay alive until the release callback of each struct is
+// called, so the source dataframe can be garbage collected in the meantime.
+//
+// `df_import_arrow_c` takes the addresses of an `ArrowSchema` holding a struct
+// type and of one struct `ArrowArray` per chunk, from this library or any other
+// producer. Following the interface's move semantics, it moves the contents out
+// of each struct and marks the struct as released. From then on the schema is
+// released once it is read and the arrays are released when the imported
+// dataframe is dropped. The memory holding the structs themselves still belongs
+// to the producer, which must keep it valid during the call and then free it
+// without calling the release callbacks again. The resource returned by
+// `df_export_arrow_c` does exactly that when it is dropped.
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_export_arrow_c(
+    data: ExDataFrame,
+) -> Result<(ResourceArc<ExArrowCRef>, usize, Vec<usize>), ExplorerError> {
+    let mut df = data.resource.0.clone();
+    df.rechunk();
+
+    let fields: Vec<ArrowField> = df.iter().map(|s| s.field().to_arrow()).collect();
+    let data_type = ArrowDataType::Struct(fields);
+    let schema = Box::new(ffi::export_field_to_c(&ArrowField::new(
+        "",
+        data_type.clone(),
+        false,
+    )));
+
+    let arrays: Vec<Box<ffi::ArrowArray>> = df
+        .iter_chunks()
+        .map(|chunk| {
+            let array = StructArray::new(data_type.clone(), chunk.into_arrays(), None);
+            Box::new(unsafe { ffi::export_array_to_c(Box::new(array)) })
+        })
+        .collect();
+
+    let schema_ptr = &*schema as *const ffi::ArrowSchema as usize;
+    let array_ptrs = arrays
+        .iter()
+        .map(|array| &**array as *const ffi::ArrowArray as usize)
+        .collect();
+
+    Ok((
+        ResourceArc::new(ExArrowCRef::new(schema, arrays)),
+        schema_ptr,
+        array_ptrs,
+    ))
+}
+
+// The addresses must point to live structs laid out as in the Arrow C Data
+// Interface. They cannot be validated beyond checking for null pointers and
+// structs that were already released.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_import_arrow_c(
+    schema_ptr: usize,
+    array_ptrs: Vec<usize>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let schema_ptr = schema_ptr as *mut ffi::ArrowSchema;
+    let array_ptrs: Vec<*mut ffi::ArrowArray> = array_ptrs
+        .into_iter()
+        .map(|ptr| ptr as *mut ffi::ArrowArray)
+        .collect();
+
+    unsafe {
+        check_arrow_c_struct(schema_ptr as *const u8, ARROW_SCHEMA_RELEASE_OFFSET)?;
+
+        for ptr in &array_ptrs {
+            check_arrow_c_struct(*ptr as *const u8, ARROW_ARRAY_RELEASE_OFFSET)?;
+        }
+    }
+
+    // Moving the contents out leaves released structs behind, whose release
+    // callbacks are null, so the producer won't release them a second time.
+    let schema = unsafe { std::ptr::replace(schema_ptr, ffi::ArrowSchema::empty()) };
+    let arrays: Vec<ffi::ArrowArray> = array_ptrs
+        .into_iter()
+        .map(|ptr| unsafe { std::ptr::replace(ptr, ffi::ArrowArray::empty()) })
+        .collect();
+
+    let field = unsafe { ffi::import_field_from_c(&schema)? };
+    let fields = match field.data_type() {
+        ArrowDataType::Struct(fields) => fields.clone(),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Expected a struct schema, got: {:?}",
+                other
+            )))
+        }
+    };
+
+    let mut chunks = Vec::with_capacity(arrays.len());
+
+    // Imported arrays take over the release callbacks, keeping the exported
+    // buffers alive for as long as the new dataframe references them.
+    for array in arrays {
+        let array = unsafe { ffi::import_array_from_c(array, field.data_type().clone())? };
+        let struct_array = array
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .ok_or_else(|| ExplorerError::Other("Expected a struct array".into()))?;
+
+        let columns = fields
+            .iter()
+            .zip(struct_array.values())
+            .map(|(field, values)| Series::try_from((field.name.as_str(), values.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        chunks.push(DataFrame::new(columns)?);
+    }
+
+    let mut chunks = chunks.into_iter();
+    let mut df = match chunks.next() {
+        Some(df) => df,
+        None => DataFrame::new(
+            fields
+                .iter()
+                .map(|field| Series::new_empty(&field.name, &DataType::from(field.data_type())))
+                .collect(),
+        )?,
+    };
+
+    for chunk in chunks {
+        df.vstack_mut(&chunk)?;
+    }
+
+    Ok(ExDataFrame::new(df))
+}
+
+// Byte offsets of the release callback in `ArrowSchema` and `ArrowArray`,
+// as laid out by the Arrow C Data Interface on 64-bit platforms.
+const ARROW_SCHEMA_RELEASE_OFFSET: usize = 56;
+const ARROW_ARRAY_RELEASE_OFFSET: usize = 64;
+
+unsafe fn check_arrow_c_struct(ptr: *const u8, release_offset: usize) -> Result<(), ExplorerError> {
+    if ptr.is_null() {
+        return Err(ExplorerError::Other(
+            "Arrow C struct address must not be null".to_string(),
+        ));
+    }
+
+    let release = std::ptr::read_unaligned(ptr.add(release_offset) as *const usize);
+
+    if release == 0 {
+        Err(ExplorerError::Other(format!(
+            "Arrow C struct at address {} was already released",
+            ptr as usize
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -1,13 +1,38 @@
 use crate::atoms;
+use crate::ExplorerError;
 use chrono::prelude::*;
+use chrono::{Offset as _, TimeZone as _};
+use chrono_tz::Tz;
+use polars::export::arrow::ffi;
 use polars::prelude::*;
 use rustler::{Atom, NifStruct, ResourceArc};
 use std::convert::TryInto;
+use std::sync::Mutex;
 
 pub struct ExDataFrameRef(pub DataFrame);
 pub struct ExExprRef(pub Expr);
 pub struct ExLazyFrameRef(pub LazyFrame);
 pub struct ExSeriesRef(pub Series);
+// Hands out a frame in batches of rows. The offset is behind a mutex as the
+// same stream may be consumed from different processes.
+pub struct ExRowStreamRef {
+    pub df: DataFrame,
+    pub chunk_size: usize,
+    pub offset: Mutex<usize>,
+}
+
+// Owns the structs exported through the Arrow C Data Interface. They are boxed
+// so their addresses stay put while the resource is alive. Dropping a struct
+// calls its release callback unless a consumer already moved its contents out.
+pub struct ExArrowCRef {
+    _schema: Box<ffi::ArrowSchema>,
+    _arrays: Vec<Box<ffi::ArrowArray>>,
+}
+
+// The structs only hold pointers to buffers owned by their private data,
+// which is never shared, and Rust code never reads them after creation.
+unsafe impl Send for ExArrowCRef {}
+unsafe impl Sync for ExArrowCRef {}
 
 #[derive(NifStruct)]
 #[module = "Explorer.PolarsBackend.DataFrame"]
@@ -57,6 +82,25 @@ impl ExSeriesRef {
     }
 }
 
+impl ExRowStreamRef {
+    pub fn new(df: DataFrame, chunk_size: usize) -> Self {
+        Self {
+            df,
+            chunk_size,
+            offset: Mutex::new(0),
+        }
+    }
+}
+
+impl ExArrowCRef {
+    pub fn new(schema: Box<ffi::ArrowSchema>, arrays: Vec<Box<ffi::ArrowArray>>) -> Self {
+        Self {
+            _schema: schema,
+            _arrays: arrays,
+        }
+    }
+}
+
 impl ExDataFrame {
     pub fn new(df: DataFrame) -> Self {
         Self {
@@ -146,6 +190,111 @@ pub struct ExDateTime {
 
 pub use polars::export::arrow::temporal_conversions::date32_to_date as days_to_date;
 
+pub fn parse_time_unit(time_unit: &str) -> Result<TimeUnit, ExplorerError> {
+    match time_unit {
+        "ms" => Ok(TimeUnit::Milliseconds),
+        "us" => Ok(TimeUnit::Microseconds),
+        "ns" => Ok(TimeUnit::Nanoseconds),
+        other => Err(ExplorerError::Other(format!(
+            "Invalid time unit: {}, expected one of: ms, us, ns",
+            other
+        ))),
+    }
+}
+
+pub fn parse_time_zone(time_zone: &str) -> Result<Tz, ExplorerError> {
+    time_zone
+        .parse()
+        .map_err(|_| ExplorerError::Other(format!("Invalid time zone: {}", time_zone)))
+}
+
+/// Parses a fixed-length interval such as "15m" or "1h30m" into nanoseconds.
+/// Calendar units (months, years) have no fixed length and are rejected.
+pub fn parse_interval(interval: &str) -> Result<i64, ExplorerError> {
+    let invalid = || ExplorerError::Other(format!("Invalid interval: {}", interval));
+    let mut total: i64 = 0;
+    let mut rest = interval;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let nanoseconds = match &rest[..unit_len] {
+            "ns" => 1,
+            "us" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            "h" => 3_600 * 1_000_000_000,
+            "d" => 86_400 * 1_000_000_000,
+            "w" => 7 * 86_400 * 1_000_000_000,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[unit_len..];
+
+        total = amount
+            .checked_mul(nanoseconds)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(invalid)?;
+    }
+
+    if total > 0 {
+        Ok(total)
+    } else {
+        Err(invalid())
+    }
+}
+
+#[inline]
+fn units_per_second(time_unit: TimeUnit) -> i64 {
+    match time_unit {
+        TimeUnit::Milliseconds => 1_000,
+        TimeUnit::Microseconds => 1_000_000,
+        TimeUnit::Nanoseconds => 1_000_000_000,
+    }
+}
+
+#[inline]
+fn timestamp_to_naive(v: i64, time_unit: TimeUnit) -> NaiveDateTime {
+    let units = units_per_second(time_unit);
+    let nanoseconds = v.rem_euclid(units) * (1_000_000_000 / units);
+    NaiveDateTime::from_timestamp(v.div_euclid(units), nanoseconds as u32)
+}
+
+/// Shifts a UTC timestamp to the wall-clock time of the given time zone.
+pub fn utc_to_local(v: i64, time_unit: TimeUnit, time_zone: &Tz) -> i64 {
+    let offset = time_zone
+        .offset_from_utc_datetime(&timestamp_to_naive(v, time_unit))
+        .fix()
+        .local_minus_utc();
+
+    v + offset as i64 * units_per_second(time_unit)
+}
+
+/// Checks that a wall-clock timestamp maps to a single instant in the
+/// given time zone. Daylight saving transitions skip some wall-clock
+/// times and repeat others.
+pub fn check_local_time(v: i64, time_unit: TimeUnit, time_zone: &Tz) -> Result<(), String> {
+    let local = timestamp_to_naive(v, time_unit);
+
+    match time_zone.from_local_datetime(&local) {
+        LocalResult::Single(_) => Ok(()),
+        LocalResult::None => Err(format!(
+            "{} does not exist in time zone {}",
+            local, time_zone
+        )),
+        LocalResult::Ambiguous(_, _) => {
+            Err(format!("{} is ambiguous in time zone {}", local, time_zone))
+        }
+    }
+}
+
 /// Converts a microsecond i64 to a `NaiveDateTime`.
 /// This is because when getting a timestamp, it might have negative values.
 pub fn timestamp_to_datetime(microseconds: i64) -> NaiveDateTime {
@@ -1,12 +1,15 @@
 use chrono::prelude::*;
 use polars::prelude::*;
-use rustler::{Encoder, Env, ResourceArc, Term};
+use rustler::{Binary, Encoder, Env, NewBinary, ResourceArc, Term};
 
 use crate::atoms::{
     self, calendar, day, hour, infinity, microsecond, minute, month, nan, neg_infinity, second,
     year,
 };
-use crate::datatypes::{days_to_date, timestamp_to_datetime, ExSeries, ExSeriesRef};
+use crate::datatypes::{
+    days_to_date, parse_time_zone, timestamp_to_datetime, utc_to_local, ExSeries, ExSeriesRef,
+};
+use crate::ExplorerError;
 
 use rustler::types::atom;
 use rustler::wrapper::{binary, list, map, NIF_TERM};
@@ -234,9 +237,29 @@ fn encode_utf8_series<'b>(
     unsafe { Term::new(env, list) }
 }
 
+#[inline]
+fn encode_bytes<'b>(bytes: &[u8], env: Env<'b>) -> Term<'b> {
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.copy_from_slice(bytes);
+    Binary::from(binary).to_term(env)
+}
+
+#[inline]
+fn encode_binary_series<'b>(s: &Series, env: Env<'b>) -> Result<Term<'b>, ExplorerError> {
+    Ok(unsafe_iterator_to_list!(
+        env,
+        s.binary()?
+            .into_iter()
+            .map(|option| option.map(|bytes| encode_bytes(bytes, env)).encode(env))
+    ))
+}
+
 // Convert f64 series taking into account NaN and Infinity floats (they are encoded as atoms).
 #[inline]
-fn encode_float64_series<'b>(s: &Series, env: Env<'b>) -> Term<'b> {
+fn encode_float_series<'b, I>(values: I, env: Env<'b>) -> Term<'b>
+where
+    I: DoubleEndedIterator<Item = Option<f64>>,
+{
     let nan_atom = nan().encode(env);
     let neg_infinity_atom = neg_infinity().encode(env);
     let infinity_atom = infinity().encode(env);
@@ -244,7 +267,7 @@ fn encode_float64_series<'b>(s: &Series, env: Env<'b>) -> Term<'b> {
 
     unsafe_iterator_to_list!(
         env,
-        s.f64().unwrap().into_iter().map(|option| {
+        values.map(|option| {
             match option {
                 Some(x) => {
                     if x.is_finite() {
@@ -275,35 +298,15 @@ macro_rules! encode {
     };
 }
 
-macro_rules! encode_list {
-    ($s:ident, $env:ident, $convert_function:ident, $out_type:ty) => {
-        $s.list()
-            .unwrap()
-            .into_iter()
-            .map(|item| item)
-            .collect::<Vec<Option<Series>>>()
-            .iter()
-            .map(|item| {
-                item.clone()
-                    .unwrap()
-                    .$convert_function()
-                    .unwrap()
-                    .into_iter()
-                    .map(|item| item)
-                    .collect::<Vec<Option<$out_type>>>()
-            })
-            .collect::<Vec<Vec<Option<$out_type>>>>()
-            .encode($env)
-    };
-}
-
 // API
 
-pub fn term_from_value<'b>(v: AnyValue, env: Env<'b>) -> Term<'b> {
-    match v {
+pub fn term_from_value<'b>(v: AnyValue, env: Env<'b>) -> Result<Term<'b>, ExplorerError> {
+    let term = match v {
         AnyValue::Null => None::<bool>.encode(env),
         AnyValue::Boolean(v) => Some(v).encode(env),
         AnyValue::Utf8(v) => Some(v).encode(env),
+        AnyValue::Binary(v) => encode_bytes(v, env),
+        AnyValue::Categorical(idx, mapping) => Some(mapping.get(idx)).encode(env),
         AnyValue::Int8(v) => Some(v).encode(env),
         AnyValue::Int16(v) => Some(v).encode(env),
         AnyValue::Int32(v) => Some(v).encode(env),
@@ -316,26 +319,141 @@ pub fn term_from_value<'b>(v: AnyValue, env: Env<'b>) -> Term<'b> {
         AnyValue::Float32(v) => Some(v).encode(env),
         AnyValue::Date(v) => encode_date(v, env),
         AnyValue::Datetime(v, time_unit, None) => encode_datetime(v, time_unit, env),
-        dt => panic!("get/2 not implemented for {:?}", dt),
-    }
+        AnyValue::Datetime(v, time_unit, Some(time_zone)) => {
+            let tz = parse_time_zone(time_zone)?;
+            encode_datetime(utc_to_local(v, time_unit, &tz), time_unit, env)
+        }
+        dt => {
+            return Err(ExplorerError::Other(format!(
+                "get/2 not implemented for {:?}",
+                dt
+            )))
+        }
+    };
+
+    Ok(term)
 }
 
-pub fn list_from_series(data: ExSeries, env: Env) -> Term {
+pub fn list_from_series(data: ExSeries, env: Env) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
 
-    match s.dtype() {
+    let term = match s.dtype() {
         DataType::Boolean => encode!(s, env, bool),
+        DataType::Int8 => encode!(s, env, i8),
+        DataType::Int16 => encode!(s, env, i16),
         DataType::Int32 => encode!(s, env, i32),
         DataType::Int64 => encode!(s, env, i64),
         DataType::UInt8 => encode!(s, env, u8),
+        DataType::UInt16 => encode!(s, env, u16),
         DataType::UInt32 => encode!(s, env, u32),
+        DataType::UInt64 => encode!(s, env, u64),
         DataType::Utf8 => encode_utf8_series(&data.resource, s, env),
-        DataType::Float64 => encode_float64_series(s, env),
+        DataType::Categorical(_) => {
+            let utf8 = s.cast(&DataType::Utf8)?;
+            encode!(utf8, env, utf8)
+        }
+        DataType::Binary => encode_binary_series(s, env)?,
+        DataType::Float32 => encode_float_series(
+            s.f32()?.into_iter().map(|option| option.map(f64::from)),
+            env,
+        ),
+        DataType::Float64 => encode_float_series(s.f64()?.into_iter(), env),
         DataType::Date => encode_date_series(s, env),
         DataType::Datetime(time_unit, None) => encode_datetime_series(s, *time_unit, env),
-        DataType::List(t) if t as &DataType == &DataType::UInt32 => {
-            encode_list!(s, env, u32, u32)
+        DataType::Datetime(time_unit, Some(time_zone)) => {
+            let tz = parse_time_zone(time_zone)?;
+            let local = s
+                .datetime()?
+                .apply(|v| utc_to_local(v, *time_unit, &tz))
+                .into_datetime(*time_unit, None)
+                .into_series();
+            encode_datetime_series(&local, *time_unit, env)
+        }
+        // Each inner series is encoded recursively, so nested lists round-trip.
+        DataType::List(_) => {
+            let mut items: Vec<Term> = Vec::with_capacity(s.len());
+            for item in s.list()?.into_iter() {
+                let term = match item {
+                    Some(inner) => list_from_series(ExSeries::new(inner), env)?,
+                    None => None::<bool>.encode(env),
+                };
+                items.push(term);
+            }
+            items.encode(env)
+        }
+        dt => {
+            return Err(ExplorerError::Other(format!(
+                "to_list/1 not implemented for {:?}",
+                dt
+            )))
         }
-        dt => panic!("to_list/1 not implemented for {:?}", dt),
+    };
+
+    Ok(term)
+}
+
+// Encodes a value the way list_from_series encodes the values of its
+// series, so non-finite floats become atoms and lists are encoded
+// recursively.
+fn term_from_list_value<'b>(v: AnyValue, env: Env<'b>) -> Result<Term<'b>, ExplorerError> {
+    let term = match v {
+        AnyValue::Float32(x) => encode_float(x as f64, env),
+        AnyValue::Float64(x) => encode_float(x, env),
+        AnyValue::List(inner) => list_from_series(ExSeries::new(inner), env)?,
+        v => term_from_value(v, env)?,
+    };
+
+    Ok(term)
+}
+
+fn encode_float(x: f64, env: Env) -> Term {
+    if x.is_finite() {
+        x.encode(env)
+    } else {
+        match (x.is_nan(), x.is_sign_negative()) {
+            (true, _) => nan().encode(env),
+            (false, true) => neg_infinity().encode(env),
+            (false, false) => infinity().encode(env),
+        }
+    }
+}
+
+// Builds one map per row, keyed by column name, in a single pass over the
+// rows. Each value is encoded as list_from_series would encode it, so the
+// values are exactly the ones to_list returns.
+pub fn rows_from_dataframe<'b>(
+    df: &DataFrame,
+    atom_keys: bool,
+    env: Env<'b>,
+) -> Result<Term<'b>, ExplorerError> {
+    let keys = df
+        .get_column_names()
+        .into_iter()
+        .map(|name| {
+            if atom_keys {
+                Ok(rustler::Atom::from_str(env, name)?.encode(env))
+            } else {
+                Ok(name.encode(env))
+            }
+        })
+        .collect::<Result<Vec<Term>, rustler::Error>>()
+        .map_err(|_| ExplorerError::Internal("cannot encode column names".into()))?;
+
+    let columns = df.get_columns();
+    let mut values: Vec<Term> = Vec::with_capacity(columns.len());
+    let mut rows = Vec::with_capacity(df.height());
+
+    for i in 0..df.height() {
+        values.clear();
+
+        for s in columns {
+            values.push(term_from_list_value(s.get(i), env)?);
+        }
+
+        let row = Term::map_from_arrays(env, &keys, &values)
+            .map_err(|_| ExplorerError::Internal("cannot build row map".into()))?;
+        rows.push(row);
     }
+
+    Ok(rows.encode(env))
 }
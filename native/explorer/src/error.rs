@@ -22,8 +22,14 @@ pub enum ExplorerError {
     #[error(transparent)]
     TryFromInt(#[from] std::num::TryFromIntError),
     #[error(transparent)]
+    Arrow(#[from] polars::export::arrow::error::Error),
+    #[error(transparent)]
     Parquet(#[from] polars::export::arrow::io::parquet::read::ParquetError),
     #[error(transparent)]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+    #[error(transparent)]
+    Calamine(#[from] calamine::Error),
+    #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
 
@@ -3,14 +3,35 @@
 // to the Rust side. Each function receives a basic type
 // or an expression and returns an expression that is
 // wrapped in an Elixir struct.
+//
+// Building an expression doesn't touch any data, so these NIFs
+// run on normal schedulers. The work happens when a dataframe
+// NIF evaluates the expression on a dirty scheduler.
 
 use chrono::{NaiveDate, NaiveDateTime};
-use polars::prelude::{col, when, DataFrame, IntoLazy, LiteralValue, SortOptions};
-use polars::prelude::{Expr, Literal};
-
-use crate::datatypes::{ExDate, ExDateTime};
-use crate::series::{cast_str_to_dtype, rolling_opts};
-use crate::{ExDataFrame, ExExpr};
+use chrono_tz::Tz;
+use polars::prelude::{
+    apply_binary, arange, col, count, lit, map_binary, map_multiple, when, AnyValue, ChunkApply,
+    DataFrame, DataType, Field, GetOutput, IntoLazy, IntoSeries, LazyFrame, LiteralValue,
+    PolarsError, SearchSortedSide, Series, SortOptions, StructChunked, TimeUnit, UInt32Chunked,
+    Utf8Chunked, Utf8JsonPathImpl,
+};
+use polars::prelude::{
+    ChunkAgg, ChunkFullNull, Expr, Float64Chunked, Int64Chunked, IsSorted, Literal,
+    QuantileInterpolOptions,
+};
+use polars_core::utils::get_supertype;
+use rustler::{Binary, Env, NewBinary};
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+use crate::datatypes::{
+    check_local_time, parse_interval, parse_time_unit, parse_time_zone, utc_to_local, ExDate,
+    ExDateTime,
+};
+use crate::hashing::hash_rows;
+use crate::series::{cast_str_to_dtype, checked_sum_series, rolling_opts, value_counts_frame};
+use crate::{ExDataFrame, ExExpr, ExSeries, ExplorerError};
 
 #[rustler::nif]
 pub fn expr_integer(number: i64) -> ExExpr {
@@ -36,6 +57,17 @@ pub fn expr_boolean(boolean: bool) -> ExExpr {
     ExExpr::new(expr)
 }
 
+#[rustler::nif]
+pub fn expr_nil() -> ExExpr {
+    ExExpr::new(Expr::Literal(LiteralValue::Null))
+}
+
+#[rustler::nif]
+pub fn expr_binary(binary: Binary) -> ExExpr {
+    let expr = Expr::Literal(LiteralValue::Binary(binary.as_slice().to_vec()));
+    ExExpr::new(expr)
+}
+
 #[rustler::nif]
 pub fn expr_date(date: ExDate) -> ExExpr {
     let naive_date = NaiveDate::from(date);
@@ -50,12 +82,255 @@ pub fn expr_datetime(datetime: ExDateTime) -> ExExpr {
     ExExpr::new(expr)
 }
 
+// Strict casts fail when a value can't be converted, while non-strict
+// casts turn those values into nil. Only an unknown dtype is an error here.
 #[rustler::nif]
-pub fn expr_cast(data: ExExpr, to_dtype: &str) -> ExExpr {
+pub fn expr_cast(data: ExExpr, to_dtype: &str, strict: bool) -> Result<ExExpr, ExplorerError> {
     let expr: Expr = data.resource.0.clone();
-    let to_dtype = cast_str_to_dtype(to_dtype).expect("dtype is not valid");
+    let to_dtype = cast_str_to_dtype(to_dtype)?;
+
+    let expr = match strict {
+        true => expr.strict_cast(to_dtype),
+        false => expr.cast(to_dtype),
+    };
+
+    Ok(ExExpr::new(expr))
+}
+
+// Values outside of the target range saturate at its bounds instead of wrapping.
+#[rustler::nif]
+pub fn expr_clip_to_dtype_range(data: ExExpr, to_dtype: &str) -> Result<ExExpr, ExplorerError> {
+    let expr: Expr = data.resource.0.clone();
+    let to_dtype = cast_str_to_dtype(to_dtype)?;
+
+    let (min, max) = match to_dtype {
+        DataType::Int8 => (lit(i8::MIN as i64), Some(lit(i8::MAX as i64))),
+        DataType::Int16 => (lit(i16::MIN as i64), Some(lit(i16::MAX as i64))),
+        DataType::Int32 => (lit(i32::MIN as i64), Some(lit(i32::MAX as i64))),
+        DataType::Int64 => (lit(i64::MIN), Some(lit(i64::MAX))),
+        DataType::UInt8 => (lit(0i64), Some(lit(u8::MAX as i64))),
+        DataType::UInt16 => (lit(0i64), Some(lit(u16::MAX as i64))),
+        DataType::UInt32 => (lit(0i64), Some(lit(u32::MAX as i64))),
+        // Every non-negative Int64 fits, so only the lower bound applies.
+        DataType::UInt64 => (lit(0i64), None),
+        DataType::Float32 => (lit(f32::MIN as f64), Some(lit(f32::MAX as f64))),
+        DataType::Float64 => (lit(f64::MIN), Some(lit(f64::MAX))),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Cannot clip to the range of non-numeric type {}",
+                other
+            )))
+        }
+    };
+
+    let clipped = when(expr.clone().lt(min.clone())).then(min);
+    let clipped = match max {
+        Some(max) => clipped
+            .when(expr.clone().gt(max.clone()))
+            .then(max)
+            .otherwise(expr),
+        None => clipped.otherwise(expr),
+    };
+
+    Ok(ExExpr::new(clipped.cast(to_dtype)))
+}
+
+#[rustler::nif]
+pub fn expr_to_physical(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
 
-    ExExpr::new(expr.cast(to_dtype))
+    ExExpr::new(expr.to_physical())
+}
+
+#[rustler::nif]
+pub fn expr_shrink_dtype(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.shrink_dtype())
+}
+
+// Keeps the wall-clock time and interprets it in the given time zone.
+// Wall-clock times that a daylight saving transition skips or repeats
+// in the new time zone are an error.
+#[rustler::nif]
+pub fn expr_replace_time_zone(expr: ExExpr, time_zone: String) -> Result<ExExpr, ExplorerError> {
+    let tz = parse_time_zone(&time_zone)?;
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(expr.map(
+        move |s| replace_time_zone(s, &time_zone, &tz),
+        datetime_with_time_zone(time_zone.clone()),
+    )))
+}
+
+// Keeps the instant in time and changes the time zone it is displayed in.
+#[rustler::nif]
+pub fn expr_convert_time_zone(expr: ExExpr, time_zone: String) -> Result<ExExpr, ExplorerError> {
+    parse_time_zone(&time_zone)?;
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(expr.dt().with_time_zone(time_zone)))
+}
+
+// Rounds to the nearest multiple of the interval since the epoch.
+// Values exactly halfway between two boundaries round up to the later one.
+#[rustler::nif]
+pub fn expr_round_datetime(expr: ExExpr, every: String) -> Result<ExExpr, ExplorerError> {
+    let every = parse_interval(&every)?;
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(expr.map(
+        move |s| round_datetime(s, every),
+        GetOutput::same_type(),
+    )))
+}
+
+fn round_datetime(s: Series, every_ns: i64) -> Result<Series, PolarsError> {
+    let (time_unit, time_zone) = match s.dtype() {
+        DataType::Datetime(time_unit, time_zone) => (*time_unit, time_zone.clone()),
+        other => {
+            return Err(PolarsError::ComputeError(
+                format!("round_datetime expects a datetime, got {}", other).into(),
+            ))
+        }
+    };
+
+    let ns_per_unit = match time_unit {
+        TimeUnit::Milliseconds => 1_000_000,
+        TimeUnit::Microseconds => 1_000,
+        TimeUnit::Nanoseconds => 1,
+    };
+
+    if every_ns % ns_per_unit != 0 {
+        return Err(PolarsError::ComputeError(
+            "round_datetime interval is finer than the datetime precision".into(),
+        ));
+    }
+
+    let every = (every_ns / ns_per_unit) as i128;
+    let mut out = s
+        .datetime()?
+        .apply(|v| ((2 * v as i128 + every).div_euclid(2 * every) * every) as i64)
+        .into_datetime(time_unit, time_zone)
+        .into_series();
+    out.rename(s.name());
+    Ok(out)
+}
+
+#[rustler::nif]
+pub fn expr_is_between_datetime(
+    expr: ExExpr,
+    lower: ExDateTime,
+    upper: ExDateTime,
+    inclusive: bool,
+) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let lower = NaiveDateTime::from(lower).lit();
+    let upper = NaiveDateTime::from(upper).lit();
+
+    let between = if inclusive {
+        expr.clone().gt_eq(lower).and(expr.lt_eq(upper))
+    } else {
+        expr.clone().gt(lower).and(expr.lt(upper))
+    };
+
+    ExExpr::new(between)
+}
+
+#[rustler::nif]
+pub fn expr_timestamp(expr: ExExpr, time_unit: &str) -> Result<ExExpr, ExplorerError> {
+    let time_unit = parse_time_unit(time_unit)?;
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(expr.dt().timestamp(time_unit)))
+}
+
+// Durations are truncated towards zero to whole units.
+macro_rules! init_duration_total_fun {
+    ($name:ident, $label:literal, $seconds_per_unit:expr, $units_per_second:expr) => {
+        #[rustler::nif]
+        pub fn $name(expr: ExExpr) -> ExExpr {
+            let expr: Expr = expr.resource.0.clone();
+
+            ExExpr::new(expr.map(
+                |s| duration_total(s, $label, $seconds_per_unit, $units_per_second),
+                GetOutput::from_type(DataType::Int64),
+            ))
+        }
+    };
+}
+
+init_duration_total_fun!(expr_total_days, "total_days", 86_400, 1);
+init_duration_total_fun!(expr_total_seconds, "total_seconds", 1, 1);
+init_duration_total_fun!(expr_total_milliseconds, "total_milliseconds", 1, 1_000);
+
+fn duration_total(
+    s: Series,
+    label: &str,
+    seconds_per_unit: i64,
+    units_per_second: i64,
+) -> Result<Series, PolarsError> {
+    let time_unit = match s.dtype() {
+        DataType::Duration(time_unit) => *time_unit,
+        other => {
+            return Err(PolarsError::ComputeError(
+                format!("{} expects a duration, got {}", label, other).into(),
+            ))
+        }
+    };
+
+    let per_second = match time_unit {
+        TimeUnit::Milliseconds => 1_000,
+        TimeUnit::Microseconds => 1_000_000,
+        TimeUnit::Nanoseconds => 1_000_000_000,
+    };
+    let divisor = per_second * seconds_per_unit / units_per_second;
+
+    let mut out = s.duration()?.apply(|v| v / divisor).into_series();
+    out.rename(s.name());
+    Ok(out)
+}
+
+fn datetime_with_time_zone(time_zone: String) -> GetOutput {
+    GetOutput::map_dtype(move |dtype| match dtype {
+        DataType::Datetime(time_unit, _) => DataType::Datetime(*time_unit, Some(time_zone.clone())),
+        dtype => dtype.clone(),
+    })
+}
+
+// Polars only casts between time zones, so datetimes without one are
+// read as UTC first, which keeps their wall-clock time. Polars panics on
+// wall-clock times that do not map to a single instant, so those are
+// checked beforehand.
+fn replace_time_zone(s: Series, time_zone: &str, tz: &Tz) -> Result<Series, PolarsError> {
+    let ca = s.datetime()?;
+    let time_unit = ca.time_unit();
+
+    let ca = match ca.time_zone() {
+        Some(from) => {
+            let from = parse_time_zone(from)
+                .map_err(|error| PolarsError::ComputeError(error.to_string().into()))?;
+
+            for v in ca.into_iter().flatten() {
+                check_local_time(utc_to_local(v, time_unit, &from), time_unit, tz)
+                    .map_err(|error| PolarsError::ComputeError(error.into()))?;
+            }
+
+            ca.clone()
+        }
+        None => {
+            for v in ca.into_iter().flatten() {
+                check_local_time(v, time_unit, tz)
+                    .map_err(|error| PolarsError::ComputeError(error.into()))?;
+            }
+
+            ca.deref()
+                .clone()
+                .into_datetime(time_unit, Some("UTC".to_string()))
+        }
+    };
+
+    Ok(ca.cast_time_zone(time_zone)?.into_series())
 }
 
 #[rustler::nif]
@@ -142,6 +417,39 @@ pub fn expr_is_not_nil(expr: ExExpr) -> ExExpr {
     ExExpr::new(expr.is_not_null())
 }
 
+// Every occurrence of a repeated value is flagged, and nulls count as equal.
+#[rustler::nif]
+pub fn expr_is_duplicated(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.is_duplicated())
+}
+
+#[rustler::nif]
+pub fn expr_is_unique(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.is_unique())
+}
+
+// Categoricals are compared by their string values.
+#[rustler::nif]
+pub fn expr_is_in(expr: ExExpr, values: ExSeries) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let values: Series = values.resource.0.clone();
+
+    ExExpr::new(expr.map(
+        move |s| {
+            let s = match s.dtype() {
+                DataType::Categorical(_) => s.cast(&DataType::Utf8)?,
+                _ => s,
+            };
+            Ok(s.is_in(&values)?.into_series())
+        },
+        GetOutput::from_type(DataType::Boolean),
+    ))
+}
+
 #[rustler::nif]
 pub fn expr_all_equal(left: ExExpr, right: ExExpr) -> ExExpr {
     let left_expr: Expr = left.resource.0.clone();
@@ -157,6 +465,22 @@ pub fn expr_slice(expr: ExExpr, offset: i64, length: u32) -> ExExpr {
     ExExpr::new(expr.slice(offset, length))
 }
 
+// Takes every nth value, starting at the given offset.
+#[rustler::nif]
+pub fn expr_gather_every(expr: ExExpr, n: usize, offset: usize) -> Result<ExExpr, ExplorerError> {
+    if n == 0 {
+        return Err(ExplorerError::Other(
+            "gather_every expects n to be greater than zero".to_string(),
+        ));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(
+        expr.slice(offset as i64, u32::MAX).take_every(n),
+    ))
+}
+
 #[rustler::nif]
 pub fn expr_head(expr: ExExpr, length: usize) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
@@ -171,31 +495,121 @@ pub fn expr_tail(expr: ExExpr, length: usize) -> ExExpr {
     ExExpr::new(expr.tail(Some(length)))
 }
 
+// The "global" mode flags every value equal to the column's min or max.
+// The "local" mode flags values strictly greater (or smaller) than both of
+// their neighbors. Endpoints and values next to a nil have a missing
+// neighbor and are never local peaks, and neither are plateaus.
 #[rustler::nif]
-pub fn expr_peaks(data: ExExpr, min_or_max: &str) -> ExExpr {
+pub fn expr_peak_max(data: ExExpr, mode: &str) -> Result<ExExpr, ExplorerError> {
     let expr: Expr = data.resource.0.clone();
-    let type_expr = if min_or_max == "min" {
-        expr.min()
-    } else {
-        expr.max()
+
+    let peaks = match mode {
+        "global" => expr.clone().eq(expr.max()),
+        "local" => {
+            let prev = expr.clone().shift(1);
+            let next = expr.clone().shift(-1);
+            expr.clone()
+                .gt(prev)
+                .and(expr.gt(next))
+                .fill_null(lit(false))
+        }
+        other => return Err(invalid_peak_mode(other)),
     };
 
-    ExExpr::new(data.resource.0.clone().eq(type_expr))
+    Ok(ExExpr::new(peaks))
 }
 
 #[rustler::nif]
-pub fn expr_fill_missing(data: ExExpr, strategy: &str) -> ExExpr {
-    let orig_expr = &data.resource.0;
-    let expr: Expr = orig_expr.clone();
+pub fn expr_peak_min(data: ExExpr, mode: &str) -> Result<ExExpr, ExplorerError> {
+    let expr: Expr = data.resource.0.clone();
+
+    let peaks = match mode {
+        "global" => expr.clone().eq(expr.min()),
+        "local" => {
+            let prev = expr.clone().shift(1);
+            let next = expr.clone().shift(-1);
+            expr.clone()
+                .lt(prev)
+                .and(expr.lt(next))
+                .fill_null(lit(false))
+        }
+        other => return Err(invalid_peak_mode(other)),
+    };
+
+    Ok(ExExpr::new(peaks))
+}
+
+fn invalid_peak_mode(mode: &str) -> ExplorerError {
+    ExplorerError::Other(format!(
+        "Invalid peak mode: {}, expected global or local",
+        mode
+    ))
+}
+
+#[rustler::nif]
+pub fn expr_fill_missing(data: ExExpr, strategy: &str) -> Result<ExExpr, ExplorerError> {
+    let expr: Expr = data.resource.0.clone();
     let result_expr = match strategy {
         "backward" => expr.backward_fill(None),
         "forward" => expr.forward_fill(None),
-        "min" => expr.fill_null(orig_expr.clone().min()),
-        "max" => expr.fill_null(orig_expr.clone().max()),
-        "mean" => expr.fill_null(orig_expr.clone().mean()),
-        _other => panic!("unknown strategy"),
+        "min" => expr.clone().fill_null(expr.min()),
+        "max" => expr.clone().fill_null(expr.max()),
+        "mean" => expr.clone().fill_null(expr.mean()),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Unknown fill strategy: {}",
+                other
+            )))
+        }
     };
-    ExExpr::new(result_expr)
+    Ok(ExExpr::new(result_expr))
+}
+
+// With a limit, only up to that many consecutive nulls are filled
+// and the rest of a longer gap stays null.
+#[rustler::nif]
+pub fn expr_forward_fill(data: ExExpr, limit: Option<u32>) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    ExExpr::new(expr.forward_fill(limit))
+}
+
+#[rustler::nif]
+pub fn expr_backward_fill(data: ExExpr, limit: Option<u32>) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    ExExpr::new(expr.backward_fill(limit))
+}
+
+// Nulls and values without a matching key take the default.
+#[rustler::nif]
+pub fn expr_map_dict(
+    expr: ExExpr,
+    keys: Vec<ExExpr>,
+    values: Vec<ExExpr>,
+    default: ExExpr,
+) -> Result<ExExpr, ExplorerError> {
+    if keys.len() != values.len() {
+        return Err(ExplorerError::Other(format!(
+            "Keys and values must have the same length, got {} keys and {} values",
+            keys.len(),
+            values.len()
+        )));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+    let default: Expr = default.resource.0.clone();
+
+    // Folding from the last pair keeps the first matching key winning.
+    let mapped = keys
+        .iter()
+        .zip(values.iter())
+        .rev()
+        .fold(default, |otherwise, (key, value)| {
+            when(expr.clone().eq(key.resource.0.clone()))
+                .then(value.resource.0.clone())
+                .otherwise(otherwise)
+        });
+
+    Ok(ExExpr::new(mapped))
 }
 
 #[rustler::nif]
@@ -221,12 +635,43 @@ pub fn expr_subtract(left: ExExpr, right: ExExpr) -> ExExpr {
     ExExpr::new(left_expr - right_expr)
 }
 
+// Division always returns floats. The mode decides what a zero denominator
+// gives: "null" returns nil, "infinity" follows IEEE 754 (so 0 / 0 is NaN)
+// and "error" fails the whole query.
 #[rustler::nif]
-pub fn expr_divide(left: ExExpr, right: ExExpr) -> ExExpr {
-    let left_expr: Expr = left.resource.0.clone();
-    let right_expr: Expr = right.resource.0.clone();
+pub fn expr_divide(left: ExExpr, right: ExExpr, mode: &str) -> Result<ExExpr, ExplorerError> {
+    let left_expr: Expr = left.resource.0.clone().cast(DataType::Float64);
+    let right_expr: Expr = right.resource.0.clone().cast(DataType::Float64);
+
+    let quotient = match mode {
+        "null" => {
+            left_expr
+                / when(right_expr.clone().eq(lit(0.0)))
+                    .then(Expr::Literal(LiteralValue::Null))
+                    .otherwise(right_expr)
+        }
+        "infinity" => left_expr / right_expr,
+        "error" => map_binary(
+            left_expr,
+            right_expr,
+            |left, right| {
+                if right.f64()?.into_iter().any(|v| v == Some(0.0)) {
+                    return Err(PolarsError::ComputeError("division by zero".into()));
+                }
+
+                Ok(&left / &right)
+            },
+            GetOutput::from_type(DataType::Float64),
+        ),
+        mode => {
+            return Err(ExplorerError::Other(format!(
+                "unknown division mode {}, expected null, infinity or error",
+                mode
+            )))
+        }
+    };
 
-    ExExpr::new(left_expr / right_expr)
+    Ok(ExExpr::new(quotient))
 }
 
 #[rustler::nif]
@@ -247,15 +692,21 @@ pub fn expr_remainder(left: ExExpr, right: ExExpr) -> ExExpr {
     let left_expr: Expr = left.resource.0.clone();
     let right_expr: Expr = right.resource.0.clone();
 
-    let quotient = left_expr.clone()
-        / when(right_expr.clone().eq(0))
+    // The sign follows the numerator, like Elixir's rem/2.
+    let remainder = left_expr
+        % when(right_expr.clone().eq(0))
             .then(Expr::Literal(LiteralValue::Null))
-            .otherwise(right_expr.clone());
+            .otherwise(right_expr);
+
+    ExExpr::new(remainder)
+}
 
-    let mult = right_expr * quotient;
-    let result = left_expr - mult;
+#[rustler::nif]
+pub fn expr_abs_diff(left: ExExpr, right: ExExpr) -> ExExpr {
+    let left_expr: Expr = left.resource.0.clone();
+    let right_expr: Expr = right.resource.0.clone();
 
-    ExExpr::new(result)
+    ExExpr::new((left_expr - right_expr).abs())
 }
 
 #[rustler::nif]
@@ -274,11 +725,213 @@ pub fn expr_pow(left: ExExpr, right: ExExpr) -> ExExpr {
     ExExpr::new(left_expr.pow(right_expr))
 }
 
+// The checked variants work on integers as i64 and yield nil where the
+// result would overflow, instead of silently wrapping around.
+#[rustler::nif]
+pub fn expr_checked_add(left: ExExpr, right: ExExpr) -> ExExpr {
+    let left_expr: Expr = left.resource.0.clone();
+    let right_expr: Expr = right.resource.0.clone();
+
+    ExExpr::new(map_binary(
+        left_expr,
+        right_expr,
+        |left, right| checked_binary(left, right, "checked_add", i64::checked_add),
+        GetOutput::from_type(DataType::Int64),
+    ))
+}
+
+#[rustler::nif]
+pub fn expr_checked_multiply(left: ExExpr, right: ExExpr) -> ExExpr {
+    let left_expr: Expr = left.resource.0.clone();
+    let right_expr: Expr = right.resource.0.clone();
+
+    ExExpr::new(map_binary(
+        left_expr,
+        right_expr,
+        |left, right| checked_binary(left, right, "checked_multiply", i64::checked_mul),
+        GetOutput::from_type(DataType::Int64),
+    ))
+}
+
+// Bitwise operators on integers, as opposed to expr_and/expr_or which
+// combine booleans. Both sides are widened to i64 like the checked ones.
+macro_rules! init_bitwise_expr_fun {
+    ($name:ident, $label:expr, $op:expr) => {
+        #[rustler::nif]
+        pub fn $name(left: ExExpr, right: ExExpr) -> ExExpr {
+            let left_expr: Expr = left.resource.0.clone();
+            let right_expr: Expr = right.resource.0.clone();
+
+            ExExpr::new(map_binary(
+                left_expr,
+                right_expr,
+                |left, right| checked_binary(left, right, $label, |a, b| Some(($op)(a, b))),
+                GetOutput::from_type(DataType::Int64),
+            ))
+        }
+    };
+}
+
+init_bitwise_expr_fun!(expr_bitwise_and, "bitwise_and", |a: i64, b: i64| a & b);
+init_bitwise_expr_fun!(expr_bitwise_or, "bitwise_or", |a: i64, b: i64| a | b);
+init_bitwise_expr_fun!(expr_bitwise_xor, "bitwise_xor", |a: i64, b: i64| a ^ b);
+
+// Shifts are done on i64 and bits shifted out are dropped. Right shifts
+// keep the sign. Shifting by a negative amount or by 64 or more yields nil.
+#[rustler::nif]
+pub fn expr_shift_left(expr: ExExpr, amount: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let amount: Expr = amount.resource.0.clone();
+
+    ExExpr::new(map_binary(
+        expr,
+        amount,
+        |left, right| {
+            checked_binary(left, right, "shift_left", |a, b| {
+                a.checked_shl(u32::try_from(b).ok()?)
+            })
+        },
+        GetOutput::from_type(DataType::Int64),
+    ))
+}
+
+#[rustler::nif]
+pub fn expr_shift_right(expr: ExExpr, amount: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let amount: Expr = amount.resource.0.clone();
+
+    ExExpr::new(map_binary(
+        expr,
+        amount,
+        |left, right| {
+            checked_binary(left, right, "shift_right", |a, b| {
+                a.checked_shr(u32::try_from(b).ok()?)
+            })
+        },
+        GetOutput::from_type(DataType::Int64),
+    ))
+}
+
+fn checked_binary(
+    left: Series,
+    right: Series,
+    label: &str,
+    op: fn(i64, i64) -> Option<i64>,
+) -> Result<Series, PolarsError> {
+    for s in [&left, &right] {
+        if !is_integer_dtype(s.dtype()) {
+            return Err(PolarsError::ComputeError(
+                format!("{} expects integers, got {}", label, s.dtype()).into(),
+            ));
+        }
+    }
+
+    let left_i64 = cast_to_i64(&left, label)?;
+    let right_i64 = cast_to_i64(&right, label)?;
+    let (left_ca, right_ca) = (left_i64.i64()?, right_i64.i64()?);
+
+    let apply = |a: Option<i64>, b: Option<i64>| match (a, b) {
+        (Some(a), Some(b)) => op(a, b),
+        _ => None,
+    };
+
+    // A literal on either side comes in as a single value and is broadcast.
+    let mut out: Int64Chunked = match (left_ca.len(), right_ca.len()) {
+        (l, r) if l == r => left_ca
+            .into_iter()
+            .zip(right_ca.into_iter())
+            .map(|(a, b)| apply(a, b))
+            .collect(),
+        (_, 1) => {
+            let b = right_ca.get(0);
+            left_ca.into_iter().map(|a| apply(a, b)).collect()
+        }
+        (1, _) => {
+            let a = left_ca.get(0);
+            right_ca.into_iter().map(|b| apply(a, b)).collect()
+        }
+        (l, r) => {
+            return Err(PolarsError::ShapeMisMatch(
+                format!("{} got series of lengths {} and {}", label, l, r).into(),
+            ))
+        }
+    };
+
+    out.rename(left.name());
+    Ok(out.into_series())
+}
+
+// A plain cast would turn u64 values above i64::MAX into nil, so they are
+// rejected instead.
+fn cast_to_i64(s: &Series, label: &str) -> Result<Series, PolarsError> {
+    if let DataType::UInt64 = s.dtype() {
+        if let Some(max) = s.u64()?.max() {
+            if max > i64::MAX as u64 {
+                return Err(PolarsError::ComputeError(
+                    format!(
+                        "{} got {}, which does not fit in a signed 64-bit integer",
+                        label, max
+                    )
+                    .into(),
+                ));
+            }
+        }
+    }
+
+    s.cast(&DataType::Int64)
+}
+
+fn is_integer_dtype(dtype: &DataType) -> bool {
+    matches!(
+        dtype,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+    )
+}
+
+#[rustler::nif]
+pub fn expr_round_to_multiple(expr: ExExpr, step: f64) -> Result<ExExpr, ExplorerError> {
+    if step == 0.0 {
+        return Err(ExplorerError::Other(
+            "Cannot round to a multiple of zero".to_string(),
+        ));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new((expr / lit(step)).round(0) * lit(step)))
+}
+
+// Integer sums are folded with checked additions, per group when
+// aggregating, so sums that overflow are nil just like in `s_sum`. As in
+// `expr_quantiles`, the values are gathered into a list first so every
+// group sums to a single row.
 #[rustler::nif]
 pub fn expr_sum(expr: ExExpr) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
+    let output_type = GetOutput::map_dtype(|dtype| sum_dtype(dtype.inner_dtype().unwrap_or(dtype)));
+
+    ExExpr::new(expr.list().map(
+        |lists| {
+            let dtype = sum_dtype(&lists.list()?.inner_dtype());
+            map_lists(&lists, &dtype, checked_sum_series)
+        },
+        output_type,
+    ))
+}
 
-    ExExpr::new(expr.sum())
+fn sum_dtype(dtype: &DataType) -> DataType {
+    match dtype {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 => DataType::Int64,
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 => DataType::UInt64,
+        dtype => dtype.clone(),
+    }
 }
 
 #[rustler::nif]
@@ -309,6 +962,16 @@ pub fn expr_median(expr: ExExpr) -> ExExpr {
     ExExpr::new(expr.median())
 }
 
+// Median absolute deviation: the median distance to the median, which
+// unlike the standard deviation is barely moved by outliers.
+#[rustler::nif]
+pub fn expr_mad(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone().drop_nulls();
+    let deviation = (expr.clone() - expr.median()).abs();
+
+    ExExpr::new(deviation.median())
+}
+
 #[rustler::nif]
 pub fn expr_var(expr: ExExpr) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
@@ -323,6 +986,98 @@ pub fn expr_std(expr: ExExpr) -> ExExpr {
     ExExpr::new(expr.std(1))
 }
 
+// Inside a groupby each group is sorted on its own, which makes
+// `first`/`last` of the result pick values by another column.
+// Rows that tie on every key keep their original order.
+#[rustler::nif]
+pub fn expr_sort_by(
+    expr: ExExpr,
+    by: Vec<ExExpr>,
+    descending: Vec<bool>,
+) -> Result<ExExpr, ExplorerError> {
+    if by.len() != descending.len() {
+        return Err(ExplorerError::Other(format!(
+            "sort_by expects one direction per column, got {} columns and {} directions",
+            by.len(),
+            descending.len()
+        )));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+    let by: Vec<Expr> = by
+        .iter()
+        .map(|ex_expr| ex_expr.resource.0.clone())
+        .collect();
+
+    Ok(ExExpr::new(expr.sort_by(by, descending)))
+}
+
+// Negative and out of bounds indices are an error, like in `s_take`.
+// Inside a groupby the indices are relative to each group.
+#[rustler::nif]
+pub fn expr_take(expr: ExExpr, indices: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let indices: Expr = indices.resource.0.clone();
+
+    ExExpr::new(apply_binary(
+        expr,
+        indices,
+        |s, indices| {
+            let indices = indices.cast(&DataType::Int64)?;
+
+            if let Some(idx) = indices
+                .i64()?
+                .into_iter()
+                .flatten()
+                .find(|idx| *idx < 0 || *idx >= s.len() as i64)
+            {
+                return Err(PolarsError::ComputeError(
+                    format!(
+                        "Index {} is out of bounds for series of length {}",
+                        idx,
+                        s.len()
+                    )
+                    .into(),
+                ));
+            }
+
+            s.take(indices.cast(&DataType::UInt32)?.u32()?)
+        },
+        GetOutput::same_type(),
+    ))
+}
+
+// Keeps the values where the predicate is true. Inside a groupby this
+// filters each group, which allows conditional aggregations.
+#[rustler::nif]
+pub fn expr_filter(expr: ExExpr, predicate: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let predicate: Expr = predicate.resource.0.clone();
+
+    ExExpr::new(expr.filter(predicate))
+}
+
+// Returns a single struct column with `values` and `counts` fields,
+// following the ordering rules of `value_counts_frame`.
+#[rustler::nif]
+pub fn expr_value_counts(expr: ExExpr, sort: bool, parallel: bool) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let output_type = GetOutput::map_dtype(|dtype| {
+        DataType::Struct(vec![
+            Field::new("values", dtype.clone()),
+            Field::new("counts", DataType::Int64),
+        ])
+    });
+
+    ExExpr::new(expr.map(
+        move |s| {
+            let counts = value_counts_frame(&s, sort, parallel)?;
+            Ok(StructChunked::new(s.name(), counts.get_columns())?.into_series())
+        },
+        output_type,
+    ))
+}
+
 #[rustler::nif]
 pub fn expr_quantile(expr: ExExpr, quantile: f64) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
@@ -332,6 +1087,141 @@ pub fn expr_quantile(expr: ExExpr, quantile: f64) -> ExExpr {
     ExExpr::new(expr.quantile(quantile, strategy))
 }
 
+// Sorts each group once and reads every quantile from it. The values are
+// gathered into a list first, so there is one row per group when
+// aggregating and a single row otherwise, each holding a list of floats.
+#[rustler::nif]
+pub fn expr_quantiles(
+    expr: ExExpr,
+    quantiles: Vec<f64>,
+    strategy: &str,
+) -> Result<ExExpr, ExplorerError> {
+    if quantiles.is_empty() {
+        return Err(ExplorerError::Other(
+            "quantiles expects at least one quantile".to_string(),
+        ));
+    }
+
+    if let Some(quantile) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+        return Err(ExplorerError::Other(format!(
+            "quantile must be between 0 and 1, got {}",
+            quantile
+        )));
+    }
+
+    let strategy = crate::parse_quantile_interpol_options(strategy);
+    let expr: Expr = expr.resource.0.clone();
+    let dtype = DataType::List(Box::new(DataType::Float64));
+
+    Ok(ExExpr::new(expr.list().map(
+        move |lists| {
+            map_lists(&lists, &dtype, |s| {
+                let sorted: Vec<f64> = s
+                    .cast(&DataType::Float64)?
+                    .sort(false)
+                    .f64()?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+
+                let values: Float64Chunked = quantiles
+                    .iter()
+                    .map(|q| sorted_quantile(&sorted, *q, strategy))
+                    .collect();
+
+                Ok(Series::new(s.name(), &[values.into_series()]))
+            })
+        },
+        GetOutput::from_type(DataType::List(Box::new(DataType::Float64))),
+    )))
+}
+
+// Calls `fun` on the values of every row of a list series and stacks the
+// results, which must be a single row each, into a series of `dtype`.
+fn map_lists(
+    lists: &Series,
+    dtype: &DataType,
+    fun: impl Fn(Series) -> Result<Series, PolarsError>,
+) -> Result<Series, PolarsError> {
+    let ca = lists.list()?;
+    let inner_dtype = ca.inner_dtype();
+    let mut out = Series::new_empty(lists.name(), dtype);
+
+    for values in ca.into_iter() {
+        let values = values.unwrap_or_else(|| Series::new_empty(lists.name(), &inner_dtype));
+        out.append(&fun(values)?.cast(dtype)?)?;
+    }
+
+    Ok(out)
+}
+
+fn sorted_quantile(
+    sorted: &[f64],
+    quantile: f64,
+    strategy: QuantileInterpolOptions,
+) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let float_idx = last as f64 * quantile;
+    let lower = sorted[float_idx.floor() as usize];
+    let upper = sorted[float_idx.ceil() as usize];
+
+    let value = match strategy {
+        QuantileInterpolOptions::Nearest => sorted[float_idx.round() as usize],
+        QuantileInterpolOptions::Lower => lower,
+        QuantileInterpolOptions::Higher => upper,
+        QuantileInterpolOptions::Midpoint => (lower + upper) / 2.0,
+        QuantileInterpolOptions::Linear => lower + (upper - lower) * float_idx.fract(),
+    };
+
+    Some(value)
+}
+
+// A constant column has no range to scale to, so it becomes nil.
+#[rustler::nif]
+pub fn expr_min_max_scale(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone().cast(DataType::Float64);
+    let min = expr.clone().min();
+    let range = expr.clone().max() - min.clone();
+
+    let scaled = (expr - min)
+        / when(range.clone().eq(0))
+            .then(Expr::Literal(LiteralValue::Null))
+            .otherwise(range);
+
+    ExExpr::new(scaled)
+}
+
+#[rustler::nif]
+pub fn expr_winsorize(expr: ExExpr, lower: f64, upper: f64) -> Result<ExExpr, ExplorerError> {
+    if let Some(quantile) = [lower, upper].iter().find(|q| !(0.0..=1.0).contains(*q)) {
+        return Err(ExplorerError::Other(format!(
+            "winsorize quantiles must be between 0 and 1, got {}",
+            quantile
+        )));
+    }
+
+    if lower > upper {
+        return Err(ExplorerError::Other(format!(
+            "winsorize lower quantile {} must not be greater than upper quantile {}",
+            lower, upper
+        )));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+    let strategy = crate::parse_quantile_interpol_options("linear");
+    // Nulls are skipped by quantile and kept as is by the fallback branch.
+    let lower_bound = expr.clone().quantile(lower, strategy);
+    let upper_bound = expr.clone().quantile(upper, strategy);
+
+    let winsorized = when(expr.clone().lt(lower_bound.clone()))
+        .then(lower_bound)
+        .when(expr.clone().gt(upper_bound.clone()))
+        .then(upper_bound)
+        .otherwise(expr);
+
+    Ok(ExExpr::new(winsorized))
+}
+
 #[rustler::nif]
 pub fn expr_alias(expr: ExExpr, name: &str) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
@@ -339,18 +1229,27 @@ pub fn expr_alias(expr: ExExpr, name: &str) -> ExExpr {
     ExExpr::new(expr.alias(name))
 }
 
+// Counts and indices are u32 in polars. They are cast to i64 so they are
+// reported as `:integer`, like the counts of `s_value_counts`.
 #[rustler::nif]
 pub fn expr_count(expr: ExExpr) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
 
-    ExExpr::new(expr.count())
+    ExExpr::new(expr.count().cast(DataType::Int64))
 }
 
 #[rustler::nif]
 pub fn expr_n_distinct(expr: ExExpr) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
 
-    ExExpr::new(expr.n_unique())
+    ExExpr::new(expr.n_unique().cast(DataType::Int64))
+}
+
+#[rustler::nif]
+pub fn expr_nil_count(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.null_count().cast(DataType::Int64))
 }
 
 #[rustler::nif]
@@ -367,21 +1266,186 @@ pub fn expr_last(expr: ExExpr) -> ExExpr {
     ExExpr::new(expr.last())
 }
 
+#[rustler::nif]
+pub fn expr_first_non_null(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.drop_nulls().first())
+}
+
+#[rustler::nif]
+pub fn expr_last_non_null(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.drop_nulls().last())
+}
+
+// Nulls are ignored by `all` and `any`, so a group with only nulls
+// behaves like an empty one: `all` is true and `any` is false.
+#[rustler::nif]
+pub fn expr_all(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(boolean_expr(expr, "all").drop_nulls().all())
+}
+
+#[rustler::nif]
+pub fn expr_any(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(boolean_expr(expr, "any").drop_nulls().any())
+}
+
+fn boolean_expr(expr: Expr, op: &'static str) -> Expr {
+    expr.map(
+        move |s: Series| match s.dtype() {
+            DataType::Boolean => Ok(s),
+            dtype => Err(PolarsError::ComputeError(
+                format!("{} expects a boolean expression, got {}", op, dtype).into(),
+            )),
+        },
+        GetOutput::from_type(DataType::Boolean),
+    )
+}
+
+// Stacks the right values after the left ones, upcasting both sides to a
+// common dtype.
+#[rustler::nif]
+pub fn expr_append(left: ExExpr, right: ExExpr) -> ExExpr {
+    let left_expr: Expr = left.resource.0.clone();
+    let right_expr: Expr = right.resource.0.clone();
+
+    ExExpr::new(left_expr.append(right_expr, true))
+}
+
 #[rustler::nif]
 pub fn expr_coalesce(left: ExExpr, right: ExExpr) -> ExExpr {
-    let predicate: Expr = left.resource.0.clone().is_not_null();
     let left_expr: Expr = left.resource.0.clone();
     let right_expr: Expr = right.resource.0.clone();
 
-    let condition = when(predicate).then(left_expr).otherwise(right_expr);
+    let condition = when(left_expr.clone().is_not_null())
+        .then(left_expr)
+        .otherwise(right_expr);
 
     ExExpr::new(condition)
 }
 
+// A nil predicate counts as false, so those rows take the otherwise value.
+#[rustler::nif]
+pub fn expr_if_else(predicate: ExExpr, on_true: ExExpr, on_false: ExExpr) -> ExExpr {
+    let predicate: Expr = predicate.resource.0.clone();
+    let on_true: Expr = on_true.resource.0.clone();
+    let on_false: Expr = on_false.resource.0.clone();
+
+    ExExpr::new(when(predicate).then(on_true).otherwise(on_false))
+}
+
+// The first matching old value wins. Values without a match are kept as is
+// and a nil old value matches nil entries.
+#[rustler::nif]
+pub fn expr_replace_values(
+    expr: ExExpr,
+    old: Vec<ExExpr>,
+    new: Vec<ExExpr>,
+) -> Result<ExExpr, ExplorerError> {
+    if old.len() != new.len() {
+        return Err(ExplorerError::Other(format!(
+            "replace_values expects the same number of old and new values, got {} and {}",
+            old.len(),
+            new.len()
+        )));
+    }
+
+    let expr: Expr = expr.resource.0.clone();
+
+    let replaced = old
+        .iter()
+        .zip(new.iter())
+        .rev()
+        .fold(expr.clone(), |acc, (old, new)| {
+            let matches = match &old.resource.0 {
+                Expr::Literal(LiteralValue::Null) => expr.clone().is_null(),
+                old => expr.clone().eq(old.clone()),
+            };
+
+            when(matches).then(new.resource.0.clone()).otherwise(acc)
+        });
+
+    Ok(ExExpr::new(replaced))
+}
+
+// Horizontal functions combine the inputs pairwise from left to right in a
+// single pass over the evaluated series, after casting them to their common
+// supertype. With ignore_nulls a nil input is skipped and a row is only nil
+// when all of its inputs are. Otherwise any nil input makes the row nil.
+macro_rules! init_horizontal_expr_fun {
+    ($name:ident, $combine:expr) => {
+        #[rustler::nif]
+        pub fn $name(exprs: Vec<ExExpr>, ignore_nulls: bool) -> Result<ExExpr, ExplorerError> {
+            if exprs.is_empty() {
+                return Err(ExplorerError::Other(format!(
+                    "{} expects at least one expression",
+                    stringify!($name)
+                )));
+            }
+
+            let exprs: Vec<Expr> = exprs
+                .into_iter()
+                .map(|expr| expr.resource.0.clone())
+                .collect();
+
+            let result = map_multiple(
+                move |series: &mut [Series]| horizontal_fold(series, ignore_nulls, $combine),
+                exprs,
+                GetOutput::super_type(),
+            );
+
+            Ok(ExExpr::new(result))
+        }
+    };
+}
+
+fn horizontal_fold(
+    series: &mut [Series],
+    ignore_nulls: bool,
+    combine: fn(&Series, &Series) -> Result<Series, PolarsError>,
+) -> Result<Series, PolarsError> {
+    let mut dtype = series[0].dtype().clone();
+    for s in series.iter().skip(1) {
+        dtype = get_supertype(&dtype, s.dtype())?;
+    }
+
+    let mut acc = series[0].cast(&dtype)?;
+    for s in series.iter().skip(1) {
+        let s = s.cast(&dtype)?;
+        let both_present = combine(&acc, &s)?;
+
+        acc = if ignore_nulls {
+            both_present
+                .zip_with(&s.is_not_null(), &acc)?
+                .zip_with(&acc.is_not_null(), &s)?
+        } else {
+            let present = acc.is_not_null() & s.is_not_null();
+            let nulls = Series::full_null(acc.name(), acc.len(), &dtype);
+            both_present.zip_with(&present, &nulls)?
+        };
+    }
+
+    Ok(acc)
+}
+
+init_horizontal_expr_fun!(expr_max_horizontal, |left, right| {
+    left.zip_with(&left.gt_eq(right)?, right)
+});
+init_horizontal_expr_fun!(expr_min_horizontal, |left, right| {
+    left.zip_with(&left.lt_eq(right)?, right)
+});
+init_horizontal_expr_fun!(expr_sum_horizontal, |left, right| Ok(left + right));
+
 // window functions
 macro_rules! init_window_expr_fun {
     ($name:ident, $fun:ident) => {
-        #[rustler::nif(schedule = "DirtyCpu")]
+        #[rustler::nif]
         pub fn $name(
             data: ExExpr,
             window_size: usize,
@@ -402,21 +1466,348 @@ init_window_expr_fun!(expr_window_sum, rolling_sum);
 init_window_expr_fun!(expr_window_mean, rolling_mean);
 
 #[rustler::nif]
-pub fn expr_cumulative_min(data: ExExpr, reverse: bool) -> ExExpr {
+pub fn expr_window_skew(
+    data: ExExpr,
+    window_size: usize,
+    min_periods: Option<usize>,
+    bias: bool,
+) -> ExExpr {
     let expr: Expr = data.resource.0.clone();
-    ExExpr::new(expr.cummin(reverse))
+    let min_periods = min_periods.unwrap_or(window_size);
+
+    ExExpr::new(expr.map(
+        move |s| rolling_skew(s, window_size, min_periods, bias),
+        GetOutput::from_type(DataType::Float64),
+    ))
 }
 
+// Built from rolling means of x, x² and x³, so each window costs the same
+// regardless of its size. Nulls are skipped inside each window, windows with
+// fewer than `min_periods` non-null values yield null and so do constant
+// windows, whose skewness is undefined.
+fn rolling_skew(
+    s: Series,
+    window_size: usize,
+    min_periods: usize,
+    bias: bool,
+) -> Result<Series, PolarsError> {
+    let x = s.cast(&DataType::Float64)?;
+    // The skewness does not change with a shift, and centering the values
+    // keeps the raw moments from cancelling each other out for large values.
+    let x = match x.mean() {
+        Some(mean) => &x - mean,
+        None => x,
+    };
+    let opts = || rolling_opts(window_size, None, Some(min_periods), false).into();
+
+    let x2 = &x * &x;
+    let x3 = &x2 * &x;
+    let mean = x.rolling_mean(opts())?;
+    let mean2 = x2.rolling_mean(opts())?;
+    let mean3 = x3.rolling_mean(opts())?;
+    let counts = x
+        .is_not_null()
+        .cast(&DataType::Float64)?
+        .rolling_sum(opts())?;
+
+    let skews: Float64Chunked = mean
+        .f64()?
+        .into_iter()
+        .zip(mean2.f64()?)
+        .zip(mean3.f64()?)
+        .zip(counts.f64()?)
+        .map(|(((mean, mean2), mean3), n)| moments_skew(mean?, mean2?, mean3?, n?, bias))
+        .collect();
+
+    let mut skews = skews.into_series();
+    skews.rename(s.name());
+    Ok(skews)
+}
+
+fn moments_skew(mean: f64, mean2: f64, mean3: f64, n: f64, bias: bool) -> Option<f64> {
+    let m2 = mean2 - mean * mean;
+
+    if m2 <= mean2 * 1e-12 {
+        return None;
+    }
+
+    let m3 = mean3 - 3.0 * mean * mean2 + 2.0 * mean.powi(3);
+    let skew = m3 / m2.powf(1.5);
+
+    if bias || n < 3.0 {
+        Some(skew)
+    } else {
+        Some(skew * (n * (n - 1.0)).sqrt() / (n - 2.0))
+    }
+}
+
+// Sums the values whose timestamp falls within `window` of the current row,
+// so rows are grouped by time proximity rather than by position. The index
+// must be a sorted date or datetime column without nils. `closed` picks
+// which window bounds are inclusive: "right" (the default in polars),
+// "left", "both" or "none".
 #[rustler::nif]
-pub fn expr_cumulative_max(data: ExExpr, reverse: bool) -> ExExpr {
+pub fn expr_rolling_sum_by(
+    data: ExExpr,
+    by: ExExpr,
+    window: String,
+    closed: String,
+) -> Result<ExExpr, ExplorerError> {
+    let window_ns = parse_interval(&window)?;
+    let (closed_left, closed_right) = match closed.as_str() {
+        "right" => (false, true),
+        "left" => (true, false),
+        "both" => (true, true),
+        "none" => (false, false),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Invalid closed window: {}, expected one of: left, right, both, none",
+                other
+            )))
+        }
+    };
+
     let expr: Expr = data.resource.0.clone();
-    ExExpr::new(expr.cummax(reverse))
+    let by: Expr = by.resource.0.clone();
+
+    Ok(ExExpr::new(map_binary(
+        expr,
+        by,
+        move |values, index| rolling_sum_by(values, index, window_ns, closed_left, closed_right),
+        GetOutput::from_type(DataType::Float64),
+    )))
 }
 
+fn rolling_sum_by(
+    values: Series,
+    index: Series,
+    window_ns: i64,
+    closed_left: bool,
+    closed_right: bool,
+) -> Result<Series, PolarsError> {
+    let ns_per_unit = match index.dtype() {
+        DataType::Date => 86_400_000_000_000,
+        DataType::Datetime(TimeUnit::Milliseconds, _) => 1_000_000,
+        DataType::Datetime(TimeUnit::Microseconds, _) => 1_000,
+        DataType::Datetime(TimeUnit::Nanoseconds, _) => 1,
+        other => {
+            return Err(PolarsError::ComputeError(
+                format!(
+                    "rolling_sum_by expects a date or datetime index, got {}",
+                    other
+                )
+                .into(),
+            ))
+        }
+    };
+
+    if window_ns % ns_per_unit != 0 {
+        return Err(PolarsError::ComputeError(
+            "rolling_sum_by window is finer than the index precision".into(),
+        ));
+    }
+
+    if index.null_count() > 0 {
+        return Err(PolarsError::ComputeError(
+            "rolling_sum_by index must not contain nils".into(),
+        ));
+    }
+
+    let times: Vec<i64> = index
+        .cast(&DataType::Int64)?
+        .i64()?
+        .into_no_null_iter()
+        .collect();
+
+    if times.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(PolarsError::ComputeError(
+            "rolling_sum_by index must be sorted in ascending order".into(),
+        ));
+    }
+
+    let window = window_ns / ns_per_unit;
+    let floats = values.cast(&DataType::Float64)?;
+    let floats: Vec<f64> = floats
+        .f64()?
+        .into_iter()
+        .map(|v| v.unwrap_or(0.0))
+        .collect();
+
+    // prefix[i] holds the sum of the first i values.
+    let mut prefix = Vec::with_capacity(floats.len() + 1);
+    prefix.push(0.0);
+    for v in &floats {
+        prefix.push(prefix.last().unwrap() + v);
+    }
+
+    let (mut start, mut end) = (0, 0);
+    let sums: Float64Chunked = times
+        .iter()
+        .map(|t| {
+            let lower = t - window;
+
+            while start < times.len()
+                && (times[start] < lower || (!closed_left && times[start] == lower))
+            {
+                start += 1;
+            }
+
+            while end < times.len() && (times[end] < *t || (closed_right && times[end] == *t)) {
+                end += 1;
+            }
+
+            Some(prefix[end.max(start)] - prefix[start])
+        })
+        .collect();
+
+    let mut out = sums.into_series();
+    out.rename(values.name());
+    Ok(out)
+}
+
+// Linearly interpolates interior nils, weighting by the distance between
+// the `by` values rather than assuming evenly spaced rows. Rows are visited
+// in ascending `by` order, so the index does not need to be sorted. Nils
+// before the first or after the last known value are kept.
 #[rustler::nif]
-pub fn expr_cumulative_sum(data: ExExpr, reverse: bool) -> ExExpr {
+pub fn expr_interpolate_by(data: ExExpr, by: ExExpr) -> ExExpr {
     let expr: Expr = data.resource.0.clone();
-    ExExpr::new(expr.cumsum(reverse))
+    let by: Expr = by.resource.0.clone();
+
+    ExExpr::new(map_binary(
+        expr,
+        by,
+        interpolate_by,
+        GetOutput::from_type(DataType::Float64),
+    ))
+}
+
+fn interpolate_by(values: Series, index: Series) -> Result<Series, PolarsError> {
+    if index.null_count() > 0 {
+        return Err(PolarsError::ComputeError(
+            "interpolate_by index must not contain nils".into(),
+        ));
+    }
+
+    let xs: Vec<f64> = index
+        .to_physical_repr()
+        .cast(&DataType::Float64)?
+        .f64()?
+        .into_no_null_iter()
+        .collect();
+    let ys = values.cast(&DataType::Float64)?;
+    let mut ys: Vec<Option<f64>> = ys.f64()?.into_iter().collect();
+
+    let mut order: Vec<usize> = (0..xs.len()).collect();
+    order.sort_by(|a, b| xs[*a].total_cmp(&xs[*b]));
+
+    let mut previous: Option<usize> = None;
+    for (position, &row) in order.iter().enumerate() {
+        if ys[row].is_none() {
+            continue;
+        }
+
+        if let Some(start) = previous {
+            let (x0, y0) = (xs[order[start]], ys[order[start]].unwrap());
+            let (x1, y1) = (xs[row], ys[row].unwrap());
+
+            for &gap in &order[start + 1..position] {
+                ys[gap] = Some(if x1 == x0 {
+                    y0
+                } else {
+                    y0 + (y1 - y0) * (xs[gap] - x0) / (x1 - x0)
+                });
+            }
+        }
+
+        previous = Some(position);
+    }
+
+    let mut out = ys.into_iter().collect::<Float64Chunked>().into_series();
+    out.rename(values.name());
+    Ok(out)
+}
+
+// NaN never becomes the running min or max, its position takes the
+// running value instead.
+#[rustler::nif]
+pub fn expr_cumulative_min(data: ExExpr, reverse: bool, skip_nulls: bool) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    let cummin = expr.clone().cummin(reverse);
+
+    ExExpr::new(cumulative_nulls(expr, cummin, reverse, skip_nulls))
+}
+
+#[rustler::nif]
+pub fn expr_cumulative_max(data: ExExpr, reverse: bool, skip_nulls: bool) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    let cummax = expr.clone().cummax(reverse);
+
+    ExExpr::new(cumulative_nulls(expr, cummax, reverse, skip_nulls))
+}
+
+#[rustler::nif]
+pub fn expr_cumulative_sum(data: ExExpr, reverse: bool, skip_nulls: bool) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    let cumsum = expr.clone().cumsum(reverse);
+
+    ExExpr::new(cumulative_nulls(expr, cumsum, reverse, skip_nulls))
+}
+
+// Without skip_nulls, once a null is seen every following value is null.
+fn cumulative_nulls(expr: Expr, cumulative: Expr, reverse: bool, skip_nulls: bool) -> Expr {
+    if skip_nulls {
+        return cumulative;
+    }
+
+    let poisoned = expr
+        .is_null()
+        .cast(DataType::UInt32)
+        .cumsum(reverse)
+        .gt(lit(0u32));
+
+    when(poisoned)
+        .then(Expr::Literal(LiteralValue::Null))
+        .otherwise(cumulative)
+}
+
+// Running totals restart for each partition, and nil keys form a partition
+// of their own. Nil values are skipped.
+#[rustler::nif]
+pub fn expr_cumulative_sum_over(data: ExExpr, partition_by: Vec<ExExpr>, reverse: bool) -> ExExpr {
+    let expr: Expr = data.resource.0.clone();
+    let partition_by: Vec<Expr> = partition_by
+        .iter()
+        .map(|ex_expr| ex_expr.resource.0.clone())
+        .collect();
+
+    ExExpr::new(expr.cumsum(reverse).over(partition_by))
+}
+
+#[rustler::nif]
+pub fn expr_row_index(offset: i64) -> ExExpr {
+    let expr = arange(lit(offset), count() + lit(offset), 1).cast(DataType::Int64);
+
+    ExExpr::new(expr)
+}
+
+#[rustler::nif]
+pub fn expr_over(expr: ExExpr, partition_by: Vec<ExExpr>) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let partition_by: Vec<Expr> = partition_by
+        .iter()
+        .map(|ex_expr| ex_expr.resource.0.clone())
+        .collect();
+
+    ExExpr::new(expr.over(partition_by))
+}
+
+#[rustler::nif]
+pub fn expr_repeat_by(expr: ExExpr, by: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let by: Expr = by.resource.0.clone();
+
+    ExExpr::new(expr.repeat_by(by))
 }
 
 #[rustler::nif]
@@ -433,6 +1824,24 @@ pub fn expr_sort(expr: ExExpr, reverse: bool) -> ExExpr {
     ExExpr::new(expr.sort(reverse))
 }
 
+// The k largest values, largest first, leaving out nils. Equal values are
+// interchangeable, so ties do not change the result. With k larger than
+// the number of values, all values are returned.
+#[rustler::nif]
+pub fn expr_top_k(expr: ExExpr, k: usize) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.drop_nulls().sort(true).head(Some(k)))
+}
+
+// The k smallest values, smallest first, leaving out nils.
+#[rustler::nif]
+pub fn expr_bottom_k(expr: ExExpr, k: usize) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.drop_nulls().sort(false).head(Some(k)))
+}
+
 #[rustler::nif]
 pub fn expr_argsort(expr: ExExpr, reverse: bool) -> ExExpr {
     let expr: Expr = expr.resource.0.clone();
@@ -442,7 +1851,59 @@ pub fn expr_argsort(expr: ExExpr, reverse: bool) -> ExExpr {
         nulls_last: false,
     };
 
-    ExExpr::new(expr.arg_sort(opts))
+    ExExpr::new(expr.arg_sort(opts).cast(DataType::Int64))
+}
+
+// Marks the values as sorted so polars can take faster paths, such as for
+// joins and group-bys. The values are neither checked nor reordered, so
+// marking unsorted values makes those operations return wrong results.
+#[rustler::nif]
+pub fn expr_set_sorted(expr: ExExpr, descending: bool) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let flag = if descending {
+        IsSorted::Descending
+    } else {
+        IsSorted::Ascending
+    };
+
+    ExExpr::new(expr.map(
+        move |mut s| {
+            s.set_sorted(flag);
+            Ok(s)
+        },
+        GetOutput::same_type(),
+    ))
+}
+
+#[rustler::nif]
+pub fn expr_rechunk(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.map(|s| Ok(s.rechunk()), GetOutput::same_type()))
+}
+
+#[rustler::nif]
+pub fn expr_search_sorted(
+    sorted: ExExpr,
+    element: ExExpr,
+    side: &str,
+) -> Result<ExExpr, ExplorerError> {
+    let sorted: Expr = sorted.resource.0.clone();
+    let element: Expr = element.resource.0.clone();
+    let side = match side {
+        "left" => SearchSortedSide::Left,
+        "right" => SearchSortedSide::Right,
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "Search side {} not supported, expected left or right",
+                other
+            )))
+        }
+    };
+
+    Ok(ExExpr::new(
+        sorted.search_sorted(element, side).cast(DataType::Int64),
+    ))
 }
 
 #[rustler::nif]
@@ -459,9 +1920,284 @@ pub fn expr_unordered_distinct(expr: ExExpr) -> ExExpr {
     ExExpr::new(expr.unique())
 }
 
+#[rustler::nif]
+pub fn expr_rle(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let output_type = GetOutput::map_dtype(|dtype| {
+        DataType::Struct(vec![
+            Field::new("lengths", DataType::Int64),
+            Field::new("values", dtype.clone()),
+        ])
+    });
+
+    ExExpr::new(expr.map(run_length_encode, output_type))
+}
+
+// Consecutive equal values, including nulls, are collapsed into a single run.
+fn run_length_encode(s: Series) -> Result<Series, PolarsError> {
+    let s = s.rechunk();
+    let mut starts: Vec<u32> = Vec::new();
+    let mut lengths: Vec<i64> = Vec::new();
+    let mut previous: Option<AnyValue> = None;
+
+    for (index, value) in s.iter().enumerate() {
+        let same_run = matches!(&previous, Some(prev) if *prev == value);
+
+        if same_run {
+            if let Some(length) = lengths.last_mut() {
+                *length += 1;
+            }
+        } else {
+            starts.push(index as u32);
+            lengths.push(1);
+        }
+
+        previous = Some(value);
+    }
+
+    let mut values = s.take(&UInt32Chunked::from_vec("values", starts))?;
+    values.rename("values");
+    let lengths = Series::new("lengths", lengths);
+
+    Ok(StructChunked::new(s.name(), &[lengths, values])?.into_series())
+}
+
 #[rustler::nif]
 pub fn expr_describe_filter_plan(data: ExDataFrame, expr: ExExpr) -> String {
-    let df: DataFrame = data.resource.0.clone();
     let expressions: Expr = expr.resource.0.clone();
-    df.lazy().filter(expressions).describe_plan()
+    filter_plan(&data.resource.0, expressions).describe_plan()
+}
+
+#[rustler::nif]
+pub fn expr_describe_filter_plan_optimized(
+    data: ExDataFrame,
+    expr: ExExpr,
+) -> Result<String, ExplorerError> {
+    let expressions: Expr = expr.resource.0.clone();
+    Ok(filter_plan(&data.resource.0, expressions).describe_optimized_plan()?)
+}
+
+// Cloning a dataframe only bumps the reference counts of its columns,
+// so building a plan over it never copies the underlying buffers.
+fn filter_plan(df: &DataFrame, predicate: Expr) -> LazyFrame {
+    df.clone().lazy().filter(predicate)
+}
+
+#[rustler::nif]
+pub fn expr_to_string(expr: ExExpr) -> String {
+    format!("{:?}", expr.resource.0)
+}
+
+// Sampling more values than the column has without replacement errors when collected.
+#[rustler::nif]
+pub fn expr_sample_n(
+    expr: ExExpr,
+    n: usize,
+    with_replacement: bool,
+    shuffle: bool,
+    seed: Option<u64>,
+) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.sample_n(n, with_replacement, shuffle, seed))
+}
+
+#[rustler::nif]
+pub fn expr_shuffle(expr: ExExpr, seed: Option<u64>) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.shuffle(seed))
+}
+
+#[rustler::nif]
+pub fn expr_dot(left: ExExpr, right: ExExpr) -> ExExpr {
+    let left_expr: Expr = left.resource.0.clone();
+    let right_expr: Expr = right.resource.0.clone();
+
+    ExExpr::new(left_expr.dot(right_expr))
+}
+
+#[rustler::nif]
+pub fn expr_arg_max(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arg_max().cast(DataType::Int64))
+}
+
+#[rustler::nif]
+pub fn expr_arg_min(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arg_min().cast(DataType::Int64))
+}
+
+// Nulls are treated as a single value, so only the first null index is kept.
+#[rustler::nif]
+pub fn expr_arg_unique(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arg_unique().cast(DataType::Int64))
+}
+
+#[rustler::nif]
+pub fn expr_list_lengths(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arr().lengths().cast(DataType::Int64))
+}
+
+// Negative indexes count from the end of each list; out of range yields null.
+#[rustler::nif]
+pub fn expr_list_get(expr: ExExpr, index: i64) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arr().get(index))
+}
+
+#[rustler::nif]
+pub fn expr_list_join(expr: ExExpr, separator: &str) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.arr().join(separator))
+}
+
+#[rustler::nif]
+pub fn expr_list_contains(expr: ExExpr, item: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let item: Expr = item.resource.0.clone();
+    ExExpr::new(expr.arr().contains(item))
+}
+
+// Slicing counts characters rather than bytes, and stops at the end of shorter strings.
+#[rustler::nif]
+pub fn expr_str_head(expr: ExExpr, n: usize) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.str().str_slice(0, Some(n as u64)))
+}
+
+// Returns a UInt32 column. Literal patterns are escaped before being
+// handed to the regex engine.
+#[rustler::nif]
+pub fn expr_str_count_matches(expr: ExExpr, pattern: String, literal: bool) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    let pattern = if literal {
+        escape_regex(&pattern)
+    } else {
+        pattern
+    };
+
+    ExExpr::new(expr.str().count_match(&pattern))
+}
+
+// Returns a List(Utf8) column. Rows without a match get an empty list
+// and nil rows stay nil.
+#[rustler::nif]
+pub fn expr_extract_all(expr: ExExpr, pattern: String) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.str().extract_all(&pattern))
+}
+
+fn escape_regex(pattern: &str) -> String {
+    let mut escaped = String::with_capacity(pattern.len());
+
+    for c in pattern.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Strings that aren't valid JSON or don't contain the path yield nil.
+// Matched strings are returned unquoted and any other value is returned
+// as its JSON text. The path is compiled upfront so invalid paths are
+// reported when the expression is built.
+#[rustler::nif]
+pub fn expr_str_json_path_match(expr: ExExpr, path: String) -> Result<ExExpr, ExplorerError> {
+    Utf8Chunked::full_null("", 0)
+        .json_path_match(&path)
+        .map_err(|_| ExplorerError::Other(format!("Invalid JSON path: {}", path)))?;
+
+    let expr: Expr = expr.resource.0.clone();
+
+    Ok(ExExpr::new(expr.map(
+        move |s| Ok(s.utf8()?.json_path_match(&path)?.into_series()),
+        GetOutput::from_type(DataType::Utf8),
+    )))
+}
+
+// Prefix and suffix checks match the pattern literally, not as a regex.
+#[rustler::nif]
+pub fn expr_str_starts_with(expr: ExExpr, prefix: String) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.str().starts_with(&prefix))
+}
+
+#[rustler::nif]
+pub fn expr_str_ends_with(expr: ExExpr, suffix: String) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+    ExExpr::new(expr.str().ends_with(&suffix))
+}
+
+// Reverses by Unicode scalar values, so combining marks are not kept
+// attached to their base character.
+#[rustler::nif]
+pub fn expr_str_reverse(expr: ExExpr) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.map(
+        |s| {
+            let mut out = s
+                .utf8()?
+                .into_iter()
+                .map(|v| v.map(|v| v.chars().rev().collect::<String>()))
+                .collect::<Utf8Chunked>()
+                .into_series();
+            out.rename(s.name());
+            Ok(out)
+        },
+        GetOutput::from_type(DataType::Utf8),
+    ))
+}
+
+// A zero offset slices from the start, so taking no characters needs a
+// zero length instead.
+#[rustler::nif]
+pub fn expr_str_tail(expr: ExExpr, n: usize) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    match n {
+        0 => ExExpr::new(expr.str().str_slice(0, Some(0))),
+        n => ExExpr::new(expr.str().str_slice(-(n as i64), None)),
+    }
+}
+
+// Uses the portable hasher from the hashing module rather than polars' ahash,
+// so the values are reproducible for a given seed.
+#[rustler::nif]
+pub fn expr_hash(expr: ExExpr, seed: u64) -> ExExpr {
+    let expr: Expr = expr.resource.0.clone();
+
+    ExExpr::new(expr.map(
+        move |s| hash_rows(s.name(), &[s.clone()], seed),
+        GetOutput::from_type(DataType::UInt64),
+    ))
+}
+
+// Expressions are serialized to JSON through polars' serde support, so they
+// can be cached or sent to another node. Expressions that wrap a Rust closure,
+// such as the ones built on top of `map`, can't be serialized and return an error.
+#[rustler::nif]
+pub fn expr_to_binary(env: Env, expr: ExExpr) -> Result<Binary, ExplorerError> {
+    let bytes = serde_json::to_vec(&expr.resource.0)
+        .map_err(|error| ExplorerError::Other(format!("Cannot serialize expression: {}", error)))?;
+
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.copy_from_slice(&bytes);
+
+    Ok(binary.into())
+}
+
+#[rustler::nif]
+pub fn expr_from_binary(binary: Binary) -> Result<ExExpr, ExplorerError> {
+    let expr: Expr = serde_json::from_slice(binary.as_slice()).map_err(|error| {
+        ExplorerError::Other(format!("Cannot deserialize expression: {}", error))
+    })?;
+
+    Ok(ExExpr::new(expr))
 }
@@ -0,0 +1,153 @@
+// Row hashing for change detection.
+//
+// Polars' own hashes use ahash, whose output depends on the CPU
+// features available at runtime, so they are not stable across
+// machines. Instead every value is fed into a seeded 64-bit FNV-1a
+// hasher with a SplitMix64 finalizer. Both are fixed algorithms, so
+// a given seed produces the same hashes on every run and platform.
+//
+// Each value is prefixed with a tag byte (0 for null, 1 otherwise) and
+// strings and binaries are prefixed with their length, so ["ab", "c"]
+// and ["a", "bc"] hash differently. Floats that compare equal hash the
+// same: -0.0 is hashed as 0.0 and every NaN as the canonical NaN.
+
+use polars::prelude::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Clone, Copy)]
+struct RowHasher(u64);
+
+impl RowHasher {
+    fn new(seed: u64) -> Self {
+        RowHasher(FNV_OFFSET_BASIS ^ seed)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_null(&mut self) {
+        self.write(&[0]);
+    }
+
+    fn write_value(&mut self, bytes: &[u8]) {
+        self.write(&[1]);
+        self.write(bytes);
+    }
+
+    fn write_sized(&mut self, bytes: &[u8]) {
+        self.write(&[1]);
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    fn finish(self) -> u64 {
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+macro_rules! hash_numeric {
+    ($s:ident, $hashers:ident, $convert_function:ident) => {
+        for (hasher, value) in $hashers.iter_mut().zip($s.$convert_function()?.into_iter()) {
+            match value {
+                Some(v) => hasher.write_value(&v.to_le_bytes()),
+                None => hasher.write_null(),
+            }
+        }
+    };
+}
+
+macro_rules! hash_float {
+    ($s:ident, $hashers:ident, $convert_function:ident, $float:ty) => {
+        for (hasher, value) in $hashers.iter_mut().zip($s.$convert_function()?.into_iter()) {
+            match value {
+                Some(v) if v == 0.0 => hasher.write_value(&(0.0 as $float).to_le_bytes()),
+                Some(v) if v.is_nan() => hasher.write_value(&<$float>::NAN.to_le_bytes()),
+                Some(v) => hasher.write_value(&v.to_le_bytes()),
+                None => hasher.write_null(),
+            }
+        }
+    };
+}
+
+fn hash_column(s: &Series, hashers: &mut [RowHasher]) -> Result<(), PolarsError> {
+    // Categoricals hash by their values so they match the equivalent strings.
+    let s = match s.dtype() {
+        DataType::Categorical(_) => s.cast(&DataType::Utf8)?,
+        _ => s.to_physical_repr().into_owned(),
+    };
+
+    match s.dtype() {
+        DataType::Boolean => {
+            for (hasher, value) in hashers.iter_mut().zip(s.bool()?.into_iter()) {
+                match value {
+                    Some(v) => hasher.write_value(&[v as u8]),
+                    None => hasher.write_null(),
+                }
+            }
+        }
+        DataType::Utf8 => {
+            for (hasher, value) in hashers.iter_mut().zip(s.utf8()?.into_iter()) {
+                match value {
+                    Some(v) => hasher.write_sized(v.as_bytes()),
+                    None => hasher.write_null(),
+                }
+            }
+        }
+        DataType::Binary => {
+            for (hasher, value) in hashers.iter_mut().zip(s.binary()?.into_iter()) {
+                match value {
+                    Some(v) => hasher.write_sized(v),
+                    None => hasher.write_null(),
+                }
+            }
+        }
+        DataType::Int8 => hash_numeric!(s, hashers, i8),
+        DataType::Int16 => hash_numeric!(s, hashers, i16),
+        DataType::Int32 => hash_numeric!(s, hashers, i32),
+        DataType::Int64 => hash_numeric!(s, hashers, i64),
+        DataType::UInt8 => hash_numeric!(s, hashers, u8),
+        DataType::UInt16 => hash_numeric!(s, hashers, u16),
+        DataType::UInt32 => hash_numeric!(s, hashers, u32),
+        DataType::UInt64 => hash_numeric!(s, hashers, u64),
+        DataType::Float32 => hash_float!(s, hashers, f32, f32),
+        DataType::Float64 => hash_float!(s, hashers, f64, f64),
+        dt => {
+            return Err(PolarsError::ComputeError(
+                format!("hashing is not supported for {}", dt).into(),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+/// Hashes each row across the given columns, which must have the same length.
+pub fn hash_rows(name: &str, columns: &[Series], seed: u64) -> Result<Series, PolarsError> {
+    let len = match columns.first() {
+        Some(s) => s.len(),
+        None => {
+            return Err(PolarsError::ComputeError(
+                "hashing rows needs at least one column".into(),
+            ))
+        }
+    };
+    let mut hashers = vec![RowHasher::new(seed); len];
+
+    for s in columns {
+        hash_column(s, &mut hashers)?;
+    }
+
+    Ok(
+        UInt64Chunked::from_vec(name, hashers.into_iter().map(RowHasher::finish).collect())
+            .into_series(),
+    )
+}
@@ -1,11 +1,132 @@
+use polars::lazy::logical_plan::LogicalPlan;
 use polars::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
 use std::result::Result;
 
-use crate::{ExDataFrame, ExLazyFrame, ExplorerError};
+use crate::dataframe::check_local_path;
+use crate::{ExDataFrame, ExExpr, ExLazyFrame, ExplorerError};
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn lf_scan_parquet(filename: &str) -> Result<ExLazyFrame, ExplorerError> {
+    check_local_path(filename)?;
+    let lf = LazyFrame::scan_parquet(filename.to_string(), ScanArgsParquet::default())?;
+    Ok(ExLazyFrame::new(lf))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn lf_scan_csv(
+    filename: &str,
+    has_header: bool,
+    delimiter: u8,
+    skip_rows: usize,
+    null_values: Vec<String>,
+    infer_schema_length: Option<usize>,
+    max_rows: Option<usize>,
+) -> Result<ExLazyFrame, ExplorerError> {
+    check_local_path(filename)?;
+    let lf = LazyCsvReader::new(filename.to_string())
+        .has_header(has_header)
+        .with_delimiter(delimiter)
+        .with_skip_rows(skip_rows)
+        .with_null_values(Some(NullValues::AllColumns(null_values)))
+        .with_infer_schema_length(infer_schema_length)
+        .with_n_rows(max_rows)
+        .finish()?;
+    Ok(ExLazyFrame::new(lf))
+}
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn lf_collect(data: ExLazyFrame) -> Result<ExDataFrame, ExplorerError> {
-    Ok(ExDataFrame::new(data.resource.0.clone().collect()?))
+    collect(data.resource.0.clone())
+}
+
+// Runs the plan with the streaming engine, which processes the source in batches
+// so scans larger than memory can be filtered and aggregated.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn lf_collect_streaming(data: ExLazyFrame) -> Result<ExDataFrame, ExplorerError> {
+    collect(data.resource.0.clone().with_streaming(true))
+}
+
+fn collect(lf: LazyFrame) -> Result<ExDataFrame, ExplorerError> {
+    let plan = lf.logical_plan.clone();
+
+    match lf.collect() {
+        Ok(df) => Ok(ExDataFrame::new(df)),
+        Err(error) => match csv_error_line(&plan, &error) {
+            Some(line) => Err(ExplorerError::Other(format!("{}; {}", error, line))),
+            None => Err(error.into()),
+        },
+    }
+}
+
+// Polars reports the byte offset of a CSV value it could not parse, which
+// is hard to act on for large files, so we turn it into a line number of
+// the scanned file. This is best-effort and only done for plans reading a
+// single CSV file: the original error is always kept.
+fn csv_error_line(plan: &LogicalPlan, error: &PolarsError) -> Option<String> {
+    let message = error.to_string();
+    let offset: u64 = message
+        .split("offset in the file is ")
+        .nth(1)?
+        .split(' ')
+        .next()?
+        .parse()
+        .ok()?;
+
+    let mut paths = vec![];
+    csv_scan_paths(plan, &mut paths);
+
+    match paths.as_slice() {
+        [path] => {
+            let buf_reader = BufReader::new(File::open(path).ok()?);
+            let newlines = buf_reader
+                .take(offset)
+                .bytes()
+                .filter(|byte| matches!(byte, Ok(b'\n')))
+                .count();
+
+            Some(format!(
+                "could not parse line {} of {}",
+                newlines + 1,
+                path.display()
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn csv_scan_paths(plan: &LogicalPlan, paths: &mut Vec<PathBuf>) {
+    match plan {
+        LogicalPlan::CsvScan { path, .. } => paths.push(path.clone()),
+        LogicalPlan::Join {
+            input_left,
+            input_right,
+            ..
+        } => {
+            csv_scan_paths(input_left, paths);
+            csv_scan_paths(input_right, paths);
+        }
+        LogicalPlan::Union { inputs, .. } => {
+            for input in inputs {
+                csv_scan_paths(input, paths);
+            }
+        }
+        LogicalPlan::Selection { input, .. }
+        | LogicalPlan::Cache { input, .. }
+        | LogicalPlan::Projection { input, .. }
+        | LogicalPlan::LocalProjection { input, .. }
+        | LogicalPlan::Aggregate { input, .. }
+        | LogicalPlan::HStack { input, .. }
+        | LogicalPlan::Distinct { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Explode { input, .. }
+        | LogicalPlan::Slice { input, .. }
+        | LogicalPlan::Melt { input, .. }
+        | LogicalPlan::MapFunction { input, .. } => csv_scan_paths(input, paths),
+        _ => (),
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -62,3 +183,10 @@ pub fn lf_drop(data: ExLazyFrame, columns: Vec<&str>) -> Result<ExLazyFrame, Exp
     let lf = &data.resource.0.clone().select(&[col("*").exclude(columns)]);
     Ok(ExLazyFrame::new(lf.clone()))
 }
+
+#[rustler::nif]
+pub fn lf_filter(data: ExLazyFrame, ex_expr: ExExpr) -> Result<ExLazyFrame, ExplorerError> {
+    let lf = data.resource.0.clone();
+    let exp: Expr = ex_expr.resource.0.clone();
+    Ok(ExLazyFrame::new(lf.filter(exp)))
+}
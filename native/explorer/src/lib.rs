@@ -20,24 +20,32 @@ mod datatypes;
 mod encoding;
 mod error;
 mod expressions;
+mod hashing;
 mod lazyframe;
 mod series;
+mod threads;
 
 use dataframe::*;
 pub use datatypes::{
-    ExDataFrame, ExDataFrameRef, ExExpr, ExExprRef, ExLazyFrame, ExLazyFrameRef, ExSeries,
-    ExSeriesRef,
+    ExArrowCRef, ExDataFrame, ExDataFrameRef, ExExpr, ExExprRef, ExLazyFrame, ExLazyFrameRef,
+    ExRowStreamRef, ExSeries, ExSeriesRef,
 };
 pub use error::ExplorerError;
 use expressions::*;
 use lazyframe::*;
 use series::*;
+use threads::*;
 
 fn on_load(env: Env, _info: Term) -> bool {
+    rustler::resource!(ExArrowCRef, env);
     rustler::resource!(ExDataFrameRef, env);
     rustler::resource!(ExExprRef, env);
     rustler::resource!(ExLazyFrameRef, env);
+    rustler::resource!(ExRowStreamRef, env);
     rustler::resource!(ExSeriesRef, env);
+    // Categoricals built in different calls can only be compared
+    // or combined when they share the same string cache.
+    polars::prelude::enable_string_cache(true);
     true
 }
 
@@ -65,32 +73,52 @@ rustler::init!(
     [
         df_arrange_with,
         df_column,
+        df_describe_plan,
         df_names,
         df_drop,
         df_drop_duplicates,
         df_drop_nulls,
+        df_dump_ipc,
+        df_dump_parquet,
         df_dtypes,
+        df_estimated_size,
+        df_n_chunks,
+        df_rechunk,
+        df_export_arrow_c,
         df_filter_with,
         df_get_columns,
         df_groups,
         df_groupby_agg_with,
+        df_hash_rows,
+        df_correlation,
         df_group_indices,
         df_head,
         df_height,
         df_hstack_many,
+        df_import_arrow_c,
         df_join,
+        df_load_ipc,
+        df_load_parquet,
         df_mask,
+        df_take,
         df_melt,
         df_new,
+        df_from_rows,
+        df_partition_by,
         df_pivot_wider,
         df_read_csv,
         df_read_ipc,
+        df_read_ipc_stream,
         df_read_parquet,
         df_read_ndjson,
         df_write_ndjson,
+        df_from_xlsx,
+        df_to_xlsx,
         df_select,
         df_select_at_idx,
         df_rename_columns,
+        df_rename_many,
+        df_rename_with_affix,
         df_shape,
         df_slice,
         df_slice_by_indices,
@@ -99,17 +127,37 @@ rustler::init!(
         df_to_csv,
         df_to_csv_file,
         df_to_dummies,
+        df_to_rows,
+        df_to_rows_stream,
+        df_to_rows_stream_next,
         df_to_lazy,
+        df_to_parquet_partitioned,
+        df_unnest,
         df_vstack_many,
         df_width,
         df_with_columns,
         df_with_column_exprs,
+        df_with_row_count,
         df_write_ipc,
+        df_write_ipc_stream,
         df_write_parquet,
         // expressions
+        expr_binary,
         expr_boolean,
+        expr_nil,
         expr_cast,
+        expr_clip_to_dtype_range,
+        expr_to_physical,
+        expr_shrink_dtype,
         expr_column,
+        expr_convert_time_zone,
+        expr_replace_time_zone,
+        expr_round_datetime,
+        expr_is_between_datetime,
+        expr_timestamp,
+        expr_total_days,
+        expr_total_milliseconds,
+        expr_total_seconds,
         expr_date,
         expr_datetime,
         expr_float,
@@ -118,15 +166,51 @@ rustler::init!(
         expr_slice,
         expr_head,
         expr_tail,
-        expr_peaks,
+        expr_peak_max,
+        expr_peak_min,
         expr_fill_missing,
         expr_fill_missing_with_value,
+        expr_backward_fill,
+        expr_forward_fill,
+        expr_map_dict,
+        expr_max_horizontal,
+        expr_min_horizontal,
+        expr_sum_horizontal,
+        // list
+        expr_list_contains,
+        expr_list_get,
+        expr_list_join,
+        expr_list_lengths,
+        // strings
+        expr_str_count_matches,
+        expr_extract_all,
+        expr_str_ends_with,
+        expr_str_head,
+        expr_str_json_path_match,
+        expr_str_reverse,
+        expr_str_starts_with,
+        expr_str_tail,
+        // serialization
+        expr_from_binary,
+        expr_to_binary,
+        // hashing
+        expr_hash,
         // sort
+        expr_sort_by,
         expr_argsort,
+        expr_arg_unique,
+        expr_search_sorted,
+        expr_set_sorted,
+        expr_rechunk,
         expr_distinct,
         expr_unordered_distinct,
+        expr_rle,
+        expr_sample_n,
+        expr_shuffle,
         expr_reverse,
         expr_sort,
+        expr_top_k,
+        expr_bottom_k,
         // comparison expressions
         expr_binary_and,
         expr_binary_or,
@@ -136,57 +220,102 @@ rustler::init!(
         expr_all_equal,
         expr_is_nil,
         expr_is_not_nil,
+        expr_is_duplicated,
+        expr_is_unique,
+        expr_is_in,
         expr_lt,
         expr_lt_eq,
         expr_neq,
         // arithmetic expressions
         expr_add,
+        expr_checked_add,
+        expr_checked_multiply,
+        expr_bitwise_and,
+        expr_bitwise_or,
+        expr_bitwise_xor,
+        expr_shift_left,
+        expr_shift_right,
         expr_subtract,
+        expr_abs_diff,
         expr_divide,
         expr_multiply,
         expr_pow,
         expr_quotient,
         expr_remainder,
+        expr_round_to_multiple,
         // slice and dice expressions
+        expr_filter,
+        expr_gather_every,
+        expr_take,
         expr_coalesce,
+        expr_append,
+        expr_if_else,
+        expr_replace_values,
         // agg expressions
         expr_sum,
         expr_min,
         expr_max,
         expr_mean,
         expr_median,
+        expr_mad,
         expr_n_distinct,
+        expr_nil_count,
         expr_std,
         expr_var,
         expr_quantile,
+        expr_quantiles,
+        expr_value_counts,
+        expr_min_max_scale,
+        expr_winsorize,
         expr_alias,
+        expr_arg_max,
+        expr_arg_min,
         expr_count,
         expr_first,
         expr_last,
+        expr_first_non_null,
+        expr_last_non_null,
+        expr_all,
+        expr_any,
         // window expressions
         expr_cumulative_max,
         expr_cumulative_min,
         expr_cumulative_sum,
+        expr_cumulative_sum_over,
+        expr_rolling_sum_by,
+        expr_interpolate_by,
         expr_window_max,
         expr_window_mean,
         expr_window_min,
+        expr_window_skew,
         expr_window_sum,
+        expr_over,
+        expr_repeat_by,
+        expr_row_index,
         // inspect expressions
         expr_describe_filter_plan,
+        expr_describe_filter_plan_optimized,
+        expr_to_string,
+        expr_dot,
         // lazyframe
         lf_collect,
+        lf_collect_streaming,
         lf_describe_plan,
         lf_drop,
         lf_dtypes,
         lf_fetch,
+        lf_filter,
         lf_head,
         lf_names,
+        lf_scan_csv,
+        lf_scan_parquet,
         lf_select,
         lf_tail,
         // series
         s_add,
         s_and,
         s_append,
+        s_concat,
         s_argsort,
         s_as_str,
         s_cast,
@@ -202,14 +331,22 @@ rustler::init!(
         s_fill_none_with_int,
         s_fill_none_with_float,
         s_fill_none_with_bin,
+        s_from_binary,
         s_filter,
+        s_mask,
+        s_take,
         s_get,
         s_gt,
         s_gt_eq,
         s_head,
+        s_is_duplicated,
         s_is_not_null,
         s_is_null,
+        s_is_unique,
         s_len,
+        s_size_bytes,
+        s_n_chunks,
+        s_sorted_flag,
         s_lt,
         s_lt_eq,
         s_max,
@@ -218,13 +355,19 @@ rustler::init!(
         s_min,
         s_mul,
         s_n_unique,
+        s_value_counts,
+        s_to_dummies,
+        s_null_count,
         s_name,
         s_neq,
+        s_new_binary,
         s_new_bool,
         s_new_date32,
         s_new_date64,
         s_new_f64,
         s_new_i64,
+        s_new_list,
+        s_new_u64,
         s_new_str,
         s_or,
         s_peak_max,
@@ -248,12 +391,17 @@ rustler::init!(
         s_std,
         s_sub,
         s_sum,
+        s_all,
+        s_any,
         s_tail,
         s_take_every,
         s_to_list,
+        s_to_binary,
         s_unordered_distinct,
         s_var,
-        s_value_counts,
+        // threads
+        max_threads,
+        set_max_threads,
     ],
     load = on_load
 );
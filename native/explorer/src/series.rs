@@ -4,12 +4,17 @@ use crate::{
 };
 
 use polars::prelude::*;
+use polars_core::utils::get_supertype;
 use rand::seq::IteratorRandom;
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
-use rustler::{Encoder, Env, Term};
+use rustler::{Binary, Encoder, Env, NewBinary, Term};
 use std::result::Result;
 
+// NIFs that are linear on the series length run on dirty CPU schedulers,
+// including building series from Elixir lists. Accessors for metadata
+// and zero-copy slices run on normal schedulers.
+
 pub(crate) fn to_series_collection(s: Vec<ExSeries>) -> Vec<Series> {
     s.into_iter().map(|c| c.resource.0.clone()).collect()
 }
@@ -25,7 +30,7 @@ pub fn s_as_str(data: ExSeries) -> Result<String, ExplorerError> {
 
 macro_rules! init_method {
     ($name:ident, $type:ty) => {
-        #[rustler::nif]
+        #[rustler::nif(schedule = "DirtyCpu")]
         pub fn $name(name: &str, val: Vec<Option<$type>>) -> ExSeries {
             ExSeries::new(Series::new(name, val.as_slice()))
         }
@@ -33,36 +38,92 @@ macro_rules! init_method {
 }
 
 init_method!(s_new_i64, i64);
+init_method!(s_new_u64, u64);
 init_method!(s_new_bool, bool);
 init_method!(s_new_f64, f64);
 init_method!(s_new_str, String);
 
-#[rustler::nif]
-pub fn s_new_date32(name: &str, val: Vec<Option<ExDate>>) -> ExSeries {
-    ExSeries::new(
+// Binaries are kept as raw bytes, without any UTF-8 validation.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_new_binary(name: &str, val: Vec<Option<Binary>>) -> ExSeries {
+    let values: Vec<Option<&[u8]>> = val
+        .iter()
+        .map(|binary| binary.as_ref().map(|binary| binary.as_slice()))
+        .collect();
+
+    ExSeries::new(Series::new(name, values))
+}
+
+// Builds a list series out of already constructed series, one per row.
+// Numeric rows are cast to their supertype, other rows must share a dtype.
+// Empty rows take the dtype of the others.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_new_list(name: &str, val: Vec<Option<ExSeries>>) -> Result<ExSeries, ExplorerError> {
+    let rows: Vec<Option<&Series>> = val
+        .iter()
+        .map(|series| series.as_ref().map(|series| &series.resource.0))
+        .collect();
+
+    let mut inner_dtype: Option<DataType> = None;
+
+    for series in rows.iter().flatten().filter(|series| !series.is_empty()) {
+        inner_dtype = match inner_dtype {
+            None => Some(series.dtype().clone()),
+            Some(dtype) if dtype == *series.dtype() => Some(dtype),
+            Some(dtype) if dtype.is_numeric() && series.dtype().is_numeric() => {
+                Some(get_supertype(&dtype, series.dtype())?)
+            }
+            Some(dtype) => {
+                return Err(ExplorerError::Other(format!(
+                    "cannot build a list series out of series of dtypes {} and {}",
+                    dtype,
+                    series.dtype()
+                )))
+            }
+        };
+    }
+
+    let inner_dtype =
+        inner_dtype.or_else(|| rows.iter().flatten().next().map(|s| s.dtype().clone()));
+
+    let rows = rows
+        .into_iter()
+        .map(|series| match (series, &inner_dtype) {
+            (Some(series), Some(dtype)) => series.cast(dtype).map(Some),
+            (series, _) => Ok(series.cloned()),
+        })
+        .collect::<Result<Vec<Option<Series>>, PolarsError>>()?;
+
+    let mut list: ListChunked = rows.into_iter().collect();
+    list.rename(name);
+
+    Ok(ExSeries::new(list.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_new_date32(name: &str, val: Vec<Option<ExDate>>) -> Result<ExSeries, ExplorerError> {
+    Ok(ExSeries::new(
         Series::new(
             name,
             val.iter()
                 .map(|d| d.map(|d| d.into()))
                 .collect::<Vec<Option<i32>>>(),
         )
-        .cast(&DataType::Date)
-        .unwrap(),
-    )
+        .cast(&DataType::Date)?,
+    ))
 }
 
-#[rustler::nif]
-pub fn s_new_date64(name: &str, val: Vec<Option<ExDateTime>>) -> ExSeries {
-    ExSeries::new(
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_new_date64(name: &str, val: Vec<Option<ExDateTime>>) -> Result<ExSeries, ExplorerError> {
+    Ok(ExSeries::new(
         Series::new(
             name,
             val.iter()
                 .map(|dt| dt.map(|dt| dt.into()))
                 .collect::<Vec<Option<i64>>>(),
         )
-        .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
-        .unwrap(),
-    )
+        .cast(&DataType::Datetime(TimeUnit::Microseconds, None))?,
+    ))
 }
 
 #[rustler::nif]
@@ -99,6 +160,48 @@ pub fn s_append(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerErr
     Ok(ExSeries::new(s))
 }
 
+// Stacks the series into a single contiguous one. Series of different
+// dtypes are cast to a common one: numbers become floats as soon as one
+// of them is a float, and integers otherwise.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_concat(series: Vec<ExSeries>) -> Result<ExSeries, ExplorerError> {
+    let series = to_series_collection(series);
+    let dtypes: Vec<&DataType> = series.iter().map(|s| s.dtype()).collect();
+
+    let dtype = match dtypes.first() {
+        None => {
+            return Err(ExplorerError::Other(
+                "concat expects at least one series".into(),
+            ))
+        }
+        Some(first) if dtypes.iter().all(|dtype| dtype == first) => (*first).clone(),
+        Some(_) if dtypes.iter().all(|dtype| dtype.is_numeric()) => {
+            if dtypes
+                .iter()
+                .any(|dtype| matches!(dtype, DataType::Float32 | DataType::Float64))
+            {
+                DataType::Float64
+            } else {
+                DataType::Int64
+            }
+        }
+        Some(_) => {
+            let names: Vec<String> = dtypes.iter().map(|dtype| dtype.to_string()).collect();
+            return Err(ExplorerError::Other(format!(
+                "cannot concat series of incompatible dtypes: {}",
+                names.join(", ")
+            )));
+        }
+    };
+
+    let mut out = series[0].cast(&dtype)?;
+    for s in &series[1..] {
+        out.append(&s.cast(&dtype)?)?;
+    }
+
+    Ok(ExSeries::new(out.rechunk()))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_filter(data: ExSeries, filter: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
@@ -111,10 +214,48 @@ pub fn s_filter(data: ExSeries, filter: ExSeries) -> Result<ExSeries, ExplorerEr
     }
 }
 
+// Unlike s_filter, the mask must have exactly the series length
+// (no broadcasting) and nils in the mask drop the value.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_mask(data: ExSeries, mask: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    let mask = &mask.resource.0;
+
+    if s.len() != mask.len() {
+        return Err(ExplorerError::Other(format!(
+            "mask must have the same length as the series, got {} and {}",
+            mask.len(),
+            s.len()
+        )));
+    }
+
+    let mask = mask
+        .bool()
+        .map_err(|_| ExplorerError::Other("Expected a boolean mask".into()))?
+        .fill_null_with_values(false)?;
+
+    Ok(ExSeries::new(s.filter(&mask)?))
+}
+
+// Arithmetic between series panics in polars when lengths differ,
+// unless one side has a single value that is broadcasted.
+fn check_lengths(s: &Series, s1: &Series) -> Result<(), ExplorerError> {
+    if s.len() == s1.len() || s.len() == 1 || s1.len() == 1 {
+        Ok(())
+    } else {
+        Err(ExplorerError::Other(format!(
+            "Series must have the same length, got {} and {}",
+            s.len(),
+            s1.len()
+        )))
+    }
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_add(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     Ok(ExSeries::new(s + s1))
 }
 
@@ -122,6 +263,7 @@ pub fn s_add(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError>
 pub fn s_sub(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     Ok(ExSeries::new(s - s1))
 }
 
@@ -129,6 +271,7 @@ pub fn s_sub(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError>
 pub fn s_mul(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     Ok(ExSeries::new(s * s1))
 }
 
@@ -136,6 +279,7 @@ pub fn s_mul(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError>
 pub fn s_div(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     Ok(ExSeries::new(s / s1))
 }
 
@@ -143,6 +287,7 @@ pub fn s_div(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError>
 pub fn s_quotient(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     let div = s.checked_div(s1)?;
 
     Ok(ExSeries::new(div))
@@ -152,6 +297,7 @@ pub fn s_quotient(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerE
 pub fn s_remainder(data: ExSeries, other: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &other.resource.0;
+    check_lengths(s, s1)?;
     let div = s.checked_div(s1)?;
     let mult = s1 * &div;
     let result = s - &mult;
@@ -202,16 +348,6 @@ pub fn s_unordered_distinct(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(unique))
 }
 
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn s_value_counts(data: ExSeries) -> Result<ExDataFrame, ExplorerError> {
-    let s: &Series = &data.resource.0;
-    let mut df = s.value_counts(true, true)?;
-    let df = df
-        .try_apply("counts", |s: &Series| s.cast(&DataType::Int64))?
-        .clone();
-    Ok(ExDataFrame::new(df))
-}
-
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_slice_by_indices(data: ExSeries, indices: Vec<u32>) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
@@ -220,6 +356,26 @@ pub fn s_slice_by_indices(data: ExSeries, indices: Vec<u32>) -> Result<ExSeries,
     Ok(ExSeries::new(s1))
 }
 
+// Negative indices are rejected rather than counted from the end.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_take(data: ExSeries, indices: Vec<i64>) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+
+    if let Some(idx) = indices
+        .iter()
+        .find(|idx| **idx < 0 || **idx >= s.len() as i64)
+    {
+        return Err(ExplorerError::Other(format!(
+            "Index {} is out of bounds for series of length {}",
+            idx,
+            s.len()
+        )));
+    }
+
+    let idx: UInt32Chunked = indices.iter().map(|idx| *idx as u32).collect();
+    Ok(ExSeries::new(s.take(&idx)?))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_is_null(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
@@ -232,6 +388,25 @@ pub fn s_is_not_null(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(s.is_not_null().into_series()))
 }
 
+// Every occurrence of a repeated value is flagged, and nulls count as equal.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_is_duplicated(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(ExSeries::new(s.is_duplicated()?.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_is_unique(data: ExSeries) -> Result<ExSeries, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(ExSeries::new(s.is_unique()?.into_series()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_null_count(data: ExSeries) -> Result<usize, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(s.null_count())
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_take_every(data: ExSeries, n: usize) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
@@ -259,42 +434,42 @@ pub fn s_series_equal(
 pub fn s_eq(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.equal(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.equal(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_neq(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.not_equal(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.not_equal(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_gt(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.gt(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.gt(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_gt_eq(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.gt_eq(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.gt_eq(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_lt(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.lt(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.lt(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_lt_eq(data: ExSeries, rhs: ExSeries) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
     let s1 = &rhs.resource.0;
-    Ok(ExSeries::new(s.lt_eq(s1).unwrap().into_series()))
+    Ok(ExSeries::new(s.lt_eq(s1)?.into_series()))
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -319,6 +494,30 @@ pub fn s_len(data: ExSeries) -> Result<usize, ExplorerError> {
     Ok(s.len())
 }
 
+// Estimated heap size of the underlying Arrow buffers. For strings this
+// includes both the offsets and the bytes of the values.
+#[rustler::nif]
+pub fn s_size_bytes(data: ExSeries) -> Result<usize, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(s.estimated_size())
+}
+
+#[rustler::nif]
+pub fn s_sorted_flag(data: ExSeries) -> Result<String, ExplorerError> {
+    let flag = match data.resource.0.is_sorted() {
+        IsSorted::Ascending => "ascending",
+        IsSorted::Descending => "descending",
+        IsSorted::Not => "not",
+    };
+    Ok(flag.to_string())
+}
+
+#[rustler::nif]
+pub fn s_n_chunks(data: ExSeries) -> Result<usize, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(s.n_chunks())
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_fill_none(data: ExSeries, strategy: &str) -> Result<ExSeries, ExplorerError> {
     let strat = match strategy {
@@ -417,7 +616,9 @@ pub fn s_rolling_min(
     Ok(ExSeries::new(s1))
 }
 
-// Used for rolling functions - also see "expressions" module
+// Used for rolling functions - also see "expressions" module.
+// When `min_periods` is not given, it defaults to the full window size,
+// so leading partial windows produce nulls instead of partial results.
 pub fn rolling_opts(
     window_size: usize,
     weights: Option<Vec<f64>>,
@@ -442,7 +643,163 @@ pub fn rolling_opts(
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_to_list(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
-    Ok(encoding::list_from_series(data, env))
+    encoding::list_from_series(data, env)
+}
+// Booleans are packed as one byte per value and numbers use the native endianness.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_to_binary(env: Env, data: ExSeries) -> Result<Binary, ExplorerError> {
+    let s = data.resource.0.rechunk();
+
+    if s.null_count() > 0 {
+        return Err(ExplorerError::Other(String::from(
+            "Cannot convert a series with nil values to binary",
+        )));
+    }
+
+    let s = s.to_physical_repr();
+
+    match s.dtype() {
+        DataType::Boolean => {
+            let ca = s.bool()?;
+            let mut binary = NewBinary::new(env, ca.len());
+
+            for (byte, value) in binary.iter_mut().zip(ca.into_no_null_iter()) {
+                *byte = value as u8;
+            }
+
+            Ok(binary.into())
+        }
+        DataType::Float64 => numeric_to_binary(env, s.f64()?),
+        DataType::Int64 => numeric_to_binary(env, s.i64()?),
+        DataType::Int32 => numeric_to_binary(env, s.i32()?),
+        DataType::UInt32 => numeric_to_binary(env, s.u32()?),
+        dtype => Err(ExplorerError::Other(format!(
+            "Cannot convert series of type {} to binary",
+            dtype
+        ))),
+    }
+}
+
+fn numeric_to_binary<'a, T: PolarsNumericType>(
+    env: Env<'a>,
+    ca: &ChunkedArray<T>,
+) -> Result<Binary<'a>, ExplorerError> {
+    let values = ca.cont_slice()?;
+    let bytes = unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    };
+
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.copy_from_slice(bytes);
+
+    Ok(binary.into())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_from_binary(binary: Binary, dtype: &str) -> Result<ExSeries, ExplorerError> {
+    let bytes = binary.as_slice();
+
+    let s = match cast_str_to_dtype(dtype)? {
+        DataType::Boolean => bytes
+            .iter()
+            .map(|byte| *byte != 0)
+            .collect::<BooleanChunked>()
+            .into_series(),
+        DataType::Float64 => numeric_from_binary::<Float64Type>(bytes)?.into_series(),
+        DataType::Int64 => numeric_from_binary::<Int64Type>(bytes)?.into_series(),
+        DataType::Int32 => numeric_from_binary::<Int32Type>(bytes)?.into_series(),
+        DataType::UInt32 => numeric_from_binary::<UInt32Type>(bytes)?.into_series(),
+        DataType::Date => numeric_from_binary::<Int32Type>(bytes)?
+            .into_date()
+            .into_series(),
+        DataType::Datetime(time_unit, time_zone) => numeric_from_binary::<Int64Type>(bytes)?
+            .into_datetime(time_unit, time_zone)
+            .into_series(),
+        dtype => {
+            return Err(ExplorerError::Other(format!(
+                "Cannot build series of type {} from binary",
+                dtype
+            )))
+        }
+    };
+
+    Ok(ExSeries::new(s))
+}
+
+fn numeric_from_binary<T: PolarsNumericType>(
+    bytes: &[u8],
+) -> Result<ChunkedArray<T>, ExplorerError> {
+    let size = std::mem::size_of::<T::Native>();
+
+    if bytes.len() % size != 0 {
+        return Err(ExplorerError::Other(format!(
+            "Binary of {} bytes is not a multiple of the element size {}",
+            bytes.len(),
+            size
+        )));
+    }
+
+    let values: Vec<T::Native> = bytes
+        .chunks_exact(size)
+        .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T::Native) })
+        .collect();
+
+    Ok(ChunkedArray::from_vec("", values))
+}
+
+// Integer sums that overflow are returned as nil rather than wrapping around.
+macro_rules! checked_sum {
+    ($ca:expr, $type:ty) => {
+        $ca.into_iter()
+            .flatten()
+            .try_fold(0 as $type, |acc, v| acc.checked_add(v))
+    };
+}
+
+// The sum of an integer series as a single-value series, for expressions.
+// Signed integers are summed as i64 and unsigned ones as u64.
+pub fn checked_sum_series(s: Series) -> Result<Series, PolarsError> {
+    let name = s.name().to_string();
+    let sum = match s.dtype() {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            let s = s.cast(&DataType::Int64)?;
+            Series::new(&name, &[checked_sum!(s.i64()?, i64)])
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            let s = s.cast(&DataType::UInt64)?;
+            Series::new(&name, &[checked_sum!(s.u64()?, u64)])
+        }
+        _ => s.sum_as_series(),
+    };
+
+    Ok(sum)
+}
+
+// Nulls are ignored, so an empty or all-null series is `all` but not `any`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_all(data: ExSeries) -> Result<bool, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(boolean_series(s, "all")?
+        .into_iter()
+        .all(|v| v != Some(false)))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_any(data: ExSeries) -> Result<bool, ExplorerError> {
+    let s = &data.resource.0;
+    Ok(boolean_series(s, "any")?
+        .into_iter()
+        .any(|v| v == Some(true)))
+}
+
+fn boolean_series<'a>(s: &'a Series, op: &str) -> Result<&'a BooleanChunked, ExplorerError> {
+    match s.dtype() {
+        DataType::Boolean => Ok(s.bool()?),
+        dtype => Err(ExplorerError::Other(format!(
+            "{} expects a boolean series, got {}",
+            op, dtype
+        ))),
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -456,9 +813,16 @@ pub fn s_sum(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         | DataType::UInt16
         | DataType::Int32
         | DataType::UInt32
-        | DataType::Int64 => Ok(s.sum::<i64>().encode(env)),
+        | DataType::Int64 => {
+            let s = s.cast(&DataType::Int64)?;
+            Ok(checked_sum!(s.i64()?, i64).encode(env))
+        }
+        DataType::UInt64 => Ok(checked_sum!(s.u64()?, u64).encode(env)),
         DataType::Float32 | DataType::Float64 => Ok(s.sum::<f64>().encode(env)),
-        dt => panic!("sum/1 not implemented for {:?}", dt),
+        dt => Err(ExplorerError::Other(format!(
+            "sum/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -478,7 +842,10 @@ pub fn s_min(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         DataType::Datetime(TimeUnit::Microseconds, None) => {
             Ok(s.min::<i64>().map(ExDateTime::from).encode(env))
         }
-        dt => panic!("min/1 not implemented for {:?}", dt),
+        dt => Err(ExplorerError::Other(format!(
+            "min/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -498,7 +865,10 @@ pub fn s_max(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         DataType::Datetime(TimeUnit::Microseconds, None) => {
             Ok(s.max::<i64>().map(ExDateTime::from).encode(env))
         }
-        dt => panic!("max/1 not implemented for {:?}", dt),
+        dt => Err(ExplorerError::Other(format!(
+            "max/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -516,7 +886,10 @@ pub fn s_mean(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         | DataType::Int64
         | DataType::Float32
         | DataType::Float64 => Ok(s.mean().encode(env)),
-        dt => panic!("mean/1 not implemented for {:?}", dt),
+        dt => Err(ExplorerError::Other(format!(
+            "mean/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -533,7 +906,10 @@ pub fn s_median(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         | DataType::Int64
         | DataType::Float32
         | DataType::Float64 => Ok(s.median().encode(env)),
-        dt => panic!("median/1 not implemented for {:?}", dt),
+        dt => Err(ExplorerError::Other(format!(
+            "median/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -547,9 +923,14 @@ pub fn s_var(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         | DataType::UInt16
         | DataType::Int32
         | DataType::UInt32
-        | DataType::Int64 => Ok(s.i64().unwrap().var(1).encode(env)),
-        DataType::Float32 | DataType::Float64 => Ok(s.f64().unwrap().var(1).encode(env)),
-        dt => panic!("var/1 not implemented for {:?}", dt),
+        | DataType::Int64 => Ok(s.cast(&DataType::Int64)?.i64()?.var(1).encode(env)),
+        DataType::Float32 | DataType::Float64 => {
+            Ok(s.cast(&DataType::Float64)?.f64()?.var(1).encode(env))
+        }
+        dt => Err(ExplorerError::Other(format!(
+            "var/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
@@ -563,34 +944,108 @@ pub fn s_std(env: Env, data: ExSeries) -> Result<Term, ExplorerError> {
         | DataType::UInt16
         | DataType::Int32
         | DataType::UInt32
-        | DataType::Int64 => Ok(s.i64().unwrap().std(1).encode(env)),
-        DataType::Float32 | DataType::Float64 => Ok(s.f64().unwrap().std(1).encode(env)),
-        dt => panic!("std/1 not implemented for {:?}", dt),
+        | DataType::Int64 => Ok(s.cast(&DataType::Int64)?.i64()?.std(1).encode(env)),
+        DataType::Float32 | DataType::Float64 => {
+            Ok(s.cast(&DataType::Float64)?.f64()?.std(1).encode(env))
+        }
+        dt => Err(ExplorerError::Other(format!(
+            "std/1 not implemented for {:?}",
+            dt
+        ))),
     }
 }
 
 #[rustler::nif]
 pub fn s_get(env: Env, data: ExSeries, idx: usize) -> Result<Term, ExplorerError> {
     let s = &data.resource.0;
-    Ok(encoding::term_from_value(s.get(idx), env))
+
+    if idx >= s.len() {
+        return Err(ExplorerError::Other(format!(
+            "Index {} is out of bounds for series of length {}",
+            idx,
+            s.len()
+        )));
+    }
+
+    encoding::term_from_value(s.get(idx), env)
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn s_cum_sum(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerError> {
+pub fn s_cum_sum(
+    data: ExSeries,
+    reverse: bool,
+    skip_nulls: bool,
+) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
-    Ok(ExSeries::new(s.cumsum(reverse)))
+    let cumsum = s.cumsum(reverse);
+
+    if skip_nulls {
+        Ok(ExSeries::new(cumsum))
+    } else {
+        Ok(ExSeries::new(propagate_nulls(s, cumsum, reverse)?))
+    }
 }
 
+// Once a null is seen, every following value of `out` is null as well.
+fn propagate_nulls(s: &Series, out: Series, reverse: bool) -> Result<Series, PolarsError> {
+    if s.null_count() == 0 {
+        return Ok(out);
+    }
+
+    let mut is_null: Vec<bool> = s.is_null().into_no_null_iter().collect();
+    if reverse {
+        is_null.reverse();
+    }
+
+    let mut seen_null = false;
+    let mut mask: Vec<bool> = is_null
+        .into_iter()
+        .map(|is_null| {
+            seen_null |= is_null;
+            !seen_null
+        })
+        .collect();
+    if reverse {
+        mask.reverse();
+    }
+
+    let mask = BooleanChunked::from_slice("mask", &mask);
+    let nulls = Series::full_null(s.name(), s.len(), out.dtype());
+    out.zip_with(&mask, &nulls)
+}
+
+// NaN never becomes the running max or min, its position takes the
+// running value instead.
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn s_cum_max(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerError> {
+pub fn s_cum_max(
+    data: ExSeries,
+    reverse: bool,
+    skip_nulls: bool,
+) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
-    Ok(ExSeries::new(s.cummax(reverse)))
+    let cummax = s.cummax(reverse);
+
+    if skip_nulls {
+        Ok(ExSeries::new(cummax))
+    } else {
+        Ok(ExSeries::new(propagate_nulls(s, cummax, reverse)?))
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn s_cum_min(data: ExSeries, reverse: bool) -> Result<ExSeries, ExplorerError> {
+pub fn s_cum_min(
+    data: ExSeries,
+    reverse: bool,
+    skip_nulls: bool,
+) -> Result<ExSeries, ExplorerError> {
     let s = &data.resource.0;
-    Ok(ExSeries::new(s.cummin(reverse)))
+    let cummin = s.cummin(reverse);
+
+    if skip_nulls {
+        Ok(ExSeries::new(cummin))
+    } else {
+        Ok(ExSeries::new(propagate_nulls(s, cummin, reverse)?))
+    }
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -614,12 +1069,12 @@ pub fn s_quantile<'a>(
                 Some(microseconds) => Ok(ExDateTime::from(microseconds as i64).encode(env)),
             }
         }
-        _ => Ok(encoding::term_from_value(
+        _ => encoding::term_from_value(
             s.quantile_as_series(quantile, strategy)?
                 .cast(dtype)?
                 .get(0),
             env,
-        )),
+        ),
     }
 }
 
@@ -641,6 +1096,87 @@ pub fn s_reverse(data: ExSeries) -> Result<ExSeries, ExplorerError> {
     Ok(ExSeries::new(s.reverse()))
 }
 
+// With `parallel` set to false the values are grouped on the calling
+// thread, which is cheaper for small series.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_value_counts(
+    data: ExSeries,
+    sort: bool,
+    parallel: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    let df = value_counts_frame(&data.resource.0, sort, parallel)?;
+    Ok(ExDataFrame::new(df))
+}
+
+// There is one boolean column per distinct value, named `<series>_<value>`
+// and sorted by value, plus a `<series>_null` column when nulls are included.
+// The columns depend on the values, so this is not available as an expression.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn s_to_dummies(
+    data: ExSeries,
+    drop_first: bool,
+    include_null: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    let s = &data.resource.0;
+    let strings = s.cast(&DataType::Utf8)?;
+    let strings = strings.utf8()?;
+    let categories = strings.unique()?.sort(false);
+    let skip = usize::from(drop_first);
+
+    let mut columns: Vec<Series> = categories
+        .into_iter()
+        .flatten()
+        .skip(skip)
+        .map(|category| {
+            let mask: Vec<bool> = strings
+                .into_iter()
+                .map(|value| value == Some(category))
+                .collect();
+
+            Series::new(&format!("{}_{}", s.name(), category), mask)
+        })
+        .collect();
+
+    if include_null {
+        let mask: Vec<bool> = strings.into_iter().map(|value| value.is_none()).collect();
+        columns.push(Series::new(&format!("{}_null", s.name()), mask));
+    }
+
+    Ok(ExDataFrame::new(DataFrame::new(columns)?))
+}
+
+/// Counts each distinct value, with nulls counted as a value of their own.
+/// Rows are in order of first appearance, or by descending count when
+/// sorted, in which case ties keep their order of first appearance.
+pub fn value_counts_frame(
+    s: &Series,
+    sort: bool,
+    parallel: bool,
+) -> Result<DataFrame, PolarsError> {
+    // Sorted groups are ordered by the first index of each group.
+    let groupby =
+        DataFrame::new(vec![s.clone()])?.groupby_with_series(vec![s.clone()], parallel, true)?;
+
+    let mut values = groupby.keys().remove(0);
+    values.rename("values");
+    let counts = groupby
+        .get_groups()
+        .group_lengths("counts")
+        .cast(&DataType::Int64)?;
+
+    let df = DataFrame::new(vec![values, counts.clone()])?;
+
+    if !sort {
+        return Ok(df);
+    }
+
+    // A stable sort keeps ties in their order of first appearance.
+    let counts = counts.i64()?;
+    let mut order: Vec<u32> = (0..df.height() as u32).collect();
+    order.sort_by_key(|idx| std::cmp::Reverse(counts.get(*idx as usize)));
+    df.take(&UInt32Chunked::from_vec("order", order))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn s_n_unique(data: ExSeries) -> Result<usize, ExplorerError> {
     let s = &data.resource.0;
@@ -666,10 +1202,16 @@ pub fn s_int_pow(data: ExSeries, exponent: u32) -> Result<ExSeries, ExplorerErro
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn s_cast(data: ExSeries, to_type: &str) -> Result<ExSeries, ExplorerError> {
+pub fn s_cast(data: ExSeries, to_type: &str, strict: bool) -> Result<ExSeries, ExplorerError> {
     let s: &Series = &data.resource.0;
     let dtype = cast_str_to_dtype(to_type)?;
-    Ok(ExSeries::new(s.cast(&dtype)?))
+
+    let new_s = match strict {
+        true => s.strict_cast(&dtype)?,
+        false => s.cast(&dtype)?,
+    };
+
+    Ok(ExSeries::new(new_s))
 }
 
 pub fn cast_str_to_dtype(str_type: &str) -> Result<DataType, ExplorerError> {
@@ -680,7 +1222,20 @@ pub fn cast_str_to_dtype(str_type: &str) -> Result<DataType, ExplorerError> {
         "datetime" => Ok(DataType::Datetime(TimeUnit::Microseconds, None)),
         "boolean" => Ok(DataType::Boolean),
         "string" => Ok(DataType::Utf8),
-        _ => Err(ExplorerError::Other(String::from("Cannot cast to type"))),
+        "category" => Ok(DataType::Categorical(None)),
+        "binary" => Ok(DataType::Binary),
+        "i8" => Ok(DataType::Int8),
+        "i16" => Ok(DataType::Int16),
+        "i32" => Ok(DataType::Int32),
+        "u8" => Ok(DataType::UInt8),
+        "u16" => Ok(DataType::UInt16),
+        "u32" => Ok(DataType::UInt32),
+        "u64" => Ok(DataType::UInt64),
+        "f32" => Ok(DataType::Float32),
+        _ => Err(ExplorerError::Other(format!(
+            "Cannot cast to type {}, expected one of: float, integer, date, datetime, boolean, string, category, binary, i8, i16, i32, u8, u16, u32, u64, f32",
+            str_type
+        ))),
     }
 }
 
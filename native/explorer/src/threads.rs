@@ -0,0 +1,69 @@
+// Control over the size of the polars thread pool.
+//
+// Polars runs its parallel work on a global rayon pool that is built the
+// first time it is used, sized by the POLARS_MAX_THREADS environment
+// variable. Rayon pools cannot be resized, so the size can only be chosen
+// before any parallel operation runs.
+//
+// Sort and groupby run as lazy queries, which always use the whole pool,
+// so `parallel` flags are limited to eager joins, parquet reads and value
+// counts.
+
+use once_cell::sync::Lazy;
+use polars_core::POOL;
+use std::sync::Mutex;
+
+use crate::ExplorerError;
+
+const MAX_THREADS_VAR: &str = "POLARS_MAX_THREADS";
+
+// Serializes calls to set_max_threads, so two of them can't interleave
+// their changes to the environment variable.
+static SET_MAX_THREADS: Mutex<()> = Mutex::new(());
+
+#[rustler::nif]
+pub fn max_threads() -> usize {
+    POOL.current_num_threads()
+}
+
+// Starts the pool with `n` threads. If the pool was already started with
+// a different size, nothing changes and an error explains why.
+#[rustler::nif]
+pub fn set_max_threads(n: usize) -> Result<usize, ExplorerError> {
+    if n == 0 {
+        return Err(ExplorerError::Other(
+            "the number of threads must be greater than zero".into(),
+        ));
+    }
+
+    let _guard = SET_MAX_THREADS
+        .lock()
+        .map_err(|_| ExplorerError::Internal("set_max_threads lock is poisoned".into()))?;
+
+    // The variable is only read when the pool starts, so it is left alone
+    // once the pool is running.
+    if Lazy::get(&POOL).is_none() {
+        let previous = std::env::var_os(MAX_THREADS_VAR);
+        std::env::set_var(MAX_THREADS_VAR, n.to_string());
+
+        // Another NIF may have started the pool in the meantime, in which
+        // case the variable is restored.
+        if POOL.current_num_threads() != n {
+            match previous {
+                Some(value) => std::env::set_var(MAX_THREADS_VAR, value),
+                None => std::env::remove_var(MAX_THREADS_VAR),
+            }
+        }
+    }
+
+    let current = POOL.current_num_threads();
+
+    if current == n {
+        Ok(current)
+    } else {
+        Err(ExplorerError::Other(format!(
+            "the thread pool was already started with {} threads, set_max_threads/1 must be called before any other polars operation",
+            current
+        )))
+    }
+}
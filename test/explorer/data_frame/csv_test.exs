@@ -82,6 +82,12 @@ defmodule Explorer.DataFrame.CSVTest do
       assert_csv(:float, "-1.772232", -1.772232)
     end
 
+    test "narrow numbers" do
+      assert_csv({:s, 16}, "-101", -101, dtypes: [{"column", {:s, 16}}])
+      assert_csv({:u, 8}, "200", 200, dtypes: [{"column", {:u, 8}}])
+      assert_csv({:f, 32}, "2.5", 2.5, dtypes: [{"column", {:f, 32}}])
+    end
+
     test "boolean" do
       assert_csv(:boolean, "true", true)
       assert_csv(:boolean, "false", false)
@@ -584,6 +584,69 @@ defmodule Explorer.DataFrameTest do
                i: [10, 10, 10, 10, 10, 10, 10, 10, 10, 10]
              }
     end
+
+    test "window functions with nil min_periods require a full window" do
+      df = DF.new(a: [1, 2, 3, 4, 5])
+
+      df1 =
+        DF.mutate_with(df, fn ldf ->
+          [b: Series.window_sum(ldf["a"], 3, min_periods: nil)]
+        end)
+
+      assert DF.to_columns(df1, atom_keys: true) == %{
+               a: [1, 2, 3, 4, 5],
+               b: [nil, nil, 6, 9, 12]
+             }
+    end
+
+    test "cumulative sum with and without skipping nils" do
+      df = DF.new(a: [1, nil, 3])
+
+      df1 =
+        DF.mutate_with(df, fn ldf ->
+          [
+            b: Series.cumulative_sum(ldf["a"]),
+            c: Series.cumulative_sum(ldf["a"], skip_nulls: false),
+            d: Series.cumulative_sum(ldf["a"], skip_nulls: false, reverse: true)
+          ]
+        end)
+
+      assert DF.to_columns(df1, atom_keys: true) == %{
+               a: [1, nil, 3],
+               b: [1, nil, 4],
+               c: [1, nil, nil],
+               d: [nil, nil, 3]
+             }
+    end
+
+    test "cumulative min and max with and without skipping nils" do
+      df = DF.new(a: [2.0, nil, 1.0, 3.0])
+
+      df1 =
+        DF.mutate_with(df, fn ldf ->
+          [
+            b: Series.cumulative_max(ldf["a"]),
+            c: Series.cumulative_max(ldf["a"], skip_nulls: false),
+            d: Series.cumulative_min(ldf["a"]),
+            e: Series.cumulative_min(ldf["a"], skip_nulls: false),
+            f: Series.cumulative_min(ldf["a"], skip_nulls: false, reverse: true)
+          ]
+        end)
+
+      assert DF.to_columns(df1, atom_keys: true) == %{
+               a: [2.0, nil, 1.0, 3.0],
+               b: [2.0, nil, 2.0, 3.0],
+               c: [2.0, nil, nil, nil],
+               d: [2.0, nil, 1.0, 1.0],
+               e: [2.0, nil, nil, nil],
+               f: [nil, nil, 1.0, 3.0]
+             }
+
+      s = Series.from_list([2.0, nil, 1.0, 3.0])
+
+      assert Series.to_list(Series.cumulative_max(s, skip_nulls: false)) == [2.0, nil, nil, nil]
+      assert Series.to_list(Series.cumulative_min(s)) == [2.0, nil, 1.0, 1.0]
+    end
   end
 
   test "add columns with peaks values" do
@@ -957,6 +1020,21 @@ defmodule Explorer.DataFrameTest do
              }
     end
 
+    @tag :tmp_dir
+    test "delimiter must be a single byte", config do
+      csv = tmp_csv(config.tmp_dir, "a;b\n")
+
+      for delimiter <- ["", ";;", "é", "→"] do
+        message = "expected :delimiter to be a single byte, got: #{inspect(delimiter)}"
+
+        assert_raise ArgumentError, message, fn -> DF.from_csv(csv, delimiter: delimiter) end
+
+        assert_raise ArgumentError, message, fn ->
+          DF.dump_csv(DF.new(a: [1]), delimiter: delimiter)
+        end
+      end
+    end
+
     @tag :tmp_dir
     test "dtypes", config do
       csv =
@@ -981,6 +1059,21 @@ defmodule Explorer.DataFrameTest do
              }
     end
 
+    @tag :tmp_dir
+    test "dtypes with narrow numbers", config do
+      csv =
+        tmp_csv(config.tmp_dir, """
+        a,b
+        1,2.5
+        3,4.5
+        """)
+
+      df = DF.from_csv!(csv, dtypes: %{a: {:s, 16}, b: {:f, 32}})
+
+      assert df.dtypes == %{"a" => {:s, 16}, "b" => {:f, 32}}
+      assert DF.to_columns(df, atom_keys: true) == %{a: [1, 3], b: [2.5, 4.5]}
+    end
+
     @tag :tmp_dir
     test "dtypes - parse datetime", config do
       csv =
@@ -1071,6 +1164,41 @@ defmodule Explorer.DataFrameTest do
              }
     end
 
+    @tag :tmp_dir
+    test "null_character with a list of values", config do
+      csv =
+        tmp_csv(config.tmp_dir, """
+        code;name;amount
+        007;Bond;NA
+        042;-;1,5
+        -;Moneypenny;3
+        """)
+
+      df =
+        DF.from_csv!(csv,
+          delimiter: ";",
+          null_character: ["NA", "-"],
+          dtypes: [{"code", :string}]
+        )
+
+      assert DF.to_columns(df, atom_keys: true) == %{
+               code: ["007", "042", nil],
+               name: ["Bond", nil, "Moneypenny"],
+               amount: [nil, "1,5", "3"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "unknown columns return an error", config do
+      csv =
+        tmp_csv(config.tmp_dir, """
+        a,b
+        1,2
+        """)
+
+      assert {:error, _message} = DF.from_csv(csv, columns: ["c"])
+    end
+
     @tag :tmp_dir
     test "skip_rows", config do
       csv =
@@ -1179,6 +1307,135 @@ defmodule Explorer.DataFrameTest do
     end
   end
 
+  describe "dump_csv/2 options" do
+    setup do
+      [csv_df: DF.new(a: ["x", "y,z", nil], b: [1, nil, 3])]
+    end
+
+    test "quote_style", %{csv_df: df} do
+      assert DF.dump_csv(df) == "a,b\nx,1\n\"y,z\",\n,3\n"
+      assert DF.dump_csv(df, quote_style: :always) == "\"a\",\"b\"\n\"x\",\"1\"\n\"y,z\",\"\"\n\"\",\"3\"\n"
+      assert DF.dump_csv(df, quote_style: :non_numeric) == "\"a\",\"b\"\n\"x\",1\n\"y,z\",\n,3\n"
+      assert DF.dump_csv(df, quote_style: :never) == "a,b\nx,1\ny,z,\n,3\n"
+    end
+
+    @tag :tmp_dir
+    test "compressed output is decompressed on read", %{csv_df: df, tmp_dir: tmp_dir} do
+      for compression <- [:gzip, :zstd] do
+        # The extension is deliberately misleading, detection relies on the contents.
+        path = Path.join(tmp_dir, "compressed.csv")
+
+        assert :ok = DF.to_csv(df, path, compression: compression)
+        refute File.read!(path) == DF.dump_csv(df)
+
+        assert DF.to_columns(DF.from_csv!(path)) == DF.to_columns(df)
+      end
+    end
+
+    @tag :tmp_dir
+    test "corrupt compressed input names the file", %{csv_df: df, tmp_dir: tmp_dir} do
+      for compression <- [:gzip, :zstd] do
+        path = Path.join(tmp_dir, "corrupt_#{compression}.csv")
+
+        assert :ok = DF.to_csv(df, path, compression: compression)
+        contents = File.read!(path)
+        truncated = div(byte_size(contents), 2)
+        File.write!(path, binary_part(contents, 0, truncated))
+
+        assert {:error, error} = DF.from_csv(path)
+        assert error =~ "Could not read #{compression} compressed CSV file #{path}"
+        assert error =~ "near byte #{truncated}"
+      end
+    end
+
+    test "raises on unsupported quote_style", %{csv_df: df} do
+      assert_raise ArgumentError, "unsupported :quote_style :sometimes for CSV", fn ->
+        DF.dump_csv(df, quote_style: :sometimes)
+      end
+    end
+
+    @tag :tmp_dir
+    test "round trip with delimiter, null_character and no header", %{
+      csv_df: df,
+      tmp_dir: tmp_dir
+    } do
+      path = Path.join(tmp_dir, "options.tsv")
+
+      assert :ok = DF.to_csv(df, path, delimiter: "\t", null_character: "\\N", header: false)
+      assert File.read!(path) == "x\t1\ny,z\t\\N\n\\N\t3\n"
+
+      read_df = DF.from_csv!(path, delimiter: "\t", null_character: "\\N", header: false)
+
+      assert DF.to_columns(read_df, atom_keys: true) == %{
+               column_1: ["x", "y,z", nil],
+               column_2: [1, nil, 3]
+             }
+    end
+
+    @tag :tmp_dir
+    test "writes large frames in small batches", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "large.csv")
+      n = 100_000
+
+      df =
+        DF.new(
+          a: Enum.to_list(1..n),
+          b: Enum.map(1..n, &if(rem(&1, 7) == 0, do: nil, else: "row #{&1}"))
+        )
+
+      assert :ok = DF.to_csv(df, path, batch_size: 333)
+      assert DF.to_columns(DF.from_csv!(path)) == DF.to_columns(df)
+    end
+
+    @tag :tmp_dir
+    test "appends frames to the same file", %{csv_df: df, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "appended.csv")
+
+      assert :ok = DF.to_csv(df, path, append: true)
+      assert :ok = DF.to_csv(df, path, append: true, batch_size: 1)
+
+      assert File.read!(path) == "a,b\nx,1\n\"y,z\",\n,3\nx,1\n\"y,z\",\n,3\n"
+      assert DF.to_columns(DF.from_csv!(path)) == DF.to_columns(DF.concat_rows(df, df))
+
+      assert :ok = DF.to_csv(df, path)
+      assert File.read!(path) == DF.dump_csv(df)
+    end
+
+    @tag :tmp_dir
+    test "appending checks the existing header", %{csv_df: df, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "mismatch.csv")
+
+      assert :ok = DF.to_csv(df, path)
+      renamed = DF.rename(df, b: "c")
+
+      assert {:error, error} = DF.to_csv(renamed, path, append: true)
+      assert error =~ "its header a,b does not match the header a,c of the dataframe"
+      assert File.read!(path) == DF.dump_csv(df)
+    end
+
+    @tag :tmp_dir
+    test "appends to compressed files", %{csv_df: df, tmp_dir: tmp_dir} do
+      for compression <- [:gzip, :zstd] do
+        path = Path.join(tmp_dir, "appended_#{compression}.csv")
+
+        assert :ok = DF.to_csv(df, path, compression: compression, append: true)
+        assert :ok = DF.to_csv(df, path, compression: compression, append: true)
+
+        assert {:error, error} =
+                 DF.to_csv(DF.rename(df, b: "c"), path, compression: compression, append: true)
+
+        assert error =~ "does not match the header"
+        assert DF.to_columns(DF.from_csv!(path)) == DF.to_columns(DF.concat_rows(df, df))
+      end
+    end
+
+    test "raises on invalid batch_size", %{csv_df: df} do
+      assert_raise ArgumentError, "expected :batch_size to be a positive integer, got: 0", fn ->
+        DF.to_csv(df, "unused.csv", batch_size: 0)
+      end
+    end
+  end
+
   describe "parquet read and write" do
     @tag :tmp_dir
     test "can write parquet to file", %{df: df, tmp_dir: tmp_dir} do
@@ -1228,6 +1485,274 @@ defmodule Explorer.DataFrameTest do
     end
   end
 
+  describe "partitioned parquet" do
+    @describetag :tmp_dir
+
+    test "writes hive-style directories and reads them back", %{tmp_dir: tmp_dir} do
+      df =
+        DF.new(
+          year: [2022, 2022, 2023, 2023, 2023],
+          kind: ["a/b", "c=d", "a/b", "a/b", "e"],
+          value: [1.0, 2.0, 3.0, 4.0, 5.0]
+        )
+
+      dir = Path.join(tmp_dir, "dataset")
+
+      assert :ok = DF.to_parquet(df, dir, partition_by: ["year", "kind"], max_rows_per_file: 1)
+
+      assert File.exists?(Path.join([dir, "year=2023", "kind=a%2Fb", "part-00001.parquet"]))
+      assert File.dir?(Path.join([dir, "year=2022", "kind=c%3Dd"]))
+
+      assert {:ok, from_dir} = DF.from_parquet(dir)
+      assert {:ok, from_glob} = DF.from_parquet(Path.join(dir, "**/*.parquet"))
+
+      for read_df <- [from_dir, from_glob] do
+        assert DF.dtypes(read_df) == %{"value" => :float, "year" => :integer, "kind" => :string}
+
+        assert read_df
+               |> DF.select(["year", "kind", "value"])
+               |> DF.arrange("value")
+               |> DF.to_columns() == DF.to_columns(df)
+      end
+    end
+
+    test "errors when files have different partition keys", %{tmp_dir: tmp_dir} do
+      dir = Path.join(tmp_dir, "mismatched")
+      df = DF.new(value: [1.0])
+
+      for partition <- [["year=2022", "month=1"], ["year=2023"]] do
+        path = Path.join([dir | partition])
+        File.mkdir_p!(path)
+        assert :ok = DF.to_parquet(df, Path.join(path, "part-00000.parquet"))
+      end
+
+      assert {:error, error} = DF.from_parquet(dir)
+      assert error =~ ~s/Partition keys ["year"] of/
+      assert error =~ ~s/do not match partition keys ["year", "month"]/
+    end
+  end
+
+  describe "from_parquet/2 options" do
+    @describetag :tmp_dir
+
+    setup %{tmp_dir: tmp_dir} do
+      df =
+        DF.new(
+          a: Enum.to_list(1..1000),
+          b: Enum.map(1..1000, &(&1 * 1.5)),
+          c: Enum.map(1..1000, &"row #{&1}")
+        )
+
+      path = Path.join(tmp_dir, "options.parquet")
+      :ok = DF.to_parquet(df, path)
+
+      [path: path]
+    end
+
+    test "reads only the given columns", %{path: path} do
+      assert {:ok, df} = DF.from_parquet(path, columns: ["a", "c"])
+
+      assert DF.names(df) == ["a", "c"]
+      assert DF.dtypes(df) == %{"a" => :integer, "c" => :string}
+      assert DF.n_rows(df) == 1000
+      assert Series.to_list(df["c"]) |> Enum.take(2) == ["row 1", "row 2"]
+    end
+
+    test "reads up to max_rows", %{path: path} do
+      assert {:ok, df} = DF.from_parquet(path, max_rows: 10)
+
+      assert DF.n_rows(df) == 10
+      assert Series.to_list(df["a"]) == Enum.to_list(1..10)
+    end
+
+    test "adds an offsetted row index", %{path: path} do
+      assert {:ok, df} = DF.from_parquet(path, row_index: {"index", 5}, max_rows: 3)
+
+      assert DF.names(df) == ["index", "a", "b", "c"]
+      assert Series.to_list(df["index"]) == [5, 6, 7]
+    end
+
+    test "errors listing the available columns", %{path: path} do
+      assert {:error, message} = DF.from_parquet(path, columns: ["a", "z"])
+
+      assert message =~ "not found in parquet file"
+      assert message =~ "available columns are"
+    end
+  end
+
+  describe "dump and load binaries" do
+    setup do
+      df =
+        DF.new(
+          integer: [1, nil, 3],
+          float: [1.5, 2.0, nil],
+          boolean: [true, nil, false],
+          string: ["a", "b", nil],
+          date: [~D[2022-01-01], nil, ~D[1970-01-01]],
+          datetime: [~N[2022-01-01 10:00:00.123456], nil, ~N[1969-12-31 23:59:59.000001]]
+        )
+
+      [all_dtypes_df: df]
+    end
+
+    test "dump_parquet/2 and load_parquet/2 round trip", %{all_dtypes_df: df} do
+      for compression <- [nil, :snappy, {:zstd, 3}] do
+        assert {:ok, binary} = DF.dump_parquet(df, compression: compression)
+        assert {:ok, loaded_df} = DF.load_parquet(binary)
+
+        assert DF.dtypes(loaded_df) == DF.dtypes(df)
+        assert DF.to_columns(loaded_df) == DF.to_columns(df)
+      end
+    end
+
+    test "dump_ipc/2 and load_ipc/2 round trip", %{all_dtypes_df: df} do
+      for compression <- [nil, :lz4, :zstd] do
+        assert {:ok, binary} = DF.dump_ipc(df, compression: compression)
+        assert {:ok, loaded_df} = DF.load_ipc(binary)
+
+        assert DF.dtypes(loaded_df) == DF.dtypes(df)
+        assert DF.to_columns(loaded_df) == DF.to_columns(df)
+      end
+    end
+
+    @tag :tmp_dir
+    test "dumped binaries match the written files", %{all_dtypes_df: df, tmp_dir: tmp_dir} do
+      parquet_path = Path.join(tmp_dir, "test.parquet")
+      ipc_path = Path.join(tmp_dir, "test.ipc")
+
+      assert :ok = DF.to_parquet(df, parquet_path, compression: :snappy)
+      assert :ok = DF.to_ipc(df, ipc_path)
+
+      assert DF.dump_parquet(df, compression: :snappy) == {:ok, File.read!(parquet_path)}
+      assert DF.dump_ipc(df) == {:ok, File.read!(ipc_path)}
+    end
+
+    test "loading corrupt binaries returns an error", %{all_dtypes_df: df} do
+      {:ok, parquet} = DF.dump_parquet(df)
+      {:ok, ipc} = DF.dump_ipc(df)
+
+      assert {:error, _} = DF.load_parquet(binary_part(parquet, 0, div(byte_size(parquet), 2)))
+      assert {:error, _} = DF.load_ipc(binary_part(ipc, 0, div(byte_size(ipc), 2)))
+      assert {:error, _} = DF.load_parquet("not a parquet file")
+    end
+  end
+
+  describe "parquet compression" do
+    @tag :tmp_dir
+    test "zstd output is smaller than uncompressed output", %{tmp_dir: tmp_dir} do
+      df = DF.new(a: List.duplicate("explorer", 10_000), b: Enum.map(1..10_000, &rem(&1, 10)))
+
+      uncompressed_path = Path.join(tmp_dir, "uncompressed.parquet")
+      zstd_path = Path.join(tmp_dir, "zstd.parquet")
+
+      assert :ok = DF.to_parquet(df, uncompressed_path)
+      assert :ok = DF.to_parquet(df, zstd_path, compression: {:zstd, 3})
+
+      assert File.stat!(zstd_path).size < File.stat!(uncompressed_path).size
+    end
+
+    @tag :tmp_dir
+    test "round trips every codec at its level bounds", %{tmp_dir: tmp_dir} do
+      df = DF.new(a: ["x", nil, "z"], b: [1.5, 2.5, nil])
+
+      for compression <- [
+            :snappy,
+            :lz4raw,
+            {:gzip, 1},
+            {:gzip, 9},
+            {:brotli, 1},
+            {:brotli, 11},
+            {:zstd, -7},
+            {:zstd, 22}
+          ] do
+        path = Path.join(tmp_dir, "codec.parquet")
+
+        assert :ok = DF.to_parquet(df, path, compression: compression)
+        assert {:ok, read} = DF.from_parquet(path)
+        assert DF.to_columns(read) == DF.to_columns(df)
+      end
+    end
+
+    @tag :tmp_dir
+    test "returns errors for invalid levels and codecs", %{tmp_dir: tmp_dir} do
+      df = DF.new(a: [1, 2, 3])
+      path = Path.join(tmp_dir, "invalid.parquet")
+
+      assert {:error, error} = DF.to_parquet(df, path, compression: {:gzip, 10})
+      assert error =~ "gzip compression level must be between 1 and 9 inclusive, got 10"
+
+      assert {:error, error} = DF.to_parquet(df, path, compression: {:zstd, -8})
+      assert error =~ "zstd compression level must be between -7 and 22 inclusive, got -8"
+
+      assert {:error, error} = DF.to_parquet(df, path, compression: {:snappy, 3})
+      assert error =~ "snappy compression does not accept a compression level"
+
+      assert {:error, error} = DF.to_parquet(df, path, compression: :lzo)
+      assert error =~ "Parquet compression lzo not supported"
+
+      assert {:error, error} = DF.dump_parquet(df, compression: {:brotli, 12})
+      assert error =~ "brotli compression level must be between 1 and 11 inclusive, got 12"
+    end
+  end
+
+  describe "ipc read and write" do
+    setup do
+      df =
+        DF.new(
+          a: [1, nil, 3],
+          b: [~D[2022-01-01], nil, ~D[1970-01-01]],
+          c: [~N[2022-01-01 10:00:00.123456], ~N[1969-12-31 23:59:59.000001], nil],
+          d: ["a", nil, "c"]
+        )
+
+      [dated_df: df]
+    end
+
+    @tag :tmp_dir
+    test "can write ipc to file and read it back", %{dated_df: df, tmp_dir: tmp_dir} do
+      for compression <- [nil, :lz4, :zstd] do
+        ipc_path = Path.join(tmp_dir, "test.ipc")
+
+        assert :ok = DF.to_ipc(df, ipc_path, compression: compression)
+        assert {:ok, ipc_df} = DF.from_ipc(ipc_path)
+
+        assert DF.dtypes(df) == DF.dtypes(ipc_df)
+        assert DF.to_columns(df) == DF.to_columns(ipc_df)
+      end
+    end
+
+    @tag :tmp_dir
+    test "can write ipc stream to file and read it back", %{dated_df: df, tmp_dir: tmp_dir} do
+      for compression <- [nil, :lz4, :zstd] do
+        ipc_path = Path.join(tmp_dir, "test.arrows")
+
+        assert :ok = DF.to_ipc_stream(df, ipc_path, compression: compression)
+        assert {:ok, ipc_df} = DF.from_ipc_stream(ipc_path)
+
+        assert DF.dtypes(df) == DF.dtypes(ipc_df)
+        assert DF.to_columns(df) == DF.to_columns(ipc_df)
+      end
+    end
+
+    @tag :tmp_dir
+    test "reads only the selected columns", %{dated_df: df, tmp_dir: tmp_dir} do
+      ipc_path = Path.join(tmp_dir, "test.ipc")
+      stream_path = Path.join(tmp_dir, "test.arrows")
+
+      assert :ok = DF.to_ipc(df, ipc_path)
+      assert :ok = DF.to_ipc_stream(df, stream_path)
+
+      assert DF.names(DF.from_ipc!(ipc_path, columns: ["b", "d"])) == ["b", "d"]
+      assert DF.names(DF.from_ipc_stream!(stream_path, columns: [0, 2])) == ["a", "c"]
+    end
+
+    test "raises on unsupported compression", %{dated_df: df} do
+      assert_raise ArgumentError, "unsupported :compression :gzip for IPC", fn ->
+        DF.to_ipc_stream(df, "unused.arrows", compression: :gzip)
+      end
+    end
+  end
+
   describe "from_ndjson/2" do
     @tag :tmp_dir
     test "reads from file with default options", %{tmp_dir: tmp_dir} do
@@ -1286,6 +1811,48 @@ defmodule Explorer.DataFrameTest do
     end
   end
 
+  describe "ndjson round trip" do
+    @tag :tmp_dir
+    test "preserves nulls, unicode and mixed numbers", %{tmp_dir: tmp_dir} do
+      ndjson_path = Path.join(tmp_dir, "mixed.ndjson")
+
+      File.write!(ndjson_path, """
+      {"a":1,"b":"café","c":null}
+      {"a":2.5,"b":null,"c":true}
+      {"a":null,"b":"日本","c":false}
+      """)
+
+      assert {:ok, df} = DF.from_ndjson(ndjson_path)
+      assert DF.dtypes(df) == %{"a" => :float, "b" => :string, "c" => :boolean}
+      assert DF.to_columns(df, atom_keys: true) == %{
+               a: [1.0, 2.5, nil],
+               b: ["café", nil, "日本"],
+               c: [nil, true, false]
+             }
+
+      round_trip_path = Path.join(tmp_dir, "round_trip.ndjson")
+      assert :ok = DF.to_ndjson(df, round_trip_path)
+      assert {:ok, round_trip_df} = DF.from_ndjson(round_trip_path)
+      assert DF.to_columns(round_trip_df) == DF.to_columns(df)
+    end
+
+    @tag :tmp_dir
+    test "reports the malformed line", %{tmp_dir: tmp_dir} do
+      ndjson_path = Path.join(tmp_dir, "malformed.ndjson")
+
+      File.write!(ndjson_path, """
+      {"a":1}
+      {"a":2
+      {"a":3}
+      """)
+
+      assert {:error, message} = DF.from_ndjson(ndjson_path)
+      assert message =~ "malformed JSON on line 2"
+      # The polars error comes first and is kept as is.
+      refute message =~ ~r/^"?malformed/
+    end
+  end
+
   describe "to_ndjson" do
     @tag :tmp_dir
     test "writes to a file", %{tmp_dir: tmp_dir} do
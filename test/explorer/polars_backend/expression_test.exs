@@ -3,15 +3,12 @@ defmodule Explorer.PolarsBackend.ExpressionTest do
 
   alias Explorer.Backend.LazySeries
   alias Explorer.PolarsBackend.Expression
+  alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
 
   describe "to_expr/1" do
-    setup do
+    test "with basic int value" do
       df = Explorer.DataFrame.new(col_a: [1, 2, 3, 4, 5], col_b: [1.0, 2.4, 3.1, 1.4, 5.1])
-
-      [df: df]
-    end
-
-    test "with basic int value", %{df: df} do
       lazy = %LazySeries{op: :eq, args: [%LazySeries{op: :column, args: ["col_a"]}, 5]}
 
       assert %Expression{} = expr = Expression.to_expr(lazy)
@@ -58,7 +55,9 @@ defmodule Explorer.PolarsBackend.ExpressionTest do
       assert %Expression{} = Expression.to_expr(lazy)
     end
 
-    test "with another column", %{df: df} do
+    test "with another column" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 3, 4, 5], col_b: [1.0, 2.4, 3.1, 1.4, 5.1])
+
       lazy = %LazySeries{
         op: :eq,
         args: [
@@ -75,4 +74,1537 @@ defmodule Explorer.PolarsBackend.ExpressionTest do
              """
     end
   end
+
+  describe "plan inspection" do
+    test "expr_to_string/1 renders the expression tree" do
+      expr = Native.expr_eq(Native.expr_column("col_a"), Native.expr_integer(5))
+
+      assert Native.expr_to_string(expr) == ~s/[(col("col_a")) == (5i64)]/
+    end
+
+    test "expr_describe_filter_plan_optimized/2 pushes the predicate down" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 3], col_b: [1.0, 2.4, 3.1])
+      expr = Native.expr_eq(Native.expr_column("col_a"), Native.expr_integer(5))
+
+      assert {:ok, plan} = Native.expr_describe_filter_plan_optimized(df.data, expr)
+      refute plan =~ "FILTER"
+      assert plan =~ ~s/col("col_a")/
+    end
+
+    test "df_describe_plan/4 describes select, with_columns and groupby plans" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 3], col_b: [1.0, 2.4, 3.1])
+      exprs = [Native.expr_column("col_a")]
+
+      assert {:ok, plan} = Native.df_describe_plan(df.data, exprs, "select", false)
+      assert plan =~ "PROJECT */2 COLUMNS"
+
+      assert {:ok, plan} = Native.df_describe_plan(df.data, exprs, "select", true)
+      assert plan =~ "PROJECT 1/2 COLUMNS"
+
+      assert {:ok, plan} = Native.df_describe_plan(df.data, exprs, "with_columns", false)
+      assert plan =~ "WITH_COLUMNS"
+
+      assert {:ok, plan} = Native.df_describe_plan(df.data, exprs, "groupby", false)
+      assert plan =~ "Aggregate"
+
+      assert {:error, error} = Native.df_describe_plan(df.data, [], "explode", false)
+      assert error =~ "not supported"
+    end
+  end
+
+  describe "expr_sample_n/5" do
+    test "is deterministic with the same seed" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], col_a: [1, 10, 100, 1000, 10000])
+      sample = Native.expr_sample_n(Native.expr_column("col_a"), 3, false, true, 42)
+
+      assert {:ok, %{"col_a" => [sum]}} = agg(df, [Native.expr_sum(sample)])
+      assert agg(df, [Native.expr_sum(sample)]) == {:ok, %{"group" => [1], "col_a" => [sum]}}
+    end
+
+    test "samples more than the length only with replacement" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], col_a: [1, 10, 100, 1000, 10000])
+      with_replacement = Native.expr_sample_n(Native.expr_column("col_a"), 10, true, true, 42)
+      without = Native.expr_sample_n(Native.expr_column("col_a"), 10, false, true, 42)
+
+      assert {:ok, %{"col_a" => [_sum]}} = agg(df, [Native.expr_sum(with_replacement)])
+      assert {:error, _} = agg(df, [Native.expr_sum(without)])
+    end
+  end
+
+  test "expr_shuffle/2 preserves the values and is deterministic with the same seed" do
+    df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], col_a: [1, 10, 100, 1000, 10000])
+    shuffled = Native.expr_shuffle(Native.expr_column("col_a"), 42)
+
+    exprs = [
+      Native.expr_alias(Native.expr_sum(shuffled), "sum"),
+      Native.expr_alias(Native.expr_count(shuffled), "count"),
+      Native.expr_alias(Native.expr_first(shuffled), "first")
+    ]
+
+    assert {:ok, %{"sum" => [11111], "count" => [5]} = columns} = agg(df, exprs)
+    assert agg(df, exprs) == {:ok, columns}
+  end
+
+  describe "expr_window_skew/4" do
+    test "computes the biased and unbiased skew over full windows" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 4, 8, 16])
+
+      assert [nil, nil | skews] =
+               eval(df, Native.expr_window_skew(Native.expr_column("col_a"), 3, nil, true))
+
+      for skew <- skews do
+        assert_in_delta skew, 0.381801774, 1.0e-6
+      end
+
+      assert [nil, nil, skew | _] =
+               eval(df, Native.expr_window_skew(Native.expr_column("col_a"), 3, nil, false))
+
+      assert_in_delta skew, 0.935219529, 1.0e-6
+    end
+
+    test "respects min_periods" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 4, 8, 16])
+
+      assert [nil, skew | _] =
+               eval(df, Native.expr_window_skew(Native.expr_column("col_a"), 3, 2, true))
+
+      assert_in_delta skew, 0.0, 1.0e-9
+    end
+
+    test "returns nil for constant windows" do
+      df = Explorer.DataFrame.new(col_a: [5, 5, 5, 5, 1])
+
+      assert [nil, nil, nil, nil, skew] =
+               eval(df, Native.expr_window_skew(Native.expr_column("col_a"), 3, nil, true))
+
+      assert_in_delta skew, -0.707106781, 1.0e-6
+    end
+
+    test "skips nils inside windows" do
+      df = Explorer.DataFrame.new(col_a: [1, nil, 2, 4, 8])
+
+      assert [nil, nil, two_values, two_values_after_nil, skew] =
+               eval(df, Native.expr_window_skew(Native.expr_column("col_a"), 3, 2, true))
+
+      assert_in_delta two_values, 0.0, 1.0e-9
+      assert_in_delta two_values_after_nil, 0.0, 1.0e-9
+      assert_in_delta skew, 0.381801774, 1.0e-6
+    end
+  end
+
+  test "expr_dot/2 computes the dot product of two columns" do
+    df = Explorer.DataFrame.new(group: [1, 1, 1], col_a: [1, 2, 3], col_b: [4, 5, 6])
+    dot = Native.expr_dot(Native.expr_column("col_a"), Native.expr_column("col_b"))
+
+    assert agg(df, [Native.expr_alias(dot, "dot")]) == {:ok, %{"group" => [1], "dot" => [32]}}
+  end
+
+  test "expr_abs_diff/2 computes the absolute difference of two columns" do
+    df = Explorer.DataFrame.new(col_a: [1, 5, nil], col_b: [4, 2, 3])
+
+    abs_diff = Native.expr_abs_diff(Native.expr_column("col_a"), Native.expr_column("col_b"))
+
+    assert eval(df, abs_diff) == [3, 3, nil]
+  end
+
+  describe "expr_clip_to_dtype_range/2" do
+    test "saturates values outside of the target range" do
+      df = Explorer.DataFrame.new(col_a: [1_000_000, -1_000_000, 5, nil])
+
+      assert {:ok, i8} = Native.expr_clip_to_dtype_range(Native.expr_column("col_a"), "i8")
+      assert {:ok, u8} = Native.expr_clip_to_dtype_range(Native.expr_column("col_a"), "u8")
+
+      assert eval(df, i8) == [127, -128, 5, nil]
+      assert eval(df, u8) == [255, 0, 5, nil]
+    end
+
+    test "errors on invalid dtypes" do
+      column = Native.expr_column("col_a")
+
+      assert {:error, error} = Native.expr_clip_to_dtype_range(column, "i128")
+      assert error =~ "Cannot cast to type i128"
+
+      assert {:error, error} = Native.expr_clip_to_dtype_range(column, "string")
+      assert error =~ "non-numeric"
+    end
+  end
+
+  describe "expr_round_to_multiple/2" do
+    test "snaps values to the nearest multiple of the step" do
+      df = Explorer.DataFrame.new(col_a: [7, 8, nil])
+
+      assert {:ok, expr} = Native.expr_round_to_multiple(Native.expr_column("col_a"), 5.0)
+      assert eval(df, expr) == [5.0, 10.0, nil]
+    end
+
+    test "errors on a zero step" do
+      assert {:error, error} = Native.expr_round_to_multiple(Native.expr_column("col_a"), 0.0)
+      assert error =~ "multiple of zero"
+    end
+  end
+
+  describe "expr_map_dict/4" do
+    test "remaps values and falls back to the default" do
+      df = Explorer.DataFrame.new(code: [1, 2, 3, nil, 2])
+      keys = [Native.expr_integer(1), Native.expr_integer(2)]
+      values = [Native.expr_string("low"), Native.expr_string("high")]
+      default = Native.expr_string("unknown")
+
+      assert {:ok, expr} =
+               Native.expr_map_dict(Native.expr_column("code"), keys, values, default)
+
+      assert eval(df, expr) == ["low", "high", "unknown", "unknown", "high"]
+    end
+
+    test "errors when keys and values have different lengths" do
+      keys = [Native.expr_integer(1), Native.expr_integer(2)]
+      values = [Native.expr_string("low")]
+      default = Native.expr_string("unknown")
+
+      assert {:error, error} =
+               Native.expr_map_dict(Native.expr_column("code"), keys, values, default)
+
+      assert error =~ "same length"
+    end
+  end
+
+  describe "expr_replace_time_zone/2 and expr_convert_time_zone/2" do
+    test "assigns a time zone and converts to another one" do
+      df = Explorer.DataFrame.new(ts: [~N[2022-01-01 12:00:00.000000], nil])
+
+      {:ok, expr} = Native.expr_replace_time_zone(Native.expr_column("ts"), "UTC")
+      {:ok, utc} = Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "utc")])
+
+      {:ok, expr} = Native.expr_convert_time_zone(Native.expr_column("utc"), "America/New_York")
+      {:ok, polars_df} = Native.df_with_column_exprs(utc, [Native.expr_alias(expr, "ny")])
+
+      assert Native.df_dtypes(polars_df) ==
+               {:ok, ["datetime[μs]", "datetime[μs, UTC]", "datetime[μs, America/New_York]"]}
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() == %{
+               "ts" => [~N[2022-01-01 12:00:00.000000], nil],
+               "utc" => [~N[2022-01-01 12:00:00.000000], nil],
+               "ny" => [~N[2022-01-01 07:00:00.000000], nil]
+             }
+    end
+
+    test "replacing keeps the wall-clock time of datetimes with a time zone" do
+      df = Explorer.DataFrame.new(ts: [~N[2022-01-01 12:00:00.000000], nil])
+
+      {:ok, utc} = Native.expr_replace_time_zone(Native.expr_column("ts"), "UTC")
+      {:ok, expr} = Native.expr_replace_time_zone(utc, "America/New_York")
+
+      assert eval(df, expr) == [~N[2022-01-01 12:00:00.000000], nil]
+    end
+
+    test "replacing errors on wall-clock times skipped or repeated by DST" do
+      skipped = Explorer.DataFrame.new(ts: [~N[2022-03-13 02:30:00.000000]])
+      repeated = Explorer.DataFrame.new(ts: [~N[2022-11-06 01:30:00.000000]])
+      {:ok, expr} = Native.expr_replace_time_zone(Native.expr_column("ts"), "America/New_York")
+
+      assert {:error, error} = eval(skipped, expr)
+      assert error =~ "2022-03-13 02:30:00 does not exist in time zone America/New_York"
+
+      assert {:error, error} = eval(repeated, expr)
+      assert error =~ "2022-11-06 01:30:00 is ambiguous in time zone America/New_York"
+    end
+
+    test "errors on invalid time zones" do
+      column = Native.expr_column("ts")
+
+      assert {:error, error} = Native.expr_replace_time_zone(column, "Mars/Olympus_Mons")
+      assert error =~ "Invalid time zone"
+
+      assert {:error, error} = Native.expr_convert_time_zone(column, "Mars/Olympus_Mons")
+      assert error =~ "Invalid time zone"
+    end
+  end
+
+  describe "expr_timestamp/2" do
+    test "returns the epoch in the given time unit" do
+      df = Explorer.DataFrame.new(ts: [~N[2022-01-01 00:00:01.500000], nil])
+
+      for {unit, epoch} <- [
+            {"ms", 1_640_995_201_500},
+            {"us", 1_640_995_201_500_000},
+            {"ns", 1_640_995_201_500_000_000}
+          ] do
+        assert {:ok, expr} = Native.expr_timestamp(Native.expr_column("ts"), unit)
+        assert eval(df, expr) == [epoch, nil]
+      end
+    end
+
+    test "errors on invalid time units" do
+      assert {:error, error} = Native.expr_timestamp(Native.expr_column("ts"), "weeks")
+      assert error =~ "Invalid time unit"
+    end
+  end
+
+  describe "expr_round_datetime/2" do
+    test "rounds to the nearest quarter hour" do
+      df =
+        Explorer.DataFrame.new(
+          ts: [
+            ~N[2022-01-01 13:52:00.000000],
+            ~N[2022-01-01 13:53:00.000000],
+            ~N[2022-01-01 13:37:30.000000],
+            ~N[2022-01-01 13:00:00.000000],
+            nil
+          ]
+        )
+
+      assert {:ok, expr} = Native.expr_round_datetime(Native.expr_column("ts"), "15m")
+
+      assert eval(df, expr) == [
+               ~N[2022-01-01 13:45:00.000000],
+               ~N[2022-01-01 14:00:00.000000],
+               ~N[2022-01-01 13:45:00.000000],
+               ~N[2022-01-01 13:00:00.000000],
+               nil
+             ]
+    end
+
+    test "errors on invalid intervals" do
+      assert {:error, error} = Native.expr_round_datetime(Native.expr_column("ts"), "1mo")
+      assert error =~ "Invalid interval"
+    end
+  end
+
+  test "expr_is_between_datetime/4 flags datetimes between two timestamps" do
+    df =
+      Explorer.DataFrame.new(
+        at: [
+          ~N[2022-01-01 09:00:00.000000],
+          ~N[2022-01-01 10:00:00.000000],
+          ~N[2022-01-01 11:30:00.000000],
+          ~N[2022-01-01 12:00:00.000000],
+          nil
+        ]
+      )
+
+    from = ~N[2022-01-01 10:00:00.000000]
+    to = ~N[2022-01-01 12:00:00.000000]
+    inclusive = Native.expr_is_between_datetime(Native.expr_column("at"), from, to, true)
+    exclusive = Native.expr_is_between_datetime(Native.expr_column("at"), from, to, false)
+
+    assert eval(df, inclusive) == [false, true, true, true, nil]
+    assert eval(df, exclusive) == [false, false, true, false, nil]
+  end
+
+  describe "duration totals" do
+    test "returns the total of each unit" do
+      df =
+        Explorer.DataFrame.new(
+          start: [~N[2022-01-01 12:00:00.000000], ~N[2022-01-01 12:00:00.000000], nil],
+          stop: [~N[2022-01-01 13:30:00.000000], ~N[2022-01-03 12:00:00.500000], nil]
+        )
+
+      duration = Native.expr_subtract(Native.expr_column("stop"), Native.expr_column("start"))
+
+      assert eval(df, Native.expr_total_seconds(duration)) == [5400, 172_800, nil]
+      assert eval(df, Native.expr_total_milliseconds(duration)) == [5_400_000, 172_800_500, nil]
+      assert eval(df, Native.expr_total_days(duration)) == [0, 2, nil]
+    end
+
+    test "errors on non-duration columns" do
+      df = Explorer.DataFrame.new(start: [~N[2022-01-01 12:00:00.000000]])
+
+      assert {:error, error} = eval(df, Native.expr_total_seconds(Native.expr_column("start")))
+      assert error =~ "total_seconds expects a duration"
+    end
+  end
+
+  describe "list expressions" do
+    test "decodes list columns with empty and null lists" do
+      df = list_df("tags", [["a", "b", "c"], [], nil, ["b"]])
+
+      assert df.dtypes == %{"tags" => {:list, :string}}
+
+      assert Explorer.DataFrame.pull(df, "tags") |> Explorer.Series.to_list() ==
+               [["a", "b", "c"], [], nil, ["b"]]
+    end
+
+    test "expr_list_lengths/1" do
+      df = list_df("tags", [["a", "b", "c"], [], nil, ["b"]])
+      lengths = Native.expr_list_lengths(Native.expr_column("tags"))
+
+      assert eval(df, lengths) == [3, 0, nil, 1]
+
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [Native.expr_alias(lengths, "out")])
+      assert Native.df_dtypes(polars_df) == {:ok, ["list[str]", "i64"]}
+    end
+
+    test "expr_list_get/2 supports negative indexes" do
+      df = list_df("tags", [["a", "b", "c"], [], nil, ["b"]])
+      tags = Native.expr_column("tags")
+
+      assert eval(df, Native.expr_list_get(tags, 0)) == ["a", nil, nil, "b"]
+      assert eval(df, Native.expr_list_get(tags, -1)) == ["c", nil, nil, "b"]
+      assert eval(df, Native.expr_list_get(tags, 5)) == [nil, nil, nil, nil]
+    end
+
+    test "expr_list_join/2" do
+      df = list_df("tags", [["a", "b", "c"], [], nil, ["b"]])
+
+      assert eval(df, Native.expr_list_join(Native.expr_column("tags"), "-")) ==
+               ["a-b-c", "", nil, "b"]
+    end
+
+    test "expr_list_contains/2" do
+      df = list_df("tags", [["a", "b", "c"], [], nil, ["b"]])
+      expr = Native.expr_list_contains(Native.expr_column("tags"), Native.expr_string("b"))
+
+      assert eval(df, expr) == [true, false, nil, true]
+    end
+  end
+
+  describe "uniqueness and nil counts" do
+    test "expr_is_duplicated/1 and expr_is_unique/1 treat nils as a value" do
+      df = Explorer.DataFrame.new(value: [1, 2, 1, nil, nil])
+      value = Native.expr_column("value")
+
+      assert eval(df, Native.expr_is_duplicated(value)) == [true, false, true, true, true]
+      assert eval(df, Native.expr_is_unique(value)) == [false, true, false, false, false]
+    end
+
+    test "expr_is_duplicated/1 and expr_is_unique/1 are complementary on strings" do
+      df = Explorer.DataFrame.new(value: ["a", "b", "a", nil, "c"])
+      value = Native.expr_column("value")
+
+      assert eval(df, Native.expr_is_duplicated(value)) == [true, false, true, false, false]
+      assert eval(df, Native.expr_is_unique(value)) == [false, true, false, true, true]
+    end
+
+    test "expr_nil_count/1 counts nils per group" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 2, 2], value: [1, 2, 1, nil, nil])
+      expr = Native.expr_alias(Native.expr_nil_count(Native.expr_column("value")), "nils")
+
+      assert agg(df, [expr]) == {:ok, %{"group" => [1, 2], "nils" => [0, 2]}}
+    end
+  end
+
+  describe "string slicing and reversing" do
+    test "takes the first and last n characters" do
+      df = Explorer.DataFrame.new(text: ["abcdef", "ab", "çãoé", nil])
+      text = Native.expr_column("text")
+
+      assert eval(df, Native.expr_str_head(text, 3)) == ["abc", "ab", "ção", nil]
+      assert eval(df, Native.expr_str_tail(text, 2)) == ["ef", "ab", "oé", nil]
+    end
+
+    test "takes whole strings shorter than n" do
+      df = Explorer.DataFrame.new(text: ["abcdef", "ab", "çãoé", nil])
+      text = Native.expr_column("text")
+
+      assert eval(df, Native.expr_str_head(text, 5)) == ["abcde", "ab", "çãoé", nil]
+      assert eval(df, Native.expr_str_tail(text, 5)) == ["bcdef", "ab", "çãoé", nil]
+    end
+
+    test "takes no characters when n is zero" do
+      df = Explorer.DataFrame.new(text: ["abcdef", "ab", "çãoé", nil])
+      text = Native.expr_column("text")
+
+      assert eval(df, Native.expr_str_head(text, 0)) == ["", "", "", nil]
+      assert eval(df, Native.expr_str_tail(text, 0)) == ["", "", "", nil]
+    end
+
+    test "expr_str_reverse/1" do
+      df = Explorer.DataFrame.new(text: ["abc", "ação", "", nil])
+
+      assert eval(df, Native.expr_str_reverse(Native.expr_column("text"))) ==
+               ["cba", "oãça", "", nil]
+    end
+  end
+
+  describe "expr_peak_max/2 and expr_peak_min/2" do
+    test "global mode marks the positions of the column max and min" do
+      {:ok, max} = Native.expr_peak_max(Native.expr_column("value"), "global")
+      {:ok, min} = Native.expr_peak_min(Native.expr_column("value"), "global")
+      df = Explorer.DataFrame.new(value: [1, 2, 4, 1, 4])
+
+      assert eval(df, max) == [false, false, true, false, true]
+      assert eval(df, min) == [true, false, false, true, false]
+      assert eval(Explorer.DataFrame.new(value: [2.5, -1.0, 2.5]), min) == [false, true, false]
+    end
+
+    test "local mode flags strict local extrema" do
+      {:ok, max} = Native.expr_peak_max(Native.expr_column("value"), "local")
+      {:ok, min} = Native.expr_peak_min(Native.expr_column("value"), "local")
+      df = Explorer.DataFrame.new(value: [1, 3, 2, 5, 4])
+
+      assert eval(df, max) == [false, true, false, true, false]
+      assert eval(df, min) == [false, false, true, false, false]
+    end
+
+    test "local mode never flags endpoints, plateaus or values next to nils" do
+      {:ok, max} = Native.expr_peak_max(Native.expr_column("value"), "local")
+
+      assert eval(Explorer.DataFrame.new(value: [5, 1, 5]), max) == [false, false, false]
+
+      assert eval(Explorer.DataFrame.new(value: [1, 3, 3, 1]), max) ==
+               [false, false, false, false]
+
+      assert eval(Explorer.DataFrame.new(value: [1, 3, nil, 3, 1]), max) ==
+               [false, false, false, false, false]
+    end
+
+    test "flat and monotonic series have no local peaks" do
+      {:ok, max} = Native.expr_peak_max(Native.expr_column("value"), "local")
+      {:ok, min} = Native.expr_peak_min(Native.expr_column("value"), "local")
+      flat = Explorer.DataFrame.new(value: [2, 2, 2, 2])
+      increasing = Explorer.DataFrame.new(value: [1, 2, 3, 4])
+
+      assert eval(flat, max) == [false, false, false, false]
+      assert eval(increasing, max) == [false, false, false, false]
+      assert eval(increasing, min) == [false, false, false, false]
+    end
+
+    test "errors on invalid modes" do
+      assert {:error, error} = Native.expr_peak_max(Native.expr_column("value"), "nearby")
+      assert error =~ "Invalid peak mode"
+    end
+  end
+
+  test "expr_str_count_matches/3 counts regex and literal matches" do
+    df = Explorer.DataFrame.new(text: ["the quick brown fox", "rhythm", "a.b.c", nil])
+    text = Native.expr_column("text")
+
+    assert eval(df, Native.expr_str_count_matches(text, "[aeiou]", false)) == [5, 0, 1, nil]
+    assert eval(df, Native.expr_str_count_matches(text, ".", true)) == [0, 0, 2, nil]
+    assert eval(df, Native.expr_str_count_matches(text, ".", false)) == [19, 6, 5, nil]
+  end
+
+  test "expr_extract_all/2 collects every match per row" do
+    df = Explorer.DataFrame.new(text: ["a1b22c333", "abc", nil])
+
+    assert eval(df, Native.expr_extract_all(Native.expr_column("text"), "[0-9]+")) ==
+             [["1", "22", "333"], [], nil]
+  end
+
+  describe "expr_str_json_path_match/2" do
+    test "extracts fields by path" do
+      df =
+        Explorer.DataFrame.new(
+          json: [
+            ~s({"name": "ana", "tags": ["a", "b"], "age": 30}),
+            ~s({"name": "bob"}),
+            ~s({"age": 41}),
+            "not json",
+            nil
+          ]
+        )
+
+      for {path, expected} <- [
+            {"$.name", ["ana", "bob", nil, nil, nil]},
+            {"$.tags[1]", ["b", nil, nil, nil, nil]},
+            {"$['age']", ["30", nil, "41", nil, nil]},
+            {"$.tags", [~s(["a","b"]), nil, nil, nil, nil]}
+          ] do
+        assert {:ok, expr} = Native.expr_str_json_path_match(Native.expr_column("json"), path)
+        assert eval(df, expr) == expected
+      end
+    end
+
+    test "errors on invalid paths" do
+      assert {:error, error} = Native.expr_str_json_path_match(Native.expr_column("json"), "name")
+      assert error =~ "Invalid JSON path"
+    end
+  end
+
+  describe "expr_str_starts_with/2 and expr_str_ends_with/2" do
+    test "filters filenames by suffix" do
+      df = Explorer.DataFrame.new(file: ["data.csv", "data.csv.gz", "notes.txt", "a.csv", nil])
+      ends_with = Native.expr_str_ends_with(Native.expr_column("file"), ".csv")
+      {:ok, filtered} = Native.df_filter_with(df.data, ends_with, [])
+
+      assert filtered |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"file" => ["data.csv", "a.csv"]}
+    end
+
+    test "matches literally and keeps nils" do
+      df = Explorer.DataFrame.new(file: ["data.csv", "data.csv.gz", "notes.txt", "a.csv", nil])
+
+      assert eval(df, Native.expr_str_starts_with(Native.expr_column("file"), "a.")) ==
+               [false, false, false, true, nil]
+    end
+  end
+
+  describe "horizontal functions" do
+    test "ignores nils when asked to" do
+      df = Explorer.DataFrame.new(a: [1, nil, 3, nil], b: [4.5, 2.0, nil, nil], c: [2, 7, 1, nil])
+      columns = Enum.map(["a", "b", "c"], &Native.expr_column/1)
+
+      assert {:ok, max} = Native.expr_max_horizontal(columns, true)
+      assert {:ok, min} = Native.expr_min_horizontal(columns, true)
+      assert {:ok, sum} = Native.expr_sum_horizontal(columns, true)
+
+      assert eval(df, max) == [4.5, 7.0, 3.0, nil]
+      assert eval(df, min) == [1.0, 2.0, 1.0, nil]
+      assert eval(df, sum) == [7.5, 9.0, 4.0, nil]
+    end
+
+    test "propagates nils otherwise" do
+      df = Explorer.DataFrame.new(a: [1, nil, 3, nil], b: [4.5, 2.0, nil, nil], c: [2, 7, 1, nil])
+      columns = Enum.map(["a", "b", "c"], &Native.expr_column/1)
+
+      assert {:ok, max} = Native.expr_max_horizontal(columns, false)
+      assert {:ok, min} = Native.expr_min_horizontal(columns, false)
+      assert {:ok, sum} = Native.expr_sum_horizontal(columns, false)
+
+      assert eval(df, max) == [4.5, nil, nil, nil]
+      assert eval(df, min) == [1.0, nil, nil, nil]
+      assert eval(df, sum) == [7.5, nil, nil, nil]
+    end
+
+    test "a single expression is returned as is" do
+      df = Explorer.DataFrame.new(a: [1, nil, 3, nil])
+
+      assert {:ok, max} = Native.expr_max_horizontal([Native.expr_column("a")], true)
+      assert eval(df, max) == [1, nil, 3, nil]
+    end
+
+    test "combines many expressions" do
+      df = Explorer.DataFrame.new(for i <- 1..50, do: {"c#{i}", [i, nil, -i]})
+      columns = for i <- 1..50, do: Native.expr_column("c#{i}")
+
+      assert {:ok, max} = Native.expr_max_horizontal(columns, true)
+      assert {:ok, sum} = Native.expr_sum_horizontal(columns, false)
+
+      assert eval(df, max) == [50, nil, -1]
+      assert eval(df, sum) == [1275, nil, -1275]
+    end
+
+    test "errors without expressions" do
+      assert {:error, error} = Native.expr_sum_horizontal([], true)
+      assert error =~ "at least one expression"
+    end
+  end
+
+  describe "bitwise operators" do
+    test "combines integers bit by bit" do
+      df = Explorer.DataFrame.new(left: [6, 6, 6, nil], right: [3, 1, 3, 1])
+      left = Native.expr_column("left")
+      right = Native.expr_column("right")
+
+      assert eval(df, Native.expr_bitwise_and(left, right)) == [2, 0, 2, nil]
+      assert eval(df, Native.expr_bitwise_or(left, right)) == [7, 7, 7, nil]
+      assert eval(df, Native.expr_bitwise_xor(left, right)) == [5, 7, 5, nil]
+    end
+
+    test "shifts bits and yields nil for out of range amounts" do
+      df = Explorer.DataFrame.new(left: [1, 16, -16, 1, 1, nil], right: [3, 2, 2, 64, -1, 1])
+      left = Native.expr_column("left")
+      right = Native.expr_column("right")
+
+      assert eval(df, Native.expr_shift_left(left, right)) == [8, 64, -64, nil, nil, nil]
+      assert eval(df, Native.expr_shift_right(left, right)) == [0, 4, -4, nil, nil, nil]
+    end
+
+    test "rejects non-integer columns" do
+      df = Explorer.DataFrame.new(left: [6.0], right: [3])
+      expr = Native.expr_bitwise_and(Native.expr_column("left"), Native.expr_column("right"))
+
+      assert {:error, error} = eval(df, expr)
+      assert error =~ "bitwise_and expects integers"
+    end
+  end
+
+  describe "integer overflow" do
+    @max_i64 9_223_372_036_854_775_807
+
+    test "checked arithmetic yields nil on overflow" do
+      df = Explorer.DataFrame.new(value: [@max_i64, 1, -@max_i64, nil])
+      value = Native.expr_column("value")
+
+      assert eval(df, Native.expr_checked_add(value, Native.expr_integer(1))) ==
+               [nil, 2, -@max_i64 + 1, nil]
+
+      assert eval(df, Native.expr_checked_multiply(value, Native.expr_integer(2))) ==
+               [nil, 2, nil, nil]
+
+      assert eval(df, Native.expr_checked_add(value, value)) == [nil, 2, nil, nil]
+    end
+
+    test "checked arithmetic rejects floats" do
+      df = Explorer.DataFrame.new(value: [1.5])
+      expr = Native.expr_checked_add(Native.expr_column("value"), Native.expr_integer(1))
+
+      assert {:error, error} = eval(df, expr)
+      assert error =~ "checked_add expects integers"
+    end
+
+    test "checked arithmetic rejects unsigned values above the i64 range" do
+      df = Explorer.DataFrame.new(value: [@max_i64 + 1])
+      expr = Native.expr_checked_add(Native.expr_column("value"), Native.expr_integer(-1))
+
+      assert {:error, error} = eval(df, expr)
+      assert error =~ "checked_add got 9223372036854775808"
+    end
+
+    test "sums that overflow are nil" do
+      df = Explorer.DataFrame.new(group: [1, 1, 2, 2], value: [@max_i64, 1, @max_i64, -1])
+      expr = Native.expr_alias(Native.expr_sum(Native.expr_column("value")), "sum")
+
+      assert agg(df, [expr]) == {:ok, %{"group" => [1, 2], "sum" => [nil, @max_i64 - 1]}}
+    end
+
+    test "sums that overflow midway are nil even if the total fits" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 2, 2], value: [@max_i64, 1, -1, 1, -1])
+      expr = Native.expr_alias(Native.expr_sum(Native.expr_column("value")), "sum")
+
+      assert agg(df, [expr]) == {:ok, %{"group" => [1, 2], "sum" => [nil, 0]}}
+    end
+
+    test "unsigned sums that overflow are nil" do
+      max_u64 = 18_446_744_073_709_551_615
+      df = Explorer.DataFrame.new(group: [1, 1, 2], value: [max_u64, 1, max_u64])
+      expr = Native.expr_alias(Native.expr_sum(Native.expr_column("value")), "sum")
+
+      assert agg(df, [expr]) == {:ok, %{"group" => [1, 2], "sum" => [nil, max_u64]}}
+    end
+  end
+
+  describe "expr_to_binary/1 and expr_from_binary/1" do
+    test "round-trips a nested expression with literals of every dtype" do
+      df =
+        Explorer.DataFrame.new(
+          group: ["a", "a", "b", "b"],
+          value: [1.0, nil, 3.0, 5.0],
+          fallback: [0.5, 9.5, 0.0, 0.0],
+          day: [~D[2022-01-01], ~D[2022-02-01], ~D[2022-03-01], ~D[2022-04-01]],
+          at: [
+            ~N[2022-01-01 10:00:00.000000],
+            ~N[2022-01-01 11:00:00.000000],
+            ~N[2022-01-01 12:00:00.000000],
+            ~N[2022-01-01 13:00:00.000000]
+          ]
+        )
+
+      value = Native.expr_coalesce(Native.expr_column("value"), Native.expr_column("fallback"))
+      group_mean = Native.expr_over(Native.expr_mean(value), [Native.expr_column("group")])
+
+      expr =
+        [
+          Native.expr_gt_eq(value, group_mean),
+          Native.expr_gt(Native.expr_column("day"), Native.expr_date(~D[2022-01-15])),
+          Native.expr_lt(
+            Native.expr_column("at"),
+            Native.expr_datetime(~N[2022-01-01 13:30:00.000000])
+          ),
+          Native.expr_neq(Native.expr_column("group"), Native.expr_string("c")),
+          Native.expr_gt(Native.expr_column("fallback"), Native.expr_float(-1.0)),
+          Native.expr_eq(Native.expr_boolean(true), Native.expr_boolean(true))
+        ]
+        |> Enum.reduce(&Native.expr_binary_and(&2, &1))
+
+      assert {:ok, binary} = Native.expr_to_binary(expr)
+      assert {:ok, decoded} = Native.expr_from_binary(binary)
+
+      {:ok, original} = Native.df_filter_with(df.data, expr, [])
+      {:ok, roundtrip} = Native.df_filter_with(df.data, decoded, [])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(roundtrip)) ==
+               Explorer.DataFrame.to_columns(Shared.create_dataframe(original))
+
+      assert Native.df_shape(roundtrip) == {:ok, {2, 5}}
+    end
+
+    test "errors on expressions that wrap native functions" do
+      expr = Native.expr_str_reverse(Native.expr_column("name"))
+
+      assert {:error, error} = Native.expr_to_binary(expr)
+      assert error =~ "Cannot serialize expression"
+    end
+
+    test "errors on invalid binaries" do
+      assert {:error, error} = Native.expr_from_binary("not an expression")
+      assert error =~ "Cannot deserialize expression"
+    end
+  end
+
+  describe "expr_cast/3" do
+    test "non-strict casts turn failures into nil and strict casts error" do
+      df = Explorer.DataFrame.new(text: ["1", "two", "3", nil])
+
+      assert {:ok, lenient} = Native.expr_cast(Native.expr_column("text"), "integer", false)
+      assert {:ok, strict} = Native.expr_cast(Native.expr_column("text"), "integer", true)
+
+      assert eval(df, lenient) == [1, nil, 3, nil]
+      assert {:error, _error} = eval(df, strict)
+    end
+
+    test "errors on invalid dtypes" do
+      assert {:error, error} = Native.expr_cast(Native.expr_column("text"), "i128", false)
+      assert error =~ "Cannot cast to type i128"
+    end
+  end
+
+  describe "expr_quantiles/3" do
+    test "matches the single quantile path" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1, 1, 1, 1, 2, 2, 2],
+          value: [5, 3, 1, 4, 2, 10, nil, 30]
+        )
+
+      quantiles = [0.0, 0.25, 0.75, 1.0]
+      {:ok, expr} = Native.expr_quantiles(Native.expr_column("value"), quantiles, "nearest")
+
+      assert {:ok, %{"quantiles" => [first, second]}} =
+               agg(df, [Native.expr_alias(expr, "quantiles")])
+
+      singles =
+        for {quantile, index} <- Enum.with_index(quantiles) do
+          single = Native.expr_quantile(Native.expr_column("value"), quantile)
+          Native.expr_alias(single, "q#{index}")
+        end
+
+      {:ok, columns} = agg(df, singles)
+
+      assert first == Enum.map(0..3, &hd(columns["q#{&1}"]))
+      assert second == Enum.map(0..3, &List.last(columns["q#{&1}"]))
+      assert first == [1.0, 2.0, 4.0, 5.0]
+    end
+
+    test "interpolates linearly" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1, 1, 1, 1, 2, 2, 2],
+          value: [5, 3, 1, 4, 2, 10, nil, 30]
+        )
+
+      {:ok, expr} = Native.expr_quantiles(Native.expr_column("value"), [0.1, 0.5], "linear")
+
+      assert agg(df, [Native.expr_alias(expr, "quantiles")]) ==
+               {:ok, %{"group" => [1, 2], "quantiles" => [[1.4, 3.0], [12.0, 20.0]]}}
+    end
+
+    test "results can be used as lists" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1, 1, 1, 1, 2, 2, 2],
+          value: [5, 3, 1, 4, 2, 10, nil, 30]
+        )
+
+      {:ok, expr} = Native.expr_quantiles(Native.expr_column("value"), [0.0, 1.0], "nearest")
+      max = Native.expr_alias(Native.expr_list_get(expr, 1), "max")
+
+      assert agg(df, [max]) == {:ok, %{"group" => [1, 2], "max" => [5.0, 30.0]}}
+    end
+
+    test "returns a single list outside of a groupby" do
+      df = Explorer.DataFrame.new(value: [5, 3, 1, 4, 2, 10, nil, 30])
+      {:ok, expr} = Native.expr_quantiles(Native.expr_column("value"), [0.0, 1.0], "nearest")
+
+      {:ok, polars_df} =
+        Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "quantiles")])
+
+      assert Native.df_dtypes(polars_df) == {:ok, ["i64", "list[f64]"]}
+      assert eval(df, expr) == List.duplicate([1.0, 30.0], 8)
+    end
+
+    test "errors on invalid quantiles" do
+      column = Native.expr_column("value")
+
+      assert {:error, error} = Native.expr_quantiles(column, [], "linear")
+      assert error =~ "at least one quantile"
+
+      assert {:error, error} = Native.expr_quantiles(column, [0.5, 1.5], "linear")
+      assert error =~ "between 0 and 1"
+    end
+  end
+
+  test "expr_value_counts/3 counts in order of appearance or by descending count" do
+    df = Explorer.DataFrame.new(value: ["b", "a", nil, "a", "c", "b", nil, "a"])
+
+    for parallel <- [true, false],
+        {sort, expected} <- [
+          {false, [{"b", 2}, {"a", 3}, {nil, 2}, {"c", 1}]},
+          {true, [{"a", 3}, {"b", 2}, {nil, 2}, {"c", 1}]}
+        ] do
+      counts = Native.expr_value_counts(Native.expr_column("value"), sort, parallel)
+
+      # Each row is taken on its own, so it broadcasts to the height of the frame.
+      rows =
+        for index <- 0..3 do
+          row = Native.expr_alias(Native.expr_take(counts, Native.expr_integer(index)), "row")
+          {:ok, polars_df} = Native.df_with_column_exprs(df.data, [row])
+          {:ok, polars_df} = Native.df_unnest(polars_df, ["row"])
+          columns = polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+          {hd(columns["values"]), hd(columns["counts"])}
+        end
+
+      assert rows == expected
+    end
+  end
+
+  test "expr_filter/2 conditional sums per group match filtering first" do
+    df =
+      Explorer.DataFrame.new(
+        group: [1, 1, 1, 2, 2, 3],
+        x: [1, 2, 3, 4, 5, 6],
+        y: [1, -1, 2, 0, 3, -2]
+      )
+
+    positive_y = Native.expr_gt(Native.expr_column("y"), Native.expr_integer(0))
+    filtered_x = Native.expr_filter(Native.expr_column("x"), positive_y)
+    conditional = Native.expr_alias(Native.expr_sum(filtered_x), "x")
+
+    {:ok, filtered_df} = Native.df_filter_with(df.data, positive_y, [])
+    sum = Native.expr_alias(Native.expr_sum(Native.expr_column("x")), "x")
+    two_step = agg(Shared.create_dataframe(filtered_df), [sum])
+
+    # Group 3 has no matching rows, so its conditional sum is the empty sum
+    # and it is absent from the two-step result.
+    assert agg(df, [conditional]) == {:ok, %{"group" => [1, 2, 3], "x" => [4, 5, 0]}}
+    assert two_step == {:ok, %{"group" => [1, 2], "x" => [4, 5]}}
+  end
+
+  test "expr_min_max_scale/1 scales values to the 0-1 range and constant columns to nil" do
+    scale = Native.expr_min_max_scale(Native.expr_column("value"))
+
+    assert eval(Explorer.DataFrame.new(value: [0, 5, 10, nil]), scale) == [0.0, 0.5, 1.0, nil]
+    assert eval(Explorer.DataFrame.new(value: [-1.0, 1.0, 0.0]), scale) == [0.0, 1.0, 0.5]
+    assert eval(Explorer.DataFrame.new(value: [3, 3, 3]), scale) == [nil, nil, nil]
+  end
+
+  describe "expr_take/2" do
+    test "takes the value with the highest score per group" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1, 1, 2, 2],
+          value: ["a", "b", "c", "d", "e"],
+          score: [3, 9, 1, 2, 8]
+        )
+
+      best_index = Native.expr_arg_max(Native.expr_column("score"))
+      best = Native.expr_take(Native.expr_column("value"), best_index)
+
+      assert agg(df, [Native.expr_alias(Native.expr_first(best), "best")]) ==
+               {:ok, %{"group" => [1, 2], "best" => ["b", "e"]}}
+
+      assert eval(df, Native.expr_over(best, [Native.expr_column("group")])) ==
+               ["b", "b", "b", "e", "e"]
+    end
+
+    test "errors on out of bounds indices" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 2, 2], value: ["a", "b", "c", "d", "e"])
+      negative = Native.expr_take(Native.expr_column("value"), Native.expr_integer(-1))
+      third = Native.expr_take(Native.expr_column("value"), Native.expr_integer(2))
+
+      assert {:error, error} = eval(df, negative)
+      assert error =~ "Index -1 is out of bounds for series of length 5"
+
+      assert {:error, error} = agg(df, [Native.expr_alias(Native.expr_first(third), "taken")])
+      assert error =~ "Index 2 is out of bounds for series of length 2"
+    end
+  end
+
+  test "expr_cumulative_sum_over/3 keeps an independent running total per group" do
+    df =
+      Explorer.DataFrame.new(
+        region: ["north", "south", "north", nil, "south", nil, "north"],
+        sales: [1, 10, 2, 100, 20, 200, 3]
+      )
+
+    sales = Native.expr_column("sales")
+    region = [Native.expr_column("region")]
+
+    assert eval(df, Native.expr_cumulative_sum_over(sales, region, false)) ==
+             [1, 10, 3, 100, 30, 300, 6]
+
+    assert eval(df, Native.expr_cumulative_sum_over(sales, region, true)) ==
+             [6, 30, 5, 300, 20, 200, 3]
+  end
+
+  describe "expr_sort_by/3" do
+    test "picks the latest status per user" do
+      df =
+        Explorer.DataFrame.new(
+          user: [1, 2, 1, 2, 1, 3],
+          status: ["new", "new", "active", "blocked", "idle", "new"],
+          updated_at: [
+            ~N[2022-01-01 00:00:00.000000],
+            ~N[2022-01-02 00:00:00.000000],
+            ~N[2022-01-05 00:00:00.000000],
+            ~N[2022-01-03 00:00:00.000000],
+            ~N[2022-01-04 00:00:00.000000],
+            ~N[2022-01-01 00:00:00.000000]
+          ]
+        )
+
+      by = [Native.expr_column("updated_at")]
+      {:ok, latest} = Native.expr_sort_by(Native.expr_column("status"), by, [true])
+      latest = Native.expr_alias(Native.expr_first(latest), "status")
+
+      {:ok, polars_df} =
+        Native.df_groupby_agg_with(df.data, [Native.expr_column("user")], [latest])
+
+      result = polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+      assert result == %{"user" => [1, 2, 3], "status" => ["active", "blocked", "new"]}
+
+      {:ok, sorted} = Native.df_sort(df.data, ["updated_at"], [true], false, false, [])
+      status = Native.expr_alias(Native.expr_first(Native.expr_column("status")), "status")
+
+      {:ok, baseline} =
+        Native.df_groupby_agg_with(sorted, [Native.expr_column("user")], [status])
+
+      baseline = baseline |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+
+      assert Map.new(Enum.zip(result["user"], result["status"])) ==
+               Map.new(Enum.zip(baseline["user"], baseline["status"]))
+    end
+
+    test "errors when directions don't match the columns" do
+      assert {:error, error} =
+               Native.expr_sort_by(Native.expr_column("status"), [Native.expr_column("a")], [])
+
+      assert error =~ "one direction per column"
+    end
+  end
+
+  describe "expr_gather_every/3" do
+    test "takes every nth value from the offset" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], value: [10, 20, 30, 40, 50])
+
+      for {n, offset, expected} <- [{2, 0, [10, 30, 50]}, {2, 1, [20, 40]}, {3, 4, [50]}] do
+        assert {:ok, expr} = Native.expr_gather_every(Native.expr_column("value"), n, offset)
+        assert agg(df, [expr]) == {:ok, %{"group" => [1], "value" => [expected]}}
+      end
+    end
+
+    test "errors when n is zero" do
+      assert {:error, error} = Native.expr_gather_every(Native.expr_column("value"), 0, 0)
+      assert error =~ "greater than zero"
+    end
+  end
+
+  describe "grouping by expressions" do
+    setup do
+      df =
+        Explorer.DataFrame.new(
+          ts: [
+            ~N[2022-01-01 09:00:00.000000],
+            ~N[2022-01-01 18:00:00.000000],
+            ~N[2022-01-02 09:00:00.000000],
+            ~N[2022-01-01 12:00:00.000000]
+          ],
+          region: ["north", "north", "north", "south"],
+          value: [1, 2, 3, 4]
+        )
+
+      {:ok, day} = Native.expr_cast(Native.expr_column("ts"), "date", true)
+
+      [df: df, day: Native.expr_alias(day, "day")]
+    end
+
+    test "groups by a derived key and a column at once", %{df: df, day: day} do
+      total = Native.expr_alias(Native.expr_sum(Native.expr_column("value")), "total")
+      groups = [day, Native.expr_column("region")]
+
+      assert {:ok, polars_df} = Native.df_groupby_agg_with(df.data, groups, [total])
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() == %{
+               "day" => [~D[2022-01-01], ~D[2022-01-02], ~D[2022-01-01]],
+               "region" => ["north", "north", "south"],
+               "total" => [3, 3, 4]
+             }
+    end
+
+    test "errors on repeated output names", %{df: df, day: day} do
+      value = Native.expr_column("value")
+      clash = Native.expr_alias(Native.expr_sum(value), "day")
+      sum = Native.expr_alias(Native.expr_sum(value), "total")
+      count = Native.expr_alias(Native.expr_count(value), "total")
+
+      assert {:error, error} = Native.df_groupby_agg_with(df.data, [day], [clash])
+      assert error =~ "column day is used both as a group and as an aggregation"
+
+      assert {:error, error} = Native.df_groupby_agg_with(df.data, [day], [sum, count])
+      assert error =~ "column total is produced by more than one aggregation"
+
+      assert {:error, error} = Native.df_groupby_agg_with(df.data, [day, day], [])
+      assert error =~ "column day is used as a group more than once"
+    end
+  end
+
+  test "expr_forward_fill/2 and expr_backward_fill/2 fill up to the limit" do
+    df = Explorer.DataFrame.new(value: [1, nil, nil, 4, nil])
+    value = Native.expr_column("value")
+
+    assert eval(df, Native.expr_forward_fill(value, 1)) == [1, 1, nil, 4, 4]
+    assert eval(df, Native.expr_backward_fill(value, 1)) == [1, nil, 4, 4, nil]
+    assert eval(df, Native.expr_forward_fill(value, nil)) == [1, 1, 1, 4, 4]
+    assert eval(df, Native.expr_backward_fill(value, nil)) == [1, 4, 4, 4, nil]
+  end
+
+  describe "expr_rolling_sum_by/4" do
+    test "sums over a 2-day window" do
+      df =
+        Explorer.DataFrame.new(
+          day: [~D[2022-01-01], ~D[2022-01-02], ~D[2022-01-04], ~D[2022-01-05], ~D[2022-01-05]],
+          value: [1, 2, 3, 4, 5]
+        )
+
+      for {closed, expected} <- [
+            {"right", [1.0, 3.0, 3.0, 12.0, 12.0]},
+            {"both", [1.0, 3.0, 5.0, 12.0, 12.0]},
+            {"left", [0.0, 1.0, 2.0, 3.0, 3.0]}
+          ] do
+        assert {:ok, expr} =
+                 Native.expr_rolling_sum_by(
+                   Native.expr_column("value"),
+                   Native.expr_column("day"),
+                   "2d",
+                   closed
+                 )
+
+        assert eval(df, expr) == expected
+      end
+    end
+
+    test "errors on unsorted indexes and invalid arguments" do
+      df = Explorer.DataFrame.new(day: [~D[2022-01-02], ~D[2022-01-01]], value: [1, 2])
+      value = Native.expr_column("value")
+      day = Native.expr_column("day")
+
+      assert {:ok, expr} = Native.expr_rolling_sum_by(value, day, "2d", "right")
+      assert {:error, error} = eval(df, expr)
+      assert error =~ "must be sorted"
+
+      assert {:error, error} = Native.expr_rolling_sum_by(value, day, "2d", "middle")
+      assert error =~ "Invalid closed window"
+    end
+  end
+
+  describe "expr_interpolate_by/2" do
+    test "weights gaps by the distance between index values" do
+      df = Explorer.DataFrame.new(index: [0, 1, 2, 5, 11, 12], value: [nil, 0, nil, nil, 10, nil])
+      expr = Native.expr_interpolate_by(Native.expr_column("value"), Native.expr_column("index"))
+
+      assert eval(df, expr) == [nil, 0.0, 1.0, 4.0, 10.0, nil]
+    end
+
+    test "follows the index order rather than the row order" do
+      df = Explorer.DataFrame.new(index: [10, 0, 4], value: [5.0, 0.0, nil])
+      expr = Native.expr_interpolate_by(Native.expr_column("value"), Native.expr_column("index"))
+
+      assert eval(df, expr) == [5.0, 0.0, 2.0]
+    end
+  end
+
+  test "expr_mad/1 is not dominated by outliers" do
+    df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1, 1], value: [1, 2, nil, 3, 4, 100])
+    mad = Native.expr_alias(Native.expr_mad(Native.expr_column("value")), "mad")
+
+    # The median is 3, so the deviations are [2, 1, 0, 1, 97].
+    assert agg(df, [mad]) == {:ok, %{"group" => [1], "mad" => [1.0]}}
+  end
+
+  describe "expr_top_k/2 and expr_bottom_k/2" do
+    test "picks the largest and smallest values" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], value: [3, 1, 4, 1, 5])
+      top = Native.expr_alias(Native.expr_top_k(Native.expr_column("value"), 2), "top")
+      bottom = Native.expr_alias(Native.expr_bottom_k(Native.expr_column("value"), 2), "bottom")
+
+      assert agg(df, [top, bottom]) ==
+               {:ok, %{"group" => [1], "top" => [[5, 4]], "bottom" => [[1, 1]]}}
+    end
+
+    test "skips nils and returns everything for large k" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1], value: [3, nil, 4, 1])
+      top = Native.expr_alias(Native.expr_top_k(Native.expr_column("value"), 10), "top")
+      bottom = Native.expr_alias(Native.expr_bottom_k(Native.expr_column("value"), 10), "bottom")
+
+      assert agg(df, [top, bottom]) ==
+               {:ok, %{"group" => [1], "top" => [[4, 3, 1]], "bottom" => [[1, 3, 4]]}}
+    end
+  end
+
+  describe "expr_set_sorted/2" do
+    test "sets the flag without reordering values" do
+      for {values, descending, flag} <- [
+            {[1, 3, 5, 7], false, "ascending"},
+            {[3, 1, 2], true, "descending"}
+          ] do
+        df = Explorer.DataFrame.new(value: values)
+        sorted = Native.expr_set_sorted(Native.expr_column("value"), descending)
+        {:ok, polars_df} = Native.df_with_column_exprs(df.data, [sorted])
+        {:ok, column} = Native.df_column(polars_df, "value")
+
+        assert Native.s_sorted_flag(column) == {:ok, flag}
+        assert Native.s_to_list(column) == {:ok, values}
+      end
+    end
+
+    test "sort-dependent operations still give correct results" do
+      df = Explorer.DataFrame.new(value: [9, 4, 1])
+      sorted = Native.expr_set_sorted(Native.expr_column("value"), true)
+
+      assert eval(df, Native.expr_max(sorted)) == [9, 9, 9]
+      assert eval(df, Native.expr_min(sorted)) == [1, 1, 1]
+    end
+
+    test "rechunking keeps the values" do
+      df = Explorer.DataFrame.new(value: [1, 2, 3])
+
+      assert eval(df, Native.expr_rechunk(Native.expr_column("value"))) == [1, 2, 3]
+    end
+  end
+
+  test "expr_append/2 counts distinct values across two columns" do
+    df =
+      Explorer.DataFrame.new(
+        group: [1, 1, 2],
+        from_account: ["a", "b", "c"],
+        to_account: ["b", "c", "c"]
+      )
+
+    parties =
+      Native.expr_append(Native.expr_column("from_account"), Native.expr_column("to_account"))
+
+    expr = Native.expr_alias(Native.expr_n_distinct(parties), "parties")
+
+    assert agg(df, [expr]) == {:ok, %{"group" => [1, 2], "parties" => [3, 1]}}
+  end
+
+  test "expr_if_else/3 sends nil predicates to the otherwise branch" do
+    df = Explorer.DataFrame.new(value: [3, -2, 0, nil])
+    positive = Native.expr_gt(Native.expr_column("value"), Native.expr_integer(0))
+
+    expr =
+      Native.expr_if_else(positive, Native.expr_string("positive"), Native.expr_string("other"))
+
+    assert eval(df, expr) == ["positive", "other", "other", "other"]
+  end
+
+  describe "expr_divide/3 and expr_remainder/2" do
+    test "returns nil for zero denominators in null mode" do
+      df = Explorer.DataFrame.new(a: [-7, 7, 0, -7], b: [3, 0, 0, -3], c: [2.0, 0.0, 0.0, -2.0])
+      a = Native.expr_column("a")
+
+      assert {:ok, ints} = Native.expr_divide(a, Native.expr_column("b"), "null")
+      assert {:ok, floats} = Native.expr_divide(a, Native.expr_column("c"), "null")
+
+      assert eval(df, ints) == [-7 / 3, nil, nil, 7 / 3]
+      assert eval(df, floats) == [-3.5, nil, nil, 3.5]
+    end
+
+    test "follows floats semantics in infinity mode" do
+      df = Explorer.DataFrame.new(a: [-7, 7, 0, -7], b: [3, 0, 0, -3], c: [2.0, 0.0, 0.0, -2.0])
+      a = Native.expr_column("a")
+
+      assert {:ok, ints} = Native.expr_divide(a, Native.expr_column("b"), "infinity")
+      assert {:ok, floats} = Native.expr_divide(a, Native.expr_column("c"), "infinity")
+
+      assert eval(df, ints) == [-7 / 3, :infinity, :nan, 7 / 3]
+      assert eval(df, floats) == [-3.5, :infinity, :nan, 3.5]
+    end
+
+    test "fails on zero denominators in error mode" do
+      df = Explorer.DataFrame.new(a: [-7, 7, 0, -7], b: [3, 0, 0, -3], c: [2.0, 0.0, 0.0, -2.0])
+      a = Native.expr_column("a")
+
+      assert {:ok, ints} = Native.expr_divide(a, Native.expr_column("b"), "error")
+      assert {:ok, floats} = Native.expr_divide(a, Native.expr_column("c"), "error")
+
+      assert {:error, error} = eval(df, ints)
+      assert error =~ "division by zero"
+
+      assert {:error, error} = eval(df, floats)
+      assert error =~ "division by zero"
+
+      assert eval(Explorer.DataFrame.new(a: [-7, 7], c: [2.0, -2.0]), floats) == [-3.5, -3.5]
+    end
+
+    test "rejects unknown modes" do
+      column = Native.expr_column("a")
+
+      assert {:error, error} = Native.expr_divide(column, column, "floor")
+      assert error =~ "unknown division mode floor"
+    end
+
+    test "remainder keeps the sign of the numerator" do
+      df = Explorer.DataFrame.new(a: [-7, 7, 0, -7], b: [3, 0, 0, -3])
+      expr = Native.expr_remainder(Native.expr_column("a"), Native.expr_column("b"))
+
+      assert eval(df, expr) == [rem(-7, 3), nil, nil, rem(-7, -3)]
+    end
+  end
+
+  describe "expr_replace_values/3" do
+    test "replaces matching values, including nils, and keeps the rest" do
+      df = Explorer.DataFrame.new(col_a: [1, 2, 3, nil, 2])
+      column = Native.expr_column("col_a")
+      old = [Native.expr_integer(1), Native.expr_integer(2)]
+      new = [Native.expr_integer(10), Native.expr_integer(20)]
+
+      assert {:ok, expr} = Native.expr_replace_values(column, old, new)
+      assert eval(df, expr) == [10, 20, 3, nil, 20]
+
+      assert {:ok, expr} =
+               Native.expr_replace_values(column, [Native.expr_nil()], [Native.expr_integer(0)])
+
+      assert eval(df, expr) == [1, 2, 3, 0, 2]
+    end
+
+    test "errors when the lengths differ" do
+      column = Native.expr_column("col_a")
+
+      assert {:error, error} =
+               Native.expr_replace_values(column, [Native.expr_integer(1)], [])
+
+      assert error =~ "same number of old and new values, got 1 and 0"
+    end
+  end
+
+  describe "expr_all/1 and expr_any/1" do
+    test "reduces each group ignoring nils" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1, 2, 2, 3, 3],
+          col_a: [true, nil, true, false, nil, nil]
+        )
+
+      column = Native.expr_column("col_a")
+
+      assert agg(df, [
+               Native.expr_alias(Native.expr_all(column), "all"),
+               Native.expr_alias(Native.expr_any(column), "any")
+             ]) ==
+               {:ok,
+                %{
+                  "group" => [1, 2, 3],
+                  "all" => [true, false, true],
+                  "any" => [true, true, false]
+                }}
+    end
+
+    test "errors on non boolean expressions" do
+      df = Explorer.DataFrame.new(group: [1, 1], col_a: [1, 2])
+      column = Native.expr_column("col_a")
+
+      assert {:error, error} = agg(df, [Native.expr_all(column)])
+      assert error =~ "all expects a boolean expression, got i64"
+    end
+  end
+
+  test "expr_first_non_null/1 and expr_last_non_null/1 skip nils at the edges" do
+    df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 2, 2], col_a: [nil, 2, 3, nil, nil, nil])
+    column = Native.expr_column("col_a")
+
+    assert agg(df, [
+             Native.expr_alias(Native.expr_first_non_null(column), "first"),
+             Native.expr_alias(Native.expr_last_non_null(column), "last")
+           ]) == {:ok, %{"group" => [1, 2], "first" => [2, nil], "last" => [3, nil]}}
+  end
+
+  describe "expr_winsorize/3" do
+    test "clips values at the quantile bounds and keeps nils" do
+      df = Explorer.DataFrame.new(value: [-100, 2, nil, 3, 4, 100])
+      {:ok, expr} = Native.expr_winsorize(Native.expr_column("value"), 0.25, 0.75)
+
+      assert eval(df, expr) == [2.0, 2.0, nil, 3.0, 4.0, 4.0]
+    end
+
+    test "errors on invalid quantiles" do
+      column = Native.expr_column("value")
+
+      assert {:error, error} = Native.expr_winsorize(column, -0.1, 0.9)
+      assert error =~ "must be between 0 and 1, got -0.1"
+
+      assert {:error, error} = Native.expr_winsorize(column, 0.1, 1.5)
+      assert error =~ "must be between 0 and 1, got 1.5"
+
+      assert {:error, error} = Native.expr_winsorize(column, 0.9, 0.1)
+      assert error =~ "lower quantile 0.9 must not be greater than upper quantile 0.1"
+    end
+  end
+
+  test "expr_to_physical/1 exposes the integer representation of temporal columns" do
+    df =
+      Explorer.DataFrame.new(
+        date: [~D[1970-01-02], nil],
+        datetime: [~N[1970-01-01 00:00:01], ~N[1969-12-31 23:59:59]],
+        value: [1.5, nil]
+      )
+
+    assert eval(df, Native.expr_to_physical(Native.expr_column("date"))) == [1, nil]
+
+    assert eval(df, Native.expr_to_physical(Native.expr_column("datetime"))) ==
+             [1_000_000, -1_000_000]
+
+    assert eval(df, Native.expr_to_physical(Native.expr_column("value"))) == [1.5, nil]
+  end
+
+  test "expr_shrink_dtype/1 picks the smallest integer dtype that holds every value" do
+    for {values, dtype} <- [
+          {[-5, nil, 100], "i8"},
+          {[-1000, 1000], "i16"},
+          {[-100_000, 100_000], "i32"},
+          {[-5_000_000_000, 1], "i64"}
+        ] do
+      df = Explorer.DataFrame.new(value: values)
+      expr = Native.expr_shrink_dtype(Native.expr_column("value"))
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [expr])
+
+      assert Native.df_dtypes(polars_df) == {:ok, [dtype]}
+      assert eval(df, expr) == values
+    end
+  end
+
+  test "expr_repeat_by/2 repeats each value by the count in the same row" do
+    df = Explorer.DataFrame.new(value: [1, 2, nil, 4], times: [2, 0, 1, nil])
+    expr = Native.expr_repeat_by(Native.expr_column("value"), Native.expr_column("times"))
+
+    assert eval(df, expr) == [[1, 1], [], [nil], nil]
+  end
+
+  describe "expr_rle/1" do
+    test "collapses consecutive equal values, including nils, into runs" do
+      df = Explorer.DataFrame.new(value: [1, 1, 2, nil, nil, 1])
+      runs = Native.expr_count(Native.expr_rle(Native.expr_column("value")))
+
+      assert eval(df, runs) == List.duplicate(4, 6)
+    end
+
+    test "returns the run lengths and values" do
+      df = Explorer.DataFrame.new(value: ["a", nil, "b"])
+      expr = Native.expr_alias(Native.expr_rle(Native.expr_column("value")), "value")
+
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [expr])
+      {:ok, polars_df} = Native.df_unnest(polars_df, ["value"])
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() == %{
+               "lengths" => [1, 1, 1],
+               "values" => ["a", nil, "b"]
+             }
+    end
+  end
+
+  describe "expr_search_sorted/3" do
+    test "returns the insertion point on either side of equal values" do
+      df = Explorer.DataFrame.new(value: [1, 3, 5, 5, 7])
+
+      for {element, side, index} <- [
+            {5, "left", 2},
+            {5, "right", 4},
+            {4, "left", 2},
+            {0, "left", 0},
+            {8, "right", 5}
+          ] do
+        assert {:ok, expr} =
+                 Native.expr_search_sorted(
+                   Native.expr_column("value"),
+                   Native.expr_integer(element),
+                   side
+                 )
+
+        assert eval(df, expr) == List.duplicate(index, 5)
+      end
+    end
+
+    test "returns 64-bit integers" do
+      df = Explorer.DataFrame.new(value: [1, 3, 5, 5, 7])
+
+      {:ok, expr} =
+        Native.expr_search_sorted(Native.expr_column("value"), Native.expr_integer(5), "left")
+
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "at")])
+      assert Native.df_dtypes(polars_df) == {:ok, ["i64", "i64"]}
+    end
+
+    test "errors on unknown sides" do
+      element = Native.expr_integer(5)
+
+      assert {:error, error} =
+               Native.expr_search_sorted(Native.expr_column("value"), element, "middle")
+
+      assert error =~ "Search side middle not supported"
+    end
+  end
+
+  describe "expr_arg_max/1, expr_arg_min/1 and expr_arg_unique/1" do
+    test "return the index of the first extreme value" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], col_a: [3, 1, 9, 9, 1])
+      column = Native.expr_column("col_a")
+
+      assert agg(df, [
+               Native.expr_alias(Native.expr_arg_max(column), "arg_max"),
+               Native.expr_alias(Native.expr_arg_min(column), "arg_min")
+             ]) == {:ok, %{"group" => [1], "arg_max" => [2], "arg_min" => [1]}}
+    end
+
+    test "return nil for an all nil column" do
+      df =
+        Explorer.DataFrame.new(
+          group: [1, 1],
+          col_a: Explorer.Series.from_list([nil, nil], dtype: :integer)
+        )
+
+      column = Native.expr_column("col_a")
+
+      assert agg(df, [
+               Native.expr_alias(Native.expr_arg_max(column), "arg_max"),
+               Native.expr_alias(Native.expr_arg_min(column), "arg_min")
+             ]) == {:ok, %{"group" => [1], "arg_max" => [nil], "arg_min" => [nil]}}
+    end
+
+    test "expr_arg_unique/1 returns the indices of the first occurrences, nils included" do
+      arg_unique = Native.expr_alias(Native.expr_arg_unique(Native.expr_column("col_a")), "idx")
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1, 1], col_a: [3, 1, 3, 2, 1])
+
+      assert agg(df, [arg_unique]) == {:ok, %{"group" => [1], "idx" => [[0, 1, 3]]}}
+
+      df = Explorer.DataFrame.new(group: [1, 1, 1, 1], col_a: [nil, 1, nil, 1])
+      assert agg(df, [arg_unique]) == {:ok, %{"group" => [1], "idx" => [[0, 1]]}}
+    end
+
+    test "return 64-bit integers" do
+      df = Explorer.DataFrame.new(group: [1, 1, 2], col_a: [3, 1, 9])
+      column = Native.expr_column("col_a")
+
+      {:ok, polars_df} =
+        Native.df_groupby_agg_with(df.data, [Native.expr_column("group")], [
+          Native.expr_alias(Native.expr_arg_max(column), "arg_max"),
+          Native.expr_alias(Native.expr_arg_min(column), "arg_min"),
+          Native.expr_alias(Native.expr_arg_unique(column), "arg_unique")
+        ])
+
+      assert Native.df_dtypes(polars_df) == {:ok, ["i64", "i64", "i64", "list[i64]"]}
+    end
+  end
+
+  # Evaluates the expression with `with_columns` and returns the values of the
+  # resulting column, or the error.
+  defp eval(df, expr) do
+    with {:ok, polars_df} <-
+           Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "out")]) do
+      polars_df
+      |> Shared.create_dataframe()
+      |> Explorer.DataFrame.pull("out")
+      |> Explorer.Series.to_list()
+    end
+  end
+
+  # Aggregates the expressions per value of the "group" column.
+  defp agg(df, exprs) do
+    with {:ok, polars_df} <-
+           Native.df_groupby_agg_with(df.data, [Native.expr_column("group")], exprs) do
+      {:ok, polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()}
+    end
+  end
+
+  # Series.from_list/2 does not build lists, so the column is built natively.
+  defp list_df(name, lists) do
+    items =
+      Enum.map(lists, fn
+        nil -> nil
+        list -> Explorer.Series.from_list(list, dtype: :string).data
+      end)
+
+    {:ok, series} = Native.s_new_list(name, items)
+    {:ok, polars_df} = Native.df_new([series])
+    Shared.create_dataframe(polars_df)
+  end
 end
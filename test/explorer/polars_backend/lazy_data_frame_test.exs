@@ -0,0 +1,121 @@
+defmodule Explorer.PolarsBackend.LazyDataFrameTest do
+  use ExUnit.Case, async: true
+
+  alias Explorer.DataFrame
+  alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
+
+  @moduletag :tmp_dir
+
+  defp write_csv!(path, rows) do
+    Stream.concat(
+      ["id,group,value\n"],
+      Stream.map(1..rows, fn id -> "#{id},#{rem(id, 7)},#{rem(id * 31, 101)}\n" end)
+    )
+    |> Stream.into(File.stream!(path))
+    |> Stream.run()
+  end
+
+  defp filtered_scan(path, infer_schema_length \\ 1000) do
+    {:ok, lf} = Native.lf_scan_csv(path, true, ?,, 0, ["NA"], infer_schema_length, nil)
+    filter = Native.expr_gt(Native.expr_column("value"), Native.expr_integer(50))
+
+    Native.lf_filter(lf, filter)
+  end
+
+  describe "lf_collect_streaming/1" do
+    test "matches the eager result", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "streaming.csv")
+      write_csv!(path, 10_000)
+
+      {:ok, lf} = filtered_scan(path)
+
+      assert {:ok, streaming} = Native.lf_collect_streaming(lf)
+      assert {:ok, eager} = Native.lf_collect(lf)
+
+      streaming = Shared.create_dataframe(streaming)
+      eager = Shared.create_dataframe(eager)
+
+      assert DataFrame.n_rows(streaming) > 0
+      assert DataFrame.to_columns(streaming) == DataFrame.to_columns(eager)
+    end
+
+    test "returns parse errors", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "invalid.csv")
+      File.write!(path, "id,group,value\n1,1,10\n2,2,not a number\n")
+
+      {:ok, lf} = filtered_scan(path, 1)
+
+      assert {:error, error} = Native.lf_collect_streaming(lf)
+      assert error =~ "could not parse line 3 of #{path}"
+    end
+
+    # Generates a ~2GB file, run with `mix test --include integration`.
+    @tag :integration
+    @tag timeout: :infinity
+    test "processes files larger than memory", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "large.csv")
+      write_csv!(path, 120_000_000)
+
+      {:ok, lf} = filtered_scan(path)
+
+      assert {:ok, df} = Native.lf_collect_streaming(lf)
+      assert df |> Shared.create_dataframe() |> DataFrame.n_rows() > 0
+    end
+  end
+
+  describe "scans" do
+    test "lf_scan_csv/7 reads the whole file on collect", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "scan.csv")
+      write_csv!(path, 100)
+
+      {:ok, lf} = Native.lf_scan_csv(path, true, ?,, 0, ["NA"], 1000, nil)
+      assert Native.lf_names(lf) == {:ok, ["id", "group", "value"]}
+
+      assert {:ok, df} = Native.lf_collect(lf)
+      assert df |> Shared.create_dataframe() |> DataFrame.n_rows() == 100
+    end
+
+    test "lf_scan_parquet/1 matches the eager reader", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "scan.parquet")
+      df = DataFrame.new(id: [1, 2, 3], name: ["a", nil, "c"])
+      assert :ok = DataFrame.to_parquet(df, path)
+
+      {:ok, lf} = Native.lf_scan_parquet(path)
+      assert {:ok, scanned} = Native.lf_collect(lf)
+
+      assert scanned |> Shared.create_dataframe() |> DataFrame.to_columns() ==
+               DataFrame.to_columns(df)
+    end
+
+    test "reject remote URLs" do
+      url = "https://example.com/data.parquet"
+
+      assert {:error, error} = Native.lf_scan_parquet(url)
+      assert error =~ "Reading from remote URLs is not supported"
+
+      assert {:error, error} = Native.lf_scan_csv(url, true, ?,, 0, [], 1000, nil)
+      assert error =~ "Reading from remote URLs is not supported"
+    end
+
+    test "lf_filter/2 pushes the predicate into the scan", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "pushdown.csv")
+      write_csv!(path, 100)
+
+      {:ok, lf} = filtered_scan(path)
+
+      assert {:ok, plan} = Native.lf_describe_plan(lf, false)
+      assert plan =~ "FILTER"
+
+      assert {:ok, plan} = Native.lf_describe_plan(lf, true)
+      refute plan =~ "FILTER"
+      assert plan =~ ~s/col("value")/
+
+      assert {:ok, df} = Native.lf_collect(lf)
+      values = df |> Shared.create_dataframe() |> DataFrame.pull("value")
+
+      assert Explorer.Series.size(values) > 0
+      assert values |> Explorer.Series.to_list() |> Enum.all?(&(&1 > 50))
+    end
+  end
+end
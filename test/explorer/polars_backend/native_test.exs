@@ -0,0 +1,795 @@
+defmodule Explorer.PolarsBackend.NativeTest do
+  use ExUnit.Case, async: true
+
+  alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Series
+  alias Explorer.PolarsBackend.Shared
+
+  # NIFs must return error tuples instead of panicking.
+  describe "error propagation" do
+    test "arithmetic on series with mismatched lengths" do
+      left = Series.from_list([1, 2, 3], :integer)
+      right = Series.from_list([1, 2], :integer)
+
+      assert {:error, error} = Native.s_div(left.data, right.data)
+      assert error =~ "same length"
+    end
+
+    test "division plan referencing a missing column" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+      {:ok, expr} =
+        Native.expr_divide(Native.expr_column("a"), Native.expr_column("missing"), "infinity")
+
+      assert {:error, _} = Native.df_with_column_exprs(df.data, [expr])
+    end
+
+    test "out of bounds access" do
+      series = Series.from_list([1, 2, 3], :integer)
+
+      assert {:error, error} = Native.s_get(series.data, 10)
+      assert error =~ "out of bounds"
+
+      assert {:error, _} = Native.s_slice_by_indices(series.data, [0, 10])
+    end
+
+    test "groupby on a missing column" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+
+      assert {:error, _} = Native.df_groups(df.data, ["missing"])
+    end
+
+    test "unknown fill strategy" do
+      assert {:error, error} = Native.expr_fill_missing(Native.expr_column("a"), "unknown")
+      assert error =~ "Unknown fill strategy"
+    end
+
+    test "aggregations on unsupported dtypes" do
+      series = Series.from_list(["a", "b"], :string)
+
+      assert {:error, error} = Native.s_sum(series.data)
+      assert error =~ "sum/1 not implemented"
+    end
+  end
+
+  describe "narrow numeric dtypes" do
+    defp cast_columns(df, dtypes) do
+      exprs =
+        for {name, dtype} <- dtypes do
+          {:ok, expr} = Native.expr_cast(Native.expr_column(name), dtype, true)
+          Native.expr_alias(expr, name)
+        end
+
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, exprs)
+      polars_df
+    end
+
+    @tag :tmp_dir
+    test "are kept through parquet reads", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: Enum.map(1..10_000, &(&1 / 4)), b: Enum.to_list(1..10_000))
+      wide = cast_columns(df, [{"a", "float"}, {"b", "integer"}])
+      narrow = cast_columns(df, [{"a", "f32"}, {"b", "i16"}])
+
+      path = Path.join(tmp_dir, "narrow.parquet")
+      assert :ok = Native.df_write_parquet(narrow, path, nil, nil)
+      assert {:ok, read} = Native.df_read_parquet(path, nil, nil, nil, true)
+
+      assert Native.df_dtypes(read) == {:ok, ["f32", "i16"]}
+
+      {:ok, wide_size} = Native.df_estimated_size(wide)
+      {:ok, narrow_size} = Native.df_estimated_size(read)
+      assert_in_delta narrow_size / wide_size, 0.375, 0.05
+
+      assert read |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               Explorer.DataFrame.to_columns(df)
+    end
+
+    test "are promoted only when mixed with wider types" do
+      df = Explorer.DataFrame.new(a: [1, 2], b: [10, 20])
+      narrow = cast_columns(df, [{"a", "i16"}, {"b", "i8"}])
+
+      a = Native.expr_column("a")
+      sum = Native.expr_alias(Native.expr_add(a, Native.expr_column("b")), "sum")
+      wide = Native.expr_alias(Native.expr_add(a, Native.expr_integer(1)), "wide")
+
+      assert {:ok, polars_df} = Native.df_with_column_exprs(narrow, [sum, wide])
+      assert Native.df_dtypes(polars_df) == {:ok, ["i16", "i8", "i16", "i64"]}
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => [1, 2], "b" => [10, 20], "sum" => [11, 22], "wide" => [2, 3]}
+    end
+  end
+
+  describe "scheduling" do
+    # Generates a large file, run with `mix test --include integration`.
+    @tag :integration
+    @tag :tmp_dir
+    @tag timeout: :infinity
+    test "reading a large CSV does not block other NIF calls", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "large.csv")
+
+      Stream.concat(
+        ["id,value\n"],
+        Stream.map(1..10_000_000, fn id -> "#{id},#{rem(id * 31, 101)}\n" end)
+      )
+      |> Stream.into(File.stream!(path))
+      |> Stream.run()
+
+      series = Series.from_list([1, 2, 3], :integer)
+      read = Task.async(fn -> Explorer.DataFrame.from_csv(path) end)
+      Process.sleep(100)
+
+      {time, {:ok, 3}} = :timer.tc(fn -> Native.s_len(series.data) end)
+
+      assert Task.yield(read, 0) == nil
+      assert time < 50_000
+      assert {:ok, _df} = Task.await(read, :infinity)
+    end
+  end
+
+  describe "shared buffers" do
+    defp rss_bytes do
+      "/proc/self/status"
+      |> File.read!()
+      |> String.split("\n")
+      |> Enum.find_value(fn
+        "VmRSS:" <> kilobytes -> kilobytes |> String.trim_trailing(" kB") |> String.trim()
+        _ -> nil
+      end)
+      |> String.to_integer()
+      |> Kernel.*(1024)
+    end
+
+    # Measures the resident memory of the VM, run with `mix test --include integration`
+    # on Linux.
+    @tag :integration
+    test "read-only operations do not copy the frame" do
+      df = Explorer.DataFrame.new(a: Enum.to_list(1..10_000_000))
+      {:ok, size} = Native.df_size_bytes(df.data)
+      filter = Native.expr_gt(Native.expr_column("a"), Native.expr_integer(5))
+
+      before = rss_bytes()
+
+      results =
+        for _ <- 1..5 do
+          {
+            Native.expr_describe_filter_plan(df.data, filter),
+            Native.df_head(df.data, 9_000_000, []),
+            Native.df_slice(df.data, 1_000_000, 8_000_000)
+          }
+        end
+
+      # A copy of the frame per call would add 10 times its size.
+      assert rss_bytes() - before < size
+      assert length(results) == 5
+    end
+  end
+
+  describe "expression construction" do
+    test "chaining a thousand terms stays fast" do
+      {time, expr} =
+        :timer.tc(fn ->
+          Enum.reduce(1..1_000, Native.expr_column("a"), fn i, acc ->
+            Native.expr_add(acc, Native.expr_integer(i))
+          end)
+        end)
+
+      assert %Explorer.PolarsBackend.Expression{} = expr
+      assert time < 500_000
+    end
+  end
+
+  describe "row hashing" do
+    defp hashes(df, columns \\ nil, seed \\ 42) do
+      {:ok, series} = Native.df_hash_rows(df.data, columns, seed)
+      {:ok, list} = Native.s_to_list(series)
+      list
+    end
+
+    test "identical frames produce identical hashes" do
+      left = Explorer.DataFrame.new(a: [1, 2, nil], b: ["x", nil, "z"])
+      right = Explorer.DataFrame.new(a: [1, 2, nil], b: ["x", nil, "z"])
+
+      assert hashes(left) == hashes(right)
+      assert hashes(left) |> Enum.uniq() |> length() == 3
+    end
+
+    test "changing one cell changes exactly one hash" do
+      before = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+      later = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "changed", "z"])
+
+      changed =
+        Enum.zip(hashes(before), hashes(later))
+        |> Enum.map(fn {left, right} -> left != right end)
+
+      assert changed == [false, true, false]
+      assert hashes(before, ["a"]) == hashes(later, ["a"])
+    end
+
+    test "the same seed reproduces the values" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+
+      assert hashes(df, nil, 7) == hashes(df, nil, 7)
+      assert hashes(df, nil, 7) != hashes(df, nil, 8)
+    end
+
+    # Computed independently from the algorithm described in hashing.rs.
+    test "values are pinned for a given seed" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+
+      assert hashes(df, nil, 7) ==
+               [3_579_100_461_362_009_834, 17_810_642_307_136_413_919, 11_700_210_360_193_840_966]
+    end
+
+    test "floats that compare equal hash the same" do
+      float_hashes = fn binary ->
+        {:ok, series} = Native.s_from_binary(binary, "float")
+        {:ok, polars_df} = Native.df_new([series])
+        {:ok, hashes} = Native.df_hash_rows(polars_df, nil, 7)
+        {:ok, list} = Native.s_to_list(hashes)
+        list
+      end
+
+      zeros = <<0x0000000000000000::little-64, 0x8000000000000000::little-64>>
+      nans = <<0x7FF8000000000000::little-64, 0x7FF8000000000001::little-64>>
+
+      assert float_hashes.(zeros) == [5_004_718_581_846_886_372, 5_004_718_581_846_886_372]
+      assert [nan, nan] = float_hashes.(nans)
+    end
+
+    test "errors without columns" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+
+      assert {:error, error} = Native.df_hash_rows(df.data, [], 7)
+      assert error =~ "hashing rows needs at least one column"
+    end
+
+    test "expr_hash/2 matches hashing a single column" do
+      df = Explorer.DataFrame.new(a: [1, nil, 3])
+      expr = Native.expr_alias(Native.expr_hash(Native.expr_column("a"), 42), "a")
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [expr])
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => hashes(df, ["a"])}
+    end
+  end
+
+  describe "df_rename_many/2" do
+    setup do
+      [df: Explorer.DataFrame.new(a: [1], b: [2], c: [3])]
+    end
+
+    test "swaps column names in one call", %{df: df} do
+      assert {:ok, renamed} = Native.df_rename_many(df.data, [{"a", "b"}, {"b", "a"}])
+      assert Native.df_names(renamed) == {:ok, ["b", "a", "c"]}
+
+      assert renamed |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => [2], "b" => [1], "c" => [3]}
+    end
+
+    test "reports every clashing target", %{df: df} do
+      assert {:error, error} = Native.df_rename_many(df.data, [{"a", "c"}, {"b", "c"}])
+      assert error =~ "duplicated columns: c"
+    end
+
+    test "reports every missing source", %{df: df} do
+      assert {:error, error} = Native.df_rename_many(df.data, [{"y", "b1"}, {"x", "a1"}])
+      assert error =~ "missing columns: x, y"
+    end
+
+    test "rejects renaming a column more than once", %{df: df} do
+      pairs = [{"b", "b1"}, {"a", "a1"}, {"b", "b2"}, {"a", "a2"}, {"c", "c1"}]
+
+      assert {:error, error} = Native.df_rename_many(df.data, pairs)
+      assert error =~ "cannot rename columns more than once: a, b"
+    end
+
+    test "adds a prefix and suffix to every name", %{df: df} do
+      assert {:ok, renamed} = Native.df_rename_with_affix(df.data, "pre_", "_suf")
+      assert Native.df_names(renamed) == {:ok, ["pre_a_suf", "pre_b_suf", "pre_c_suf"]}
+    end
+  end
+
+  describe "df_correlation/3" do
+    setup do
+      df =
+        Explorer.DataFrame.new(
+          a: [1, 2, 3, 4],
+          b: [1.0, 3.0, 2.0, 4.0],
+          c: [1, 10, 100, 1000],
+          d: [5, 5, 5, 5],
+          e: ["w", "x", "y", "z"]
+        )
+
+      [df: df]
+    end
+
+    defp correlation(df, columns, method) do
+      with {:ok, matrix} <- Native.df_correlation(df.data, columns, method) do
+        matrix
+        |> Shared.create_dataframe()
+        |> Explorer.DataFrame.to_columns()
+        |> Map.new(fn
+          {"names", names} -> {"names", names}
+          {name, values} -> {name, Enum.map(values, &(&1 && Float.round(&1, 4)))}
+        end)
+      end
+    end
+
+    test "computes pearson correlations of the numeric columns", %{df: df} do
+      assert correlation(df, nil, "pearson") == %{
+               "names" => ["a", "b", "c", "d"],
+               "a" => [1.0, 0.8, 0.8241, nil],
+               "b" => [0.8, 1.0, 0.7761, nil],
+               "c" => [0.8241, 0.7761, 1.0, nil],
+               "d" => [nil, nil, nil, nil]
+             }
+    end
+
+    test "computes spearman correlations of the given columns", %{df: df} do
+      assert correlation(df, ["a", "b", "c"], "spearman") == %{
+               "names" => ["a", "b", "c"],
+               "a" => [1.0, 0.8, 1.0],
+               "b" => [0.8, 1.0, 0.8],
+               "c" => [1.0, 0.8, 1.0]
+             }
+    end
+
+    test "only pairs rows where both values are present" do
+      df = Explorer.DataFrame.new(a: [1, 2, nil, 3], b: [2, 4, 100, 6])
+
+      assert correlation(df, nil, "pearson") == %{
+               "names" => ["a", "b"],
+               "a" => [1.0, 1.0],
+               "b" => [1.0, 1.0]
+             }
+    end
+
+    test "errors on non-numeric columns and invalid methods", %{df: df} do
+      assert {:error, error} = correlation(df, ["a", "e"], "pearson")
+      assert error =~ "cannot compute correlation of column e"
+
+      assert {:error, error} = correlation(df, nil, "kendall")
+      assert error =~ "Invalid correlation method: kendall"
+    end
+  end
+
+  describe "df_to_rows/2" do
+    setup do
+      df =
+        Explorer.DataFrame.new(
+          int: [1, nil, 3],
+          float: [1.5, :nan, nil],
+          string: ["a", nil, "c"],
+          date: [~D[2022-01-01], nil, ~D[2022-01-03]],
+          datetime: [~N[2022-01-01 00:00:00], ~N[2022-01-02 12:30:00.123456], nil]
+        )
+
+      [df: df]
+    end
+
+    defp zipped_rows(df, atom_keys?) do
+      keys = if atom_keys?, do: Enum.map(df.names, &String.to_atom/1), else: df.names
+
+      df.names
+      |> Enum.map(&(df |> Explorer.DataFrame.pull(&1) |> Explorer.Series.to_list()))
+      |> Enum.zip_with(fn row -> keys |> Enum.zip(row) |> Map.new() end)
+    end
+
+    test "matches zipping the columns", %{df: df} do
+      assert Native.df_to_rows(df.data, false) == {:ok, zipped_rows(df, false)}
+      assert Native.df_to_rows(df.data, true) == {:ok, zipped_rows(df, true)}
+    end
+
+    test "streams the same rows in batches", %{df: df} do
+      assert {:ok, stream} = Native.df_to_rows_stream(df.data, 2)
+
+      batches =
+        Stream.repeatedly(fn -> Native.df_to_rows_stream_next(stream, false) end)
+        |> Enum.take_while(&(&1 != {:ok, nil}))
+        |> Enum.map(fn {:ok, rows} -> rows end)
+
+      assert Enum.map(batches, &length/1) == [2, 1]
+      assert Enum.concat(batches) == zipped_rows(df, false)
+    end
+
+    test "rejects empty batches", %{df: df} do
+      assert {:error, error} = Native.df_to_rows_stream(df.data, 0)
+      assert error =~ "chunk size must be greater than zero"
+    end
+  end
+
+  describe "df_from_rows/4" do
+    defp from_rows(rows, schema \\ nil, strict \\ true) do
+      with {:ok, df} <- Native.df_from_rows(rows, schema, nil, strict) do
+        df = Shared.create_dataframe(df)
+        {df.names, Explorer.DataFrame.to_columns(df)}
+      end
+    end
+
+    test "fills missing keys and nils in ragged rows" do
+      rows = [
+        %{"id" => 1, "name" => "a"},
+        [id: 2, date: ~D[2022-01-02]],
+        %{id: nil, name: "c", score: 1.5},
+        %{"score" => 2}
+      ]
+
+      assert from_rows(rows) ==
+               {["id", "name", "date", "score"],
+                %{
+                  "id" => [1, 2, nil, nil],
+                  "name" => ["a", nil, "c", nil],
+                  "date" => [nil, ~D[2022-01-02], nil, nil],
+                  "score" => [nil, nil, 1.5, 2.0]
+                }}
+    end
+
+    test "puts schema columns first and casts to their types" do
+      rows = [%{a: 1, b: "x"}, %{a: 2, b: "y"}]
+
+      assert {:ok, df} = Native.df_from_rows(rows, [{"b", "category"}, {"c", "u32"}], nil, true)
+      assert Native.df_names(df) == {:ok, ["b", "c", "a"]}
+      assert Native.df_dtypes(df) == {:ok, ["cat", "u32", "i64"]}
+    end
+
+    test "upcasts or errors on conflicting types" do
+      rows = [%{a: 1}, %{a: "two"}, %{a: true}]
+
+      assert {:error, error} = from_rows(rows)
+      assert error =~ "column a has values of conflicting types i64 and str"
+
+      assert from_rows(rows, nil, false) == {["a"], %{"a" => ["1", "two", "true"]}}
+    end
+
+    test "only infers types from the first rows" do
+      rows = [%{a: 1}, %{a: "two"}]
+
+      assert {:error, error} = Native.df_from_rows(rows, nil, 1, true)
+      assert error =~ "cannot convert the value of column a at row 1 to i64"
+
+      assert {:ok, df} = Native.df_from_rows(rows, nil, 1, false)
+      assert df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => [1, nil]}
+    end
+  end
+
+  describe "memory footprint" do
+    defp size_bytes(series) do
+      {:ok, size} = Native.s_size_bytes(series.data)
+      size
+    end
+
+    test "reflects the dtype and the length of series" do
+      floats = Explorer.Series.from_list(Enum.map(1..1_000, &(&1 / 4)), :float)
+      {:ok, f32} = Native.s_cast(floats.data, "f32", true)
+      {:ok, f32_size} = Native.s_size_bytes(f32)
+
+      assert_in_delta f32_size / size_bytes(floats), 0.5, 0.05
+
+      long = Explorer.Series.from_list(Enum.map(1..1_000_000, &(&1 / 4)), :float)
+      assert_in_delta size_bytes(long) / size_bytes(floats), 1_000, 50
+    end
+
+    test "accounts for the bytes of strings" do
+      short = Explorer.Series.from_list(List.duplicate("a", 1_000), :string)
+      long = Explorer.Series.from_list(List.duplicate(String.duplicate("a", 100), 1_000), :string)
+
+      assert size_bytes(long) > size_bytes(short) + 90_000
+    end
+
+    test "counts chunks until the frame is rechunked" do
+      series = Explorer.Series.from_list([1, 2], :integer)
+      {:ok, appended} = Native.s_append(series.data, series.data)
+      {:ok, appended} = Native.s_append(appended, series.data)
+      assert Native.s_n_chunks(appended) == {:ok, 3}
+
+      {:ok, df} = Native.df_new([appended])
+      assert Native.df_n_chunks(df) == {:ok, 3}
+      assert {:ok, size} = Native.df_size_bytes(df)
+      assert Native.df_estimated_size(df) == {:ok, size}
+
+      assert {:ok, rechunked} = Native.df_rechunk(df)
+      assert Native.df_n_chunks(rechunked) == {:ok, 1}
+    end
+  end
+
+  describe "parallelism" do
+    test "joins give the same result with and without parallelism" do
+      left = Explorer.DataFrame.new(a: Enum.to_list(1..1_000), b: Enum.map(1..1_000, &rem(&1, 7)))
+      right = Explorer.DataFrame.new(b: Enum.to_list(0..6), c: Enum.map(0..6, &"c#{&1}"))
+
+      for how <- ["inner", "left", "outer"] do
+        assert {:ok, parallel} =
+                 Native.df_join(left.data, right.data, ["b"], ["b"], how, "_right", true)
+
+        assert {:ok, serial} =
+                 Native.df_join(left.data, right.data, ["b"], ["b"], how, "_right", false)
+
+        assert Shared.create_dataframe(serial) |> Explorer.DataFrame.to_columns() ==
+                 Shared.create_dataframe(parallel) |> Explorer.DataFrame.to_columns()
+      end
+    end
+
+    test "the thread pool size cannot change once started" do
+      threads = Explorer.PolarsBackend.max_threads()
+      assert threads > 0
+
+      assert Explorer.PolarsBackend.set_max_threads(threads) == {:ok, threads}
+
+      variable = System.get_env("POLARS_MAX_THREADS")
+      assert {:error, error} = Explorer.PolarsBackend.set_max_threads(threads + 1)
+      assert error =~ "already started with #{threads} threads"
+      assert System.get_env("POLARS_MAX_THREADS") == variable
+    end
+  end
+
+  describe "df_sort/6" do
+    defp sort(df, by, reverse, nulls_last, maintain_order) do
+      args = [df.data, by, reverse, nulls_last, maintain_order, []]
+
+      with {:ok, sorted} <- apply(Native, :df_sort, args) do
+        sorted |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+      end
+    end
+
+    test "sorts each column in its own direction" do
+      df = Explorer.DataFrame.new(a: [1, 2, 1, 2], b: ["x", "y", "z", "w"])
+
+      assert sort(df, ["a", "b"], [false, true], false, false) ==
+               %{"a" => [1, 1, 2, 2], "b" => ["z", "x", "y", "w"]}
+
+      assert sort(df, ["a", "b"], [true, false], false, false) ==
+               %{"a" => [2, 2, 1, 1], "b" => ["w", "y", "x", "z"]}
+    end
+
+    test "places nils first or last" do
+      df = Explorer.DataFrame.new(a: [2, nil, 1])
+
+      assert sort(df, ["a"], [false], false, false) == %{"a" => [nil, 1, 2]}
+      assert sort(df, ["a"], [false], true, false) == %{"a" => [1, 2, nil]}
+      assert sort(df, ["a"], [true], true, false) == %{"a" => [2, 1, nil]}
+    end
+
+    test "keeps the order of equal keys" do
+      keys = Enum.map(1..1_000, &rem(&1, 3))
+      df = Explorer.DataFrame.new(key: keys, row: Enum.to_list(1..1_000))
+
+      %{"key" => sorted_keys, "row" => rows} = sort(df, ["key"], [true], false, true)
+
+      assert sorted_keys == Enum.sort(keys, :desc)
+
+      for {key, group} <- Enum.group_by(Enum.zip(sorted_keys, rows), &elem(&1, 0)) do
+        rows = Enum.map(group, &elem(&1, 1))
+        assert rows == Enum.sort(rows), "rows with key #{key} were reordered"
+      end
+    end
+
+    test "flags the first column as sorted" do
+      df = Explorer.DataFrame.new(a: [3, 1, 2])
+      {:ok, sorted} = Native.df_sort(df.data, ["a"], [true], false, false, [])
+      {:ok, column} = Native.df_column(sorted, "a")
+
+      assert Native.s_sorted_flag(column) == {:ok, "descending"}
+    end
+
+    test "errors when directions do not match the columns" do
+      df = Explorer.DataFrame.new(a: [1], b: [2])
+
+      assert {:error, error} = sort(df, ["a", "b"], [true], false, false)
+      assert error =~ "expected one direction per column"
+    end
+  end
+
+  describe "df_take/2 and df_mask/2" do
+    setup do
+      [df: Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])]
+    end
+
+    test "take repeats rows for duplicate indices", %{df: df} do
+      assert {:ok, taken} = Native.df_take(df.data, [2, 0, 2])
+
+      assert taken |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => [3, 1, 3], "b" => ["z", "x", "z"]}
+    end
+
+    test "take reports the first out of bounds index", %{df: df} do
+      assert {:error, error} = Native.df_take(df.data, [0, 5, 3])
+      assert error =~ "Index 5 is out of bounds for dataframe of height 3"
+    end
+
+    test "mask drops rows where the mask is nil", %{df: df} do
+      mask = Explorer.Series.from_list([true, nil, true])
+      assert {:ok, masked} = Native.df_mask(df.data, mask.data)
+
+      assert masked |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"a" => [1, 3], "b" => ["x", "z"]}
+    end
+
+    test "mask must match the height", %{df: df} do
+      mask = Explorer.Series.from_list([true])
+
+      assert {:error, error} = Native.df_mask(df.data, mask.data)
+      assert error =~ "mask must have the same length as the dataframe height, got 1 and 3"
+    end
+  end
+
+  describe "xlsx" do
+    @tag :tmp_dir
+    test "round trips strings, floats, dates and nils", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "report.xlsx")
+
+      df =
+        Explorer.DataFrame.new(
+          name: ["alpha", nil, "gamma"],
+          amount: [1.5, 2.0, nil],
+          booked: [~D[2023-03-15], nil, ~D[1999-12-31]]
+        )
+
+      assert :ok = Native.df_to_xlsx(df.data, path, "Report")
+      assert {:ok, read} = Native.df_from_xlsx(path, "Report", 0, true)
+
+      read = Shared.create_dataframe(read)
+
+      assert Explorer.DataFrame.dtypes(read) == %{
+               "name" => :string,
+               "amount" => :float,
+               "booked" => :date
+             }
+
+      assert Explorer.DataFrame.to_columns(read) == Explorer.DataFrame.to_columns(df)
+    end
+
+    @tag :tmp_dir
+    test "round trips datetimes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "datetimes.xlsx")
+      df = Explorer.DataFrame.new(at: [~N[2023-03-15 10:30:00], ~N[2023-03-16 00:00:01]])
+
+      assert :ok = Native.df_to_xlsx(df.data, path, "Sheet1")
+      assert {:ok, read} = Native.df_from_xlsx(path, nil, 0, true)
+
+      assert read |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               Explorer.DataFrame.to_columns(df)
+    end
+
+    @tag :tmp_dir
+    test "reads integers back as floats", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "integers.xlsx")
+      df = Explorer.DataFrame.new(count: [1, nil, 3])
+
+      assert :ok = Native.df_to_xlsx(df.data, path, "Sheet1")
+      assert {:ok, read} = Native.df_from_xlsx(path, nil, 0, true)
+
+      read = Shared.create_dataframe(read)
+      assert Explorer.DataFrame.dtypes(read) == %{"count" => :float}
+      assert Explorer.DataFrame.to_columns(read) == %{"count" => [1.0, nil, 3.0]}
+    end
+
+    test "reads a sheet with rows above the header" do
+      path = Path.expand("../../support/header_offset.xlsx", __DIR__)
+
+      assert {:ok, df} = Native.df_from_xlsx(path, nil, 2, true)
+
+      assert df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() == %{
+               "name" => ["alpha", "beta", "gamma"],
+               "amount" => [1.5, nil, 3.25],
+               "booked" => [~D[2023-03-15], ~D[2023-03-16], nil]
+             }
+    end
+
+    test "lists the available sheets when the sheet does not exist" do
+      path = Path.expand("../../support/header_offset.xlsx", __DIR__)
+
+      assert {:error, error} = Native.df_from_xlsx(path, "Missing", 0, true)
+      assert error =~ "sheet Missing does not exist, available sheets are: Report"
+    end
+  end
+
+  describe "df_partition_by/4" do
+    setup do
+      [df: Explorer.DataFrame.new(key: ["b", "a", "b", "a"], value: [1, 2, 3, 4])]
+    end
+
+    defp partitions(df, maintain_order, include_key) do
+      {:ok, partitions} = Native.df_partition_by(df.data, ["key"], maintain_order, include_key)
+
+      Enum.map(partitions, fn partition ->
+        partition |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+      end)
+    end
+
+    test "keeps groups in order of first appearance", %{df: df} do
+      assert partitions(df, true, true) == [
+               %{"key" => ["b", "b"], "value" => [1, 3]},
+               %{"key" => ["a", "a"], "value" => [2, 4]}
+             ]
+    end
+
+    test "drops the key columns on request", %{df: df} do
+      assert partitions(df, true, false) == [%{"value" => [1, 3]}, %{"value" => [2, 4]}]
+    end
+
+    test "returns the same groups without maintaining order", %{df: df} do
+      assert Enum.sort(partitions(df, false, true)) == Enum.sort(partitions(df, true, true))
+    end
+
+    test "errors on missing columns", %{df: df} do
+      assert {:error, _} = Native.df_partition_by(df.data, ["missing"], true, true)
+    end
+  end
+
+  describe "row indices" do
+    setup do
+      [df: Explorer.DataFrame.new(group: ["a", "b", "a"], value: [10, 20, 30])]
+    end
+
+    test "df_with_row_count/3 prepends an integer index", %{df: df} do
+      assert {:ok, counted} = Native.df_with_row_count(df.data, "row", 5)
+      assert Native.df_names(counted) == {:ok, ["row", "group", "value"]}
+
+      counted = Shared.create_dataframe(counted)
+      assert Explorer.Series.to_list(counted["row"]) == [5, 6, 7]
+      assert Explorer.DataFrame.dtypes(counted)["row"] == :integer
+    end
+
+    test "df_with_row_count/3 rejects existing names", %{df: df} do
+      assert {:error, error} = Native.df_with_row_count(df.data, "value", 0)
+      assert error =~ "Column value already exists"
+    end
+
+    test "expr_row_index/1 counts from the offset", %{df: df} do
+      expr = Native.expr_alias(Native.expr_row_index(1), "index")
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [expr])
+
+      index = polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.pull("index")
+      assert Explorer.Series.to_list(index) == [1, 2, 3]
+    end
+
+    test "expr_over/2 numbers rows within each group", %{df: df} do
+      expr = Native.expr_over(Native.expr_row_index(0), [Native.expr_column("group")])
+      {:ok, polars_df} = Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "index")])
+
+      index = polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.pull("index")
+      assert Explorer.Series.to_list(index) == [0, 0, 1]
+    end
+  end
+
+  describe "arrow c data interface" do
+    test "round trips a dataframe through export and import" do
+      df =
+        Explorer.DataFrame.new(
+          a: [1, nil, 3],
+          b: ["x", "y", nil],
+          c: [1.5, 2.5, 3.5],
+          d: [~D[2023-01-01], nil, ~D[2023-01-03]]
+        )
+
+      assert {:ok, {exported, schema_ptr, [array_ptr]}} = Native.df_export_arrow_c(df.data)
+      assert is_integer(schema_ptr) and is_integer(array_ptr)
+
+      assert {:ok, imported} = Native.df_import_arrow_c(schema_ptr, [array_ptr])
+      imported = Shared.create_dataframe(imported)
+
+      assert Explorer.DataFrame.dtypes(imported) == Explorer.DataFrame.dtypes(df)
+      assert Explorer.DataFrame.to_columns(imported) == Explorer.DataFrame.to_columns(df)
+
+      # The exported structs must stay alive until the import is done.
+      assert is_reference(exported)
+    end
+
+    test "structs can only be imported once" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+
+      assert {:ok, {exported, schema_ptr, array_ptrs}} = Native.df_export_arrow_c(df.data)
+      assert {:ok, _} = Native.df_import_arrow_c(schema_ptr, array_ptrs)
+
+      assert {:error, error} = Native.df_import_arrow_c(schema_ptr, array_ptrs)
+      assert error =~ "Arrow C struct at address #{schema_ptr} was already released"
+      assert is_reference(exported)
+    end
+
+    test "rejects null addresses" do
+      assert {:error, error} = Native.df_import_arrow_c(0, [])
+      assert error =~ "must not be null"
+    end
+  end
+end
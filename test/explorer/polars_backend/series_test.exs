@@ -1,6 +1,8 @@
 defmodule Explorer.PolarsBackend.SeriesTest do
   use ExUnit.Case, async: true
+  alias Explorer.PolarsBackend.Native
   alias Explorer.PolarsBackend.Series
+  alias Explorer.PolarsBackend.Shared
 
   test "from_list/2 of dates" do
     dates = [~D[1643-01-04], ~D[-0030-08-12], ~D[1994-05-01]]
@@ -51,6 +53,170 @@ defmodule Explorer.PolarsBackend.SeriesTest do
     assert Series.from_list(dates, :datetime) |> Series.to_list() == dates
   end
 
+  describe "unsigned integers" do
+    test "from_list/2 round-trips values above the i64 range" do
+      values = [Integer.pow(2, 63) + 1, 0, nil, Integer.pow(2, 64) - 1]
+      series = Series.from_list(values, :integer)
+
+      assert Native.s_dtype(series.data) == {:ok, "u64"}
+      assert Series.to_list(series) == values
+    end
+
+    test "from_list/2 errors when no integer dtype fits the values" do
+      message = ~r/no integer dtype fits values between -1 and 9223372036854775808/
+
+      assert_raise ArgumentError, message, fn ->
+        Series.from_list([-1, nil, Integer.pow(2, 63)], :integer)
+      end
+
+      assert_raise ArgumentError, ~r/no integer dtype fits/, fn ->
+        Series.from_list([Integer.pow(2, 64)], :integer)
+      end
+    end
+
+    test "casts to unsigned dtypes" do
+      series = Series.from_list([1, 2, nil], :integer)
+
+      for dtype <- ["u8", "u16", "u32", "u64"] do
+        assert {:ok, cast} = Native.s_cast(series.data, dtype, true)
+        assert Native.s_dtype(cast) == {:ok, dtype}
+        assert cast |> Shared.create_series() |> Series.to_list() == [1, 2, nil]
+      end
+    end
+
+    test "compare against integer literals" do
+      df = Explorer.DataFrame.new(col_a: [4_000_000_000, 1])
+      {:ok, column} = Native.expr_cast(Native.expr_column("col_a"), "u32", true)
+      gt = Native.expr_alias(Native.expr_gt(column, Native.expr_integer(1)), "gt")
+
+      assert {:ok, polars_df} = Native.df_with_column_exprs(df.data, [gt])
+
+      assert polars_df
+             |> Shared.create_dataframe()
+             |> Explorer.DataFrame.pull("gt")
+             |> Explorer.Series.to_list() == [true, false]
+    end
+
+    test "widen sums in expressions" do
+      df = Explorer.DataFrame.new(group: [1, 1, 1], col_a: [4_000_000_000, 4_000_000_000, 1])
+      {:ok, column} = Native.expr_cast(Native.expr_column("col_a"), "u32", true)
+      sum = Native.expr_alias(Native.expr_sum(column), "sum")
+
+      assert {:ok, polars_df} =
+               Native.df_groupby_agg_with(df.data, [Native.expr_column("group")], [sum])
+
+      assert polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"group" => [1], "sum" => [8_000_000_001]}
+    end
+  end
+
+  describe "categoricals" do
+    setup do
+      values = Enum.map(1..1_000, &Enum.at(["apple", "banana", nil, "cherry"], rem(&1, 4)))
+      df = Explorer.DataFrame.new(fruit: values)
+      {:ok, expr} = Native.expr_cast(Native.expr_column("fruit"), "category", true)
+      {:ok, cat_df} = Native.df_with_column_exprs(df.data, [expr])
+
+      [df: df, cat_df: cat_df, values: values]
+    end
+
+    test "round-trip through to_list", %{cat_df: cat_df, values: values} do
+      assert Native.df_dtypes(cat_df) == {:ok, ["cat"]}
+
+      fruits = cat_df |> Shared.create_dataframe() |> Explorer.DataFrame.pull("fruit")
+      assert Series.to_list(fruits) == values
+
+      series = Series.from_list(["a", nil, "b"], :string)
+      assert {:ok, cat} = Native.s_cast(series.data, "category", true)
+      assert Native.s_get(cat, 2) == {:ok, "b"}
+      assert cat |> Shared.create_series() |> Series.to_list() == ["a", nil, "b"]
+    end
+
+    test "compare against strings", %{cat_df: cat_df} do
+      column = Native.expr_column("fruit")
+      eq = Native.expr_eq(column, Native.expr_string("banana"))
+      is_in = Native.expr_is_in(column, Series.from_list(["apple", "cherry"], :string).data)
+
+      assert {:ok, filtered} = Native.df_filter_with(cat_df, eq, [])
+      assert Native.df_height(filtered) == {:ok, 250}
+
+      assert {:ok, filtered} = Native.df_filter_with(cat_df, is_in, [])
+      assert Native.df_height(filtered) == {:ok, 500}
+    end
+
+    test "group by matches strings", %{df: df, cat_df: cat_df} do
+      group_counts = fn polars_df ->
+        {:ok, grouped} =
+          Native.df_groupby_agg_with(polars_df, [Native.expr_column("fruit")], [
+            Native.expr_alias(Native.expr_count(Native.expr_column("fruit")), "count")
+          ])
+
+        grouped
+        |> Shared.create_dataframe()
+        |> Explorer.DataFrame.to_rows()
+        |> Enum.sort_by(& &1["fruit"])
+      end
+
+      assert group_counts.(cat_df) == group_counts.(df.data)
+    end
+
+    test "use less memory than strings", %{df: df, cat_df: cat_df} do
+      {:ok, string_size} = Native.df_estimated_size(df.data)
+      {:ok, cat_size} = Native.df_estimated_size(cat_df)
+
+      assert cat_size < string_size
+    end
+  end
+
+  describe "binaries" do
+    @invalid_utf8 <<0xFF, 0xFE, 0x00>>
+
+    test "from_list/2 round-trips invalid UTF-8" do
+      values = [@invalid_utf8, nil, "", "valid"]
+      series = Series.from_list(values, :binary)
+
+      assert Native.s_dtype(series.data) == {:ok, "binary"}
+      assert Series.to_list(series) == values
+      assert Native.s_get(series.data, 0) == {:ok, @invalid_utf8}
+    end
+
+    test "compare with literals" do
+      hashes = Explorer.Series.from_list([@invalid_utf8, nil, "ok"], dtype: :binary)
+      df = Explorer.DataFrame.new(hash: hashes)
+      column = Native.expr_column("hash")
+      values = Series.from_list(["ok"], :binary)
+
+      exprs = [
+        Native.expr_alias(Native.expr_eq(column, Native.expr_binary(@invalid_utf8)), "eq"),
+        Native.expr_alias(Native.expr_is_nil(column), "is_nil"),
+        Native.expr_alias(Native.expr_is_in(column, values.data), "is_in")
+      ]
+
+      assert {:ok, polars_df} = Native.df_with_column_exprs(df.data, exprs)
+      columns = polars_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+
+      assert Enum.at(columns["eq"], 0) == true
+      assert Enum.at(columns["eq"], 2) == false
+      assert columns["is_nil"] == [false, true, false]
+      assert columns["is_in"] == [false, false, true]
+    end
+
+    @tag :tmp_dir
+    test "are preserved through parquet", %{tmp_dir: tmp_dir} do
+      hashes = Explorer.Series.from_list([@invalid_utf8, nil], dtype: :binary)
+      df = Explorer.DataFrame.new(hash: hashes)
+      path = Path.join(tmp_dir, "binary.parquet")
+
+      assert :ok = Native.df_write_parquet(df.data, path, nil, nil)
+      assert {:ok, read} = Native.df_read_parquet(path, nil, nil, nil, true)
+
+      assert Native.df_dtypes(read) == {:ok, ["binary"]}
+
+      assert read |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+               %{"hash" => [@invalid_utf8, nil]}
+    end
+  end
+
   test "to_enum/1 returns a valid enumerable" do
     enum1 =
       [1, 2, 3, 4]
@@ -73,4 +239,228 @@ defmodule Explorer.PolarsBackend.SeriesTest do
     assert Enum.slice(enum1, 1..2) == [2, 3]
     assert Enum.slice(enum2, 1..2) == ["b", "c"]
   end
+
+  describe "binary conversion" do
+    test "round-trips floats" do
+      series = Series.from_list([1.0, -2.5, 3.25], :float)
+
+      assert {:ok, binary} = Native.s_to_binary(series.data)
+      assert byte_size(binary) == 3 * 8
+      assert binary == <<1.0::float-64-native, -2.5::float-64-native, 3.25::float-64-native>>
+
+      assert {:ok, series} = Native.s_from_binary(binary, "float")
+      assert series |> Shared.create_series() |> Series.to_list() == [1.0, -2.5, 3.25]
+    end
+
+    test "round-trips integers" do
+      series = Series.from_list([1, -2, 3, 4], :integer)
+
+      assert {:ok, binary} = Native.s_to_binary(series.data)
+      assert byte_size(binary) == 4 * 8
+
+      assert {:ok, series} = Native.s_from_binary(binary, "integer")
+      assert series |> Shared.create_series() |> Series.to_list() == [1, -2, 3, 4]
+    end
+
+    test "round-trips 32-bit integers" do
+      for {dtype, values} <- [{"i32", [1, -2, 3]}, {"u32", [1, 2, 4_000_000_000]}] do
+        binary = for value <- values, into: <<>>, do: <<value::32-native>>
+        {:ok, expr} = Native.expr_cast(Native.expr_column("a"), dtype, true)
+        df = Explorer.DataFrame.new(a: values)
+        {:ok, polars_df} = Native.df_with_column_exprs(df.data, [Native.expr_alias(expr, "a")])
+        {:ok, column} = Native.df_column(polars_df, "a")
+
+        assert Native.s_to_binary(column) == {:ok, binary}
+
+        assert {:ok, series} = Native.s_from_binary(binary, dtype)
+        assert Native.s_dtype(series) == {:ok, dtype}
+        assert Native.s_to_list(series) == {:ok, values}
+      end
+    end
+
+    test "round-trips booleans as one byte per value" do
+      series = Series.from_list([true, false, true], :boolean)
+
+      assert {:ok, <<1, 0, 1>> = binary} = Native.s_to_binary(series.data)
+
+      assert {:ok, series} = Native.s_from_binary(binary, "boolean")
+      assert series |> Shared.create_series() |> Series.to_list() == [true, false, true]
+    end
+
+    test "errors with nil values" do
+      series = Series.from_list([1.0, nil, 3.0], :float)
+
+      assert {:error, _} = Native.s_to_binary(series.data)
+    end
+
+    test "errors when the binary does not match the element size" do
+      assert {:error, _} = Native.s_from_binary(<<1, 2, 3>>, "integer")
+    end
+  end
+
+  describe "s_mask/2" do
+    test "keeps the values where the mask is true" do
+      series = Series.from_list([1, 2, 3, 4, 5], :integer)
+      mask = Series.from_list([true, false, nil, true, true], :boolean)
+
+      assert {:ok, masked} = Native.s_mask(series.data, mask.data)
+      assert Native.s_to_list(masked) == {:ok, [1, 4, 5]}
+      assert Native.s_len(masked) == {:ok, 3}
+    end
+
+    test "errors when the lengths differ" do
+      series = Series.from_list([1, 2, 3], :integer)
+      mask = Series.from_list([true], :boolean)
+
+      assert {:error, error} = Native.s_mask(series.data, mask.data)
+      assert error =~ "same length"
+    end
+  end
+
+  describe "s_take/2" do
+    test "gathers values by index" do
+      series = Series.from_list(["a", "b", "c"], :string)
+
+      assert {:ok, taken} = Native.s_take(series.data, [2, 0, 0])
+      assert Native.s_to_list(taken) == {:ok, ["c", "a", "a"]}
+    end
+
+    test "errors on out of bounds and negative indices" do
+      series = Series.from_list(["a", "b", "c"], :string)
+
+      assert {:error, error} = Native.s_take(series.data, [0, 3])
+      assert error =~ "Index 3 is out of bounds for series of length 3"
+
+      assert {:error, error} = Native.s_take(series.data, [-1])
+      assert error =~ "Index -1 is out of bounds"
+    end
+  end
+
+  describe "s_value_counts/3" do
+    setup do
+      [series: Series.from_list(["b", "a", nil, "a", "c", "b", nil, "a"], :string)]
+    end
+
+    test "counts values in order of appearance", %{series: series} do
+      for parallel <- [true, false] do
+        assert {:ok, counts} = Native.s_value_counts(series.data, false, parallel)
+
+        assert counts |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+                 %{"values" => ["b", "a", nil, "c"], "counts" => [2, 3, 2, 1]}
+      end
+    end
+
+    test "sorts by descending count and keeps ties stable", %{series: series} do
+      for parallel <- [true, false] do
+        assert {:ok, counts} = Native.s_value_counts(series.data, true, parallel)
+
+        assert counts |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() ==
+                 %{"values" => ["a", "b", nil, "c"], "counts" => [3, 2, 2, 1]}
+      end
+    end
+  end
+
+  describe "s_to_dummies/3" do
+    setup do
+      series = Series.from_list(["red", "green", nil, "blue", "red"], :string)
+      {:ok, series} = Native.s_rename(series.data, "color")
+
+      [series: series]
+    end
+
+    defp to_dummies(series, drop_first, include_null) do
+      {:ok, df} = Native.s_to_dummies(series, drop_first, include_null)
+      df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns()
+    end
+
+    test "produces one indicator column per category", %{series: series} do
+      assert to_dummies(series, false, false) == %{
+               "color_blue" => [false, false, false, true, false],
+               "color_green" => [false, true, false, false, false],
+               "color_red" => [true, false, false, false, true]
+             }
+    end
+
+    test "drops the first category and includes nulls", %{series: series} do
+      assert to_dummies(series, true, true) == %{
+               "color_green" => [false, true, false, false, false],
+               "color_red" => [true, false, false, false, true],
+               "color_null" => [false, false, true, false, false]
+             }
+    end
+  end
+
+  describe "s_concat/1" do
+    test "concatenates chunked series into a single chunk" do
+      ints = Series.from_list([1, 2], :integer)
+      {:ok, chunked} = Native.s_append(ints.data, ints.data)
+      floats = Series.from_list([0.5], :float)
+
+      assert {:ok, concat} = Native.s_concat([chunked, floats.data])
+      assert Native.s_to_list(concat) == {:ok, [1.0, 2.0, 1.0, 2.0, 0.5]}
+      assert Native.s_dtype(concat) == {:ok, "f64"}
+      assert Native.s_n_chunks(concat) == {:ok, 1}
+    end
+
+    test "errors on incompatible dtypes" do
+      ints = Series.from_list([1], :integer)
+      strings = Series.from_list(["a"], :string)
+
+      assert {:error, error} = Native.s_concat([ints.data, strings.data])
+      assert error =~ "incompatible dtypes: i64, str"
+    end
+  end
+
+  describe "s_all/1 and s_any/1" do
+    test "ignore nils" do
+      s = Series.from_list([true, nil], :boolean)
+
+      assert Native.s_all(s.data) == {:ok, true}
+      assert Native.s_any(s.data) == {:ok, true}
+    end
+
+    test "handle empty series" do
+      s = Series.from_list([], :boolean)
+
+      assert Native.s_all(s.data) == {:ok, true}
+      assert Native.s_any(s.data) == {:ok, false}
+    end
+
+    test "error on non boolean series" do
+      s = Series.from_list([1, 2], :integer)
+
+      assert {:error, "any expects a boolean series, got i64"} = Native.s_any(s.data)
+    end
+  end
+
+  describe "s_new_list/2" do
+    test "round-trips nested lists" do
+      items = [Series.from_list(["a"], :string).data, Series.from_list([], :string).data]
+      {:ok, inner} = Native.s_new_list("inner", items)
+      {:ok, nested} = Native.s_new_list("nested", [inner, nil])
+
+      assert Native.s_to_list(nested) == {:ok, [[["a"], []], nil]}
+      assert Native.s_dtype(nested) == {:ok, "list[list[str]]"}
+    end
+
+    test "casts numeric rows to their supertype" do
+      items = [
+        Series.from_list([1, 2], :integer).data,
+        Series.from_list([1.5], :float).data,
+        Series.from_list([], :integer).data,
+        nil
+      ]
+
+      assert {:ok, list} = Native.s_new_list("numbers", items)
+      assert Native.s_dtype(list) == {:ok, "list[f64]"}
+      assert Native.s_to_list(list) == {:ok, [[1.0, 2.0], [1.5], [], nil]}
+    end
+
+    test "errors on rows of different non-numeric dtypes" do
+      items = [Series.from_list([1], :integer).data, Series.from_list(["a"], :string).data]
+
+      assert {:error, error} = Native.s_new_list("mixed", items)
+      assert error =~ "cannot build a list series out of series of dtypes i64 and str"
+    end
+  end
 end
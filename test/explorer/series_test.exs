@@ -55,6 +55,49 @@ defmodule Explorer.SeriesTest do
     assert Series.to_list(s2) == [1, 4, 3]
   end
 
+  describe "narrow dtypes" do
+    test "from_list/2 builds and reports narrow dtypes" do
+      for dtype <- [{:s, 8}, {:s, 16}, {:s, 32}, {:u, 8}, {:u, 16}, {:u, 32}, {:u, 64}] do
+        s = Series.from_list([1, nil, 100], dtype: dtype)
+
+        assert Series.dtype(s) == dtype
+        assert Series.to_list(s) == [1, nil, 100]
+      end
+
+      s = Series.from_list([1.5, nil], dtype: {:f, 32})
+      assert Series.dtype(s) == {:f, 32}
+      assert Series.to_list(s) == [1.5, nil]
+    end
+
+    test "from_list/2 raises when values do not fit the dtype" do
+      assert_raise ArgumentError, fn -> Series.from_list([1_000], dtype: {:s, 8}) end
+      assert_raise ArgumentError, fn -> Series.from_list([-1], dtype: {:u, 32}) end
+    end
+
+    test "distinguishes f32 from f64 and keeps the dtype through arithmetic" do
+      f32 = Series.from_list([1.5, 2.5], dtype: {:f, 32})
+      f64 = Series.cast(f32, :float)
+
+      assert Series.dtype(f64) == :float
+      assert f32 |> Series.add(f32) |> Series.dtype() == {:f, 32}
+      assert f32 |> Series.add(f64) |> Series.dtype() == :float
+      assert f32 |> Series.add(f32) |> Series.to_list() == [3.0, 5.0]
+    end
+
+    test "are named in inspect" do
+      s = Series.from_list([1, 2], dtype: {:s, 16})
+
+      assert inspect(s) ==
+               """
+               #Explorer.Series<
+                 s16[2]
+                 [1, 2]
+               >
+               """
+               |> String.trim_trailing()
+    end
+  end
+
   describe "equal/2" do
     test "compare boolean series" do
       s1 = Series.from_list([true, false, true])
@@ -475,4 +518,66 @@ defmodule Explorer.SeriesTest do
       assert Series.to_list(s2) == [1, 1, 1]
     end
   end
+
+  describe "cast/3" do
+    test "raises naming the accepted dtypes on an unknown dtype" do
+      s = Series.from_list([1, 2, 3])
+
+      assert_raise ArgumentError, ~r/int64, expected one of: float, integer/, fn ->
+        Series.cast(s, :int64)
+      end
+    end
+
+    test "non-strict casts turn invalid values into nil" do
+      s = Series.from_list(["1", "a", "3"])
+
+      assert s |> Series.cast(:integer) |> Series.to_list() == [1, nil, 3]
+    end
+
+    test "strict casts raise on invalid values" do
+      s = Series.from_list(["1", "a", "3"])
+
+      assert_raise ArgumentError, fn -> Series.cast(s, :integer, strict: true) end
+    end
+
+    test "strict casts inside mutate_with raise on invalid values" do
+      df = Explorer.DataFrame.new(a: ["1", "a", "3"])
+
+      assert_raise RuntimeError, fn ->
+        Explorer.DataFrame.mutate_with(df, fn ldf ->
+          [b: Series.cast(ldf["a"], :integer, strict: true)]
+        end)
+      end
+    end
+
+    test "casts with an unknown dtype inside mutate_with raise" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+
+      assert_raise ArgumentError, ~r/int64/, fn ->
+        Explorer.DataFrame.mutate_with(df, fn ldf -> [b: Series.cast(ldf["a"], :int64)] end)
+      end
+    end
+  end
+
+  describe "sum/1" do
+    @max_i64 9_223_372_036_854_775_807
+
+    test "sums that overflow are nil" do
+      assert Series.sum(Series.from_list([@max_i64, 1])) == nil
+      assert Series.sum(Series.from_list([@max_i64, -1])) == @max_i64 - 1
+    end
+
+    test "sums that overflow midway are nil even if the total fits" do
+      assert Series.sum(Series.from_list([@max_i64, 1, -1])) == nil
+    end
+  end
+
+  describe "is_duplicated/1 and nil_count/1" do
+    test "treat nils as a value" do
+      s = Series.from_list([1, 2, 1, nil, nil])
+
+      assert s |> Series.is_duplicated() |> Series.to_list() == [true, false, true, true, true]
+      assert Series.nil_count(s) == 2
+    end
+  end
 end